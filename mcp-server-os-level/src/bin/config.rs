@@ -0,0 +1,412 @@
+use std::{collections::{HashMap, HashSet}, env, fs, net::{SocketAddr, ToSocketAddrs}, str::FromStr, time::Duration};
+use tracing::{info, warn};
+
+use crate::types::Interactability;
+
+// ================ Config ================
+
+// Default cap on listed elements when a request doesn't pass max_elements
+const DEFAULT_MAX_ELEMENTS: usize = 500;
+
+// Default pause between focusing an element and sending input to it
+const DEFAULT_INTER_ACTION_DELAY_MS: u64 = 100;
+
+// Default wait between an action and listing the app again, see ServerConfig::refresh_delay_ms
+const DEFAULT_REFRESH_DELAY_MS: u64 = 500;
+
+// Default retries when a just-opened app lists no elements, and the wait before the first one
+const DEFAULT_OPEN_APP_RETRIES: u32 = 1;
+const DEFAULT_OPEN_APP_RETRY_DELAY_MS: u64 = 1000;
+
+// Tool that runs arbitrary AppleScript, only exposed when ENABLE_RAW_APPLESCRIPT is set
+pub const RAW_APPLESCRIPT_TOOL: &str = "runAppleScript";
+
+// Default time a listing stays usable for by-index operations
+const DEFAULT_ELEMENT_CACHE_TTL_SECONDS: u64 = 30;
+
+// Default age after which cached element handles are dropped
+const DEFAULT_CACHE_IDLE_TIMEOUT_SECONDS: u64 = 300;
+
+// Default listing size above which a summary is returned instead of the elements
+const DEFAULT_SUMMARIZE_ABOVE_ELEMENTS: usize = 1000;
+
+// Default number of prominent elements a summary lists
+const DEFAULT_SUMMARY_PROMINENT_ELEMENTS: usize = 25;
+
+// Default HTTP bind address; localhost only, so other machines can't drive this one
+const DEFAULT_HOST: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 8080;
+
+/// Address the HTTP server binds to: `--host`/`--port` (as `--port 9000` or `--port=9000`),
+/// then `HOST`/`PORT`, then 127.0.0.1:8080
+pub fn bind_address(args: &[String]) -> anyhow::Result<SocketAddr> {
+    let host = cli_flag(args, "--host").or_else(|| env::var("HOST").ok());
+    let port = cli_flag(args, "--port").or_else(|| env::var("PORT").ok());
+    parse_bind_address(host.as_deref(), port.as_deref())
+}
+
+fn parse_bind_address(host: Option<&str>, port: Option<&str>) -> anyhow::Result<SocketAddr> {
+    let host = host.map(str::trim).filter(|host| !host.is_empty()).unwrap_or(DEFAULT_HOST);
+    let port = match port.map(str::trim).filter(|port| !port.is_empty()) {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| anyhow::anyhow!("invalid port {:?}: expected a number from 0 to 65535", port))?,
+        None => DEFAULT_PORT,
+    };
+    // Accepts hostnames like "localhost" as well as IP addresses (IPv6 with or without brackets)
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (host, port)
+        .to_socket_addrs()
+        .map_err(|e| anyhow::anyhow!("invalid host {:?}: {}", host, e))?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("host {:?} resolves to no address", host))
+}
+
+// Value of "--name value" or "--name=value"
+fn cli_flag(args: &[String], name: &str) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Server settings read once from the environment at startup
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Cap applied when a list request omits `max_elements` (`DEFAULT_MAX_ELEMENTS`, 0 = unbounded)
+    pub default_max_elements: Option<usize>,
+    /// Per-app role overrides for the listing heuristics, keyed by lowercase app name
+    /// (`INTERACTABILITY_OVERRIDES_FILE`, a JSON file like `{"Slack": {"AXGroup": "definite"}}`)
+    pub interactability_overrides: HashMap<String, HashMap<String, Interactability>>,
+    /// Tools to expose (`ENABLED_TOOLS`, comma-separated tool names); `None` exposes all
+    pub enabled_tools: Option<HashSet<String>>,
+    /// Tools to hide even if enabled (`DISABLED_TOOLS`, comma-separated tool names)
+    pub disabled_tools: HashSet<String>,
+    /// Pause between steps of a compound action, e.g. the focus-click before typing or
+    /// pressing a key (`INTER_ACTION_DELAY_MS`, overridable per request). Raise it to
+    /// 250-400ms for apps with long focus/popover animations (Electron apps, Safari's
+    /// address bar) when input lands on the pre-animation state; lower it for native
+    /// forms to speed up scripted runs.
+    pub inter_action_delay_ms: u64,
+    /// Wait between an action and the listing returned with it (`REFRESH_DELAY_MS`,
+    /// overridable per request with `delay_ms`). Too short and the listing shows the UI
+    /// mid-transition; lower it for snappy native apps, raise it for heavy ones.
+    pub refresh_delay_ms: u64,
+    /// Gzip/deflate responses for clients that send `Accept-Encoding` (`RESPONSE_COMPRESSION`,
+    /// default true). Element listings are repetitive JSON and typically shrink ~85-90%.
+    pub response_compression: bool,
+    /// Drop cached element handles once the listing behind them is this old
+    /// (`CACHE_IDLE_TIMEOUT_SECONDS`, 0 keeps them until overwritten). Unlike the
+    /// index TTL this is about memory: expired entries still pin live AX references.
+    pub cache_idle_timeout: Option<Duration>,
+    /// How long a listing stays usable for by-index operations before they answer "cache entry
+    /// expired" (`ELEMENT_CACHE_TTL_SECONDS`); raise it for agents that think long between steps
+    pub element_cache_ttl: Duration,
+    /// How often openApplication re-lists an app that came up without elements
+    /// (`OPEN_APP_RETRIES`, overridable per request). Slow launchers like Xcode or
+    /// heavy Electron apps can need 3-4.
+    pub open_app_retries: u32,
+    /// Wait before the first of those retries, doubling for each further one
+    /// (`OPEN_APP_RETRY_DELAY_MS`, overridable per request)
+    pub open_app_retry_delay_ms: u64,
+    /// Expose runAppleScript (`ENABLE_RAW_APPLESCRIPT`, default false). Anyone who can reach
+    /// the server can then run any script with this process's Automation and Accessibility
+    /// permissions (read mail, send messages, run shell commands via `do shell script`), so
+    /// only turn it on for servers bound to localhost with trusted clients.
+    pub enable_raw_applescript: bool,
+    /// Browser for openUrl requests that don't name one (`DEFAULT_BROWSER`, e.g. "Firefox"),
+    /// falling back to the system default if it isn't installed; `None` uses the system default
+    pub default_browser: Option<String>,
+    /// Listings with more elements than this come back as a summary (counts by role, windows
+    /// with their element counts, the most prominent elements) instead of the elements
+    /// (`SUMMARIZE_ABOVE_ELEMENTS`, overridable per request, 0 = never summarize)
+    pub summarize_above_elements: Option<usize>,
+    /// How many of the largest interactable elements a summary includes (`SUMMARY_PROMINENT_ELEMENTS`)
+    pub summary_prominent_elements: usize,
+    /// Safe mode: only send input (clicks, typing, key presses, raw mouse/keyboard events) to these
+    /// apps, refusing everything else with APP_NOT_ALLOWED (`SAFE_MODE_ALLOWED_APPS`, comma-separated
+    /// display names or bundle ids, matched case-insensitively). Reading (listings, screenshots,
+    /// text) stays allowed for every app. Tools that can reach any app (runAppleScript,
+    /// launchViaSpotlight) are refused outright. `None` turns safe mode off.
+    pub safe_mode_allowed_apps: Option<HashSet<String>>,
+    /// Where resetInputState parks the cursor unless the request says otherwise
+    /// (`INPUT_HOME_POSITION`, "x,y" in screen points); `None` leaves the cursor where it is
+    pub input_home_position: Option<(f64, f64)>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            default_max_elements: Some(DEFAULT_MAX_ELEMENTS),
+            interactability_overrides: HashMap::new(),
+            enabled_tools: None,
+            disabled_tools: HashSet::new(),
+            inter_action_delay_ms: DEFAULT_INTER_ACTION_DELAY_MS,
+            refresh_delay_ms: DEFAULT_REFRESH_DELAY_MS,
+            response_compression: true,
+            cache_idle_timeout: Some(Duration::from_secs(DEFAULT_CACHE_IDLE_TIMEOUT_SECONDS)),
+            element_cache_ttl: Duration::from_secs(DEFAULT_ELEMENT_CACHE_TTL_SECONDS),
+            open_app_retries: DEFAULT_OPEN_APP_RETRIES,
+            open_app_retry_delay_ms: DEFAULT_OPEN_APP_RETRY_DELAY_MS,
+            enable_raw_applescript: false,
+            default_browser: None,
+            summarize_above_elements: Some(DEFAULT_SUMMARIZE_ABOVE_ELEMENTS),
+            summary_prominent_elements: DEFAULT_SUMMARY_PROMINENT_ELEMENTS,
+            safe_mode_allowed_apps: None,
+            input_home_position: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Some(max) = env_parse::<usize>("DEFAULT_MAX_ELEMENTS") {
+            config.default_max_elements = if max == 0 { None } else { Some(max) };
+        }
+
+        if let Ok(path) = env::var("INTERACTABILITY_OVERRIDES_FILE") {
+            config.interactability_overrides = load_interactability_overrides(&path);
+        }
+
+        config.enabled_tools = env_list("ENABLED_TOOLS");
+        config.disabled_tools = env_list("DISABLED_TOOLS").unwrap_or_default();
+
+        if let Some(delay) = env_parse::<u64>("INTER_ACTION_DELAY_MS") {
+            config.inter_action_delay_ms = delay;
+        }
+
+        if let Some(delay) = env_parse::<u64>("REFRESH_DELAY_MS") {
+            config.refresh_delay_ms = delay;
+        }
+
+        if let Some(compress) = env_parse::<bool>("RESPONSE_COMPRESSION") {
+            config.response_compression = compress;
+        }
+
+        if let Some(seconds) = env_parse::<u64>("CACHE_IDLE_TIMEOUT_SECONDS") {
+            config.cache_idle_timeout = if seconds == 0 { None } else { Some(Duration::from_secs(seconds)) };
+        }
+
+        match env_parse::<u64>("ELEMENT_CACHE_TTL_SECONDS") {
+            Some(0) => warn!("ignoring ELEMENT_CACHE_TTL_SECONDS=0, listings would expire immediately"),
+            Some(seconds) => config.element_cache_ttl = Duration::from_secs(seconds),
+            None => {}
+        }
+        if config.cache_idle_timeout.map_or(false, |idle| idle < config.element_cache_ttl) {
+            warn!("CACHE_IDLE_TIMEOUT_SECONDS is shorter than ELEMENT_CACHE_TTL_SECONDS, listings will be dropped before they expire");
+        }
+
+        if let Some(retries) = env_parse::<u32>("OPEN_APP_RETRIES") {
+            config.open_app_retries = retries;
+        }
+
+        if let Some(delay) = env_parse::<u64>("OPEN_APP_RETRY_DELAY_MS") {
+            config.open_app_retry_delay_ms = delay;
+        }
+
+        if let Some(enabled) = env_parse::<bool>("ENABLE_RAW_APPLESCRIPT") {
+            config.enable_raw_applescript = enabled;
+            if enabled {
+                warn!("ENABLE_RAW_APPLESCRIPT is set: {} lets clients run arbitrary applescript", RAW_APPLESCRIPT_TOOL);
+            }
+        }
+
+        config.default_browser = env::var("DEFAULT_BROWSER")
+            .ok()
+            .map(|browser| browser.trim().to_string())
+            .filter(|browser| !browser.is_empty());
+
+        if let Some(ceiling) = env_parse::<usize>("SUMMARIZE_ABOVE_ELEMENTS") {
+            config.summarize_above_elements = if ceiling == 0 { None } else { Some(ceiling) };
+        }
+
+        if let Some(count) = env_parse::<usize>("SUMMARY_PROMINENT_ELEMENTS") {
+            config.summary_prominent_elements = count;
+        }
+
+        config.safe_mode_allowed_apps = env_list("SAFE_MODE_ALLOWED_APPS")
+            .map(|apps| apps.into_iter().map(|app| app.to_lowercase()).collect::<HashSet<_>>())
+            .filter(|apps| !apps.is_empty());
+        if let Some(apps) = &config.safe_mode_allowed_apps {
+            info!("safe mode: input is limited to {} apps", apps.len());
+        }
+
+        if let Ok(value) = env::var("INPUT_HOME_POSITION") {
+            config.input_home_position = parse_point(&value);
+            if config.input_home_position.is_none() {
+                warn!("ignoring invalid value for INPUT_HOME_POSITION: {}", value);
+            }
+        }
+
+        config
+    }
+
+    /// Whether a tool (by its MCP name, e.g. `clickByIndex`) is exposed by this server
+    pub fn is_tool_enabled(&self, tool: &str) -> bool {
+        // The raw script tool needs its own opt-in, ENABLED_TOOLS alone doesn't expose it
+        if tool == RAW_APPLESCRIPT_TOOL && !self.enable_raw_applescript {
+            return false;
+        }
+        let enabled = self.enabled_tools.as_ref().map_or(true, |tools| tools.contains(tool));
+        enabled && !self.disabled_tools.contains(tool)
+    }
+
+    /// Whether input may go to an app known by any of these names or bundle ids
+    pub fn is_app_allowed<S: AsRef<str>>(&self, identities: &[S]) -> bool {
+        match &self.safe_mode_allowed_apps {
+            Some(allowed) => identities.iter().any(|identity| allowed.contains(&identity.as_ref().to_lowercase())),
+            None => true,
+        }
+    }
+
+    /// Delay between action steps, preferring the request's own value
+    pub fn inter_action_delay(&self, requested_ms: Option<u64>) -> Duration {
+        Duration::from_millis(requested_ms.unwrap_or(self.inter_action_delay_ms))
+    }
+
+    /// Wait before listing the app after an action, preferring the request's own value
+    pub fn refresh_delay_ms(&self, requested_ms: Option<u64>) -> u64 {
+        requested_ms.unwrap_or(self.refresh_delay_ms)
+    }
+
+    /// Waits before each openApplication retry (exponential backoff), preferring the request's own values
+    pub fn open_app_retry_delays(&self, retries: Option<u32>, delay_ms: Option<u64>) -> Vec<Duration> {
+        let first = Duration::from_millis(delay_ms.unwrap_or(self.open_app_retry_delay_ms));
+        (0..retries.unwrap_or(self.open_app_retries))
+            .map(|attempt| first.saturating_mul(2u32.saturating_pow(attempt)))
+            .collect()
+    }
+
+    /// Look up a user-provided interactability for a role in a specific app
+    pub fn interactability_override(&self, app_name: &str, role: &str) -> Option<Interactability> {
+        self.interactability_overrides
+            .get(&app_name.to_lowercase())
+            .and_then(|roles| roles.get(role))
+            .copied()
+    }
+}
+
+fn load_interactability_overrides(path: &str) -> HashMap<String, HashMap<String, Interactability>> {
+    let parsed = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            serde_json::from_str::<HashMap<String, HashMap<String, Interactability>>>(&contents)
+                .map_err(|e| e.to_string())
+        });
+
+    match parsed {
+        Ok(overrides) => {
+            info!("loaded interactability overrides for {} apps from {}", overrides.len(), path);
+            // App names are matched case-insensitively
+            overrides
+                .into_iter()
+                .map(|(app_name, roles)| (app_name.to_lowercase(), roles))
+                .collect()
+        }
+        Err(e) => {
+            warn!("ignoring interactability overrides file {}: {}", path, e);
+            HashMap::new()
+        }
+    }
+}
+
+// Parse an environment variable, warning (and ignoring it) when the value is malformed
+fn env_parse<T: FromStr>(key: &str) -> Option<T> {
+    let value = env::var(key).ok()?;
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            warn!("ignoring invalid value for {}: {}", key, value);
+            None
+        }
+    }
+}
+
+// Parse a comma-separated environment variable into a set of names
+fn env_list(key: &str) -> Option<HashSet<String>> {
+    let value = env::var(key).ok()?;
+    Some(
+        value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+    )
+}
+
+// Parse an "x,y" screen point
+fn parse_point(value: &str) -> Option<(f64, f64)> {
+    let (x, y) = value.split_once(',')?;
+    let x = x.trim().parse::<f64>().ok().filter(|x| x.is_finite())?;
+    let y = y.trim().parse::<f64>().ok().filter(|y| y.is_finite())?;
+    Some((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_app_retries_back_off_exponentially() {
+        let config = ServerConfig::default();
+        assert_eq!(config.open_app_retry_delays(None, None), vec![Duration::from_millis(1000)]);
+        assert_eq!(
+            config.open_app_retry_delays(Some(3), Some(500)),
+            vec![Duration::from_millis(500), Duration::from_millis(1000), Duration::from_millis(2000)]
+        );
+        assert!(config.open_app_retry_delays(Some(0), None).is_empty());
+    }
+
+    #[test]
+    fn raw_applescript_needs_its_own_opt_in() {
+        let mut config = ServerConfig {
+            enabled_tools: Some([RAW_APPLESCRIPT_TOOL.to_string()].into_iter().collect()),
+            ..ServerConfig::default()
+        };
+        assert!(!config.is_tool_enabled(RAW_APPLESCRIPT_TOOL));
+
+        config.enable_raw_applescript = true;
+        assert!(config.is_tool_enabled(RAW_APPLESCRIPT_TOOL));
+    }
+
+    #[test]
+    fn safe_mode_matches_names_and_bundle_ids_case_insensitively() {
+        let mut config = ServerConfig::default();
+        assert!(config.is_app_allowed(&["Messages"]));
+
+        config.safe_mode_allowed_apps = Some(["textedit".to_string(), "com.apple.safari".to_string()].into_iter().collect());
+        assert!(config.is_app_allowed(&["TextEdit"]));
+        assert!(config.is_app_allowed(&["Safari", "com.apple.Safari"]));
+        assert!(!config.is_app_allowed(&["Messages", "com.apple.MobileSMS"]));
+        assert!(!config.is_app_allowed::<&str>(&[]));
+    }
+
+    #[test]
+    fn home_position_parses_as_x_comma_y() {
+        assert_eq!(parse_point("100, 200.5"), Some((100.0, 200.5)));
+        assert_eq!(parse_point("100"), None);
+        assert_eq!(parse_point("x,200"), None);
+        assert_eq!(parse_point("NaN,0"), None);
+    }
+
+    #[test]
+    fn bind_address_defaults_to_localhost_and_reads_flags() {
+        assert_eq!(parse_bind_address(None, None).unwrap(), "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(parse_bind_address(Some("0.0.0.0"), Some("9000")).unwrap(), "0.0.0.0:9000".parse().unwrap());
+        assert_eq!(parse_bind_address(Some("[::1]"), Some(" 81 ")).unwrap(), "[::1]:81".parse().unwrap());
+        assert!(parse_bind_address(None, Some("80800")).is_err());
+
+        let args: Vec<String> = ["server", "--host", "0.0.0.0", "--port=9001"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(cli_flag(&args, "--host").as_deref(), Some("0.0.0.0"));
+        assert_eq!(cli_flag(&args, "--port").as_deref(), Some("9001"));
+        assert_eq!(cli_flag(&args, "--stdio"), None);
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::{extract::State, response::Json as JsonResponse};
+
+use crate::types::{AppState, CacheEntryStatus, CacheStatusResponse, CachedAppStatus};
+use crate::handlers::click_menu_bar_item::MENU_BAR_CACHE_TTL;
+use crate::handlers::utils::ALL_APPS_KEY;
+
+// Report what the caches hold and whether they are still usable. Only reads
+// the cache state, so it is cheap enough to call before every by-index action.
+pub async fn cache_status_handler(State(state): State<Arc<AppState>>) -> JsonResponse<CacheStatusResponse> {
+    let (element_cache, element_caches) = {
+        let cache = state.element_cache.lock().await;
+        let mut listings: Vec<_> = cache.listings.iter().collect();
+        listings.sort_by_key(|(_, listing)| std::cmp::Reverse(listing.timestamp));
+
+        let mut latest = None;
+        let mut all = Vec::with_capacity(listings.len());
+        for (key, listing) in listings {
+            let apps = if key == ALL_APPS_KEY {
+                count_by_app(&state.element_owners.lock().await)
+            } else {
+                vec![CachedAppStatus { app_name: key.clone(), element_count: listing.elements.len() }]
+            };
+            if cache.latest_key() == Some(key.as_str()) {
+                latest = Some(entry_status(apps.clone(), listing.elements.len(), listing.timestamp, state.config.element_cache_ttl));
+            }
+            all.push(entry_status(apps, listing.elements.len(), listing.timestamp, state.config.element_cache_ttl));
+        }
+        (latest, all)
+    };
+
+    let menu_bar_cache = {
+        let cache = state.menu_bar_cache.lock().await;
+        cache
+            .as_ref()
+            .map(|(items, timestamp)| entry_status(Vec::new(), items.len(), *timestamp, MENU_BAR_CACHE_TTL))
+    };
+
+    JsonResponse(CacheStatusResponse { element_cache, element_caches, menu_bar_cache })
+}
+
+fn entry_status(apps: Vec<CachedAppStatus>, element_count: usize, timestamp: Instant, ttl: Duration) -> CacheEntryStatus {
+    let age = timestamp.elapsed();
+    CacheEntryStatus {
+        apps,
+        element_count,
+        age_seconds: age.as_secs_f64(),
+        ttl_seconds: ttl.as_secs(),
+        expired: age >= ttl,
+    }
+}
+
+// Element counts per owning app of a cross-app listing, in listing order
+fn count_by_app(owners: &[String]) -> Vec<CachedAppStatus> {
+    let mut apps: Vec<CachedAppStatus> = Vec::new();
+    for owner in owners {
+        match apps.iter_mut().find(|app| app.app_name == *owner) {
+            Some(app) => app.element_count += 1,
+            None => apps.push(CachedAppStatus { app_name: owner.clone(), element_count: 1 }),
+        }
+    }
+    apps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_elements_per_owner_in_listing_order() {
+        let owners: Vec<String> = ["Safari", "Finder", "Safari"].iter().map(|s| s.to_string()).collect();
+        let apps = count_by_app(&owners);
+        assert_eq!(apps.len(), 2);
+        assert_eq!((apps[0].app_name.as_str(), apps[0].element_count), ("Safari", 2));
+        assert_eq!((apps[1].app_name.as_str(), apps[1].element_count), ("Finder", 1));
+    }
+
+    #[test]
+    fn entry_expires_after_ttl() {
+        let ttl = Duration::from_secs(30);
+        let stale = Instant::now() - Duration::from_secs(31);
+        assert!(entry_status(Vec::new(), 0, stale, ttl).expired);
+        assert!(!entry_status(Vec::new(), 0, Instant::now(), ttl).expired);
+    }
+}
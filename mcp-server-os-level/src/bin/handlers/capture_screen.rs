@@ -0,0 +1,209 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, CaptureScreenRequest, CaptureScreenResponse, ElementBounds, ScreenMark, ScreenshotOptions};
+use crate::handlers::list_elements_and_attributes::is_non_interactable;
+use crate::handlers::utils::{element_owner, front_window_bounds, run_jxa, screencapture, shrink_screenshot};
+
+// Marks drawn when the request doesn't cap them
+const DEFAULT_MAX_MARKS: usize = 200;
+
+// Draws argv[1]'s marks ({region, marks}, screen points) onto the PNG at argv[0] in place.
+// A null region means the capture is the whole main display.
+const ANNOTATE_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+function run(argv) {
+    const spec = JSON.parse(argv[1]);
+    const screen = $.NSScreen.mainScreen.frame.size;
+    const region = spec.region || {x: 0, y: 0, width: screen.width, height: screen.height};
+    const source = $.NSBitmapImageRep.imageRepWithData($.NSData.dataWithContentsOfFile(argv[0]));
+    const width = source.pixelsWide, height = source.pixelsHigh;
+    const scale = width / region.width;
+
+    const rep = $.NSBitmapImageRep.alloc.initWithBitmapDataPlanesPixelsWidePixelsHighBitsPerSampleSamplesPerPixelHasAlphaIsPlanarColorSpaceNameBytesPerRowBitsPerPixel(
+        null, width, height, 8, 4, true, false, $.NSCalibratedRGBColorSpace, 0, 0);
+    $.NSGraphicsContext.saveGraphicsState;
+    $.NSGraphicsContext.setCurrentContext($.NSGraphicsContext.graphicsContextWithBitmapImageRep(rep));
+    source.drawInRect($.NSMakeRect(0, 0, width, height));
+
+    const color = $.NSColor.colorWithCalibratedRedGreenBlueAlpha(0.95, 0.1, 0.3, 0.9);
+    const attributes = $.NSDictionary.dictionaryWithObjectsForKeys(
+        $([$.NSFont.boldSystemFontOfSize(11 * scale), $.NSColor.whiteColor]),
+        $([$.NSFontAttributeName, $.NSForegroundColorAttributeName]));
+    for (const mark of spec.marks) {
+        // AppKit's origin is bottom-left, the screen's top-left
+        const x = (mark.x - region.x) * scale;
+        const y = height - (mark.y - region.y + mark.height) * scale;
+        const box = $.NSBezierPath.bezierPathWithRect($.NSMakeRect(x, y, mark.width * scale, mark.height * scale));
+        box.setLineWidth(1.5 * scale);
+        color.setStroke;
+        box.stroke;
+
+        const label = $(String(mark.index));
+        const size = label.sizeWithAttributes(attributes);
+        const top = y + mark.height * scale;
+        color.setFill;
+        $.NSBezierPath.fillRect($.NSMakeRect(x, top - size.height, size.width + 4 * scale, size.height));
+        label.drawAtPointWithAttributes($.NSMakePoint(x + 2 * scale, top - size.height), attributes);
+    }
+
+    $.NSGraphicsContext.restoreGraphicsState;
+    rep.representationUsingTypeProperties($.NSBitmapImageFileTypePNG, $({})).writeToFileAtomically(argv[0], true);
+    return String(spec.marks.length);
+}
+"#;
+
+// Capture a display, or one app's front window, as a base64 image, optionally with the
+// cached elements boxed and numbered by index so a model can answer "click 7"
+pub async fn capture_screen_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CaptureScreenRequest>,
+) -> Result<JsonResponse<CaptureScreenResponse>, (StatusCode, JsonResponse<Value>)> {
+    let region = match (&request.app_name, &request.bundle_id) {
+        (None, None) => None,
+        (app_name, bundle_id) => Some(front_window_bounds(app_name.as_deref().unwrap_or_default(), bundle_id.as_deref())?),
+    };
+    if request.display == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "display is numbered from 1 (the main display)"})),
+        ));
+    }
+    let display = if region.is_some() { None } else { request.display };
+    if request.annotate && display.map_or(false, |display| display != 1) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "annotate only supports app windows and the main display"})),
+        ));
+    }
+    info!("capturing {}", match (region, display) {
+        (Some(region), _) => format!("window at {:?}", region),
+        (None, Some(display)) => format!("display {}", display),
+        (None, None) => "the main display".to_string(),
+    });
+
+    let marks = if request.annotate { Some(cached_marks(&state, &request, region).await?) } else { None };
+
+    let path = std::env::temp_dir().join(format!("capture-{}.png", uuid::Uuid::new_v4()));
+    let path_str = path.to_string_lossy().to_string();
+    let mut captured = screencapture(&path_str, region, display).await;
+    if let Some(marks) = marks.as_ref().filter(|_| captured.is_ok()) {
+        let spec = json!({"region": region, "marks": marks}).to_string();
+        if let Err(e) = run_jxa(ANNOTATE_SCRIPT, &[&path_str, &spec]).await {
+            error!("failed to annotate screen capture: {}", e);
+            captured = Err(e.to_response());
+        }
+    }
+    let png = std::fs::read(&path);
+    let _ = std::fs::remove_file(&path);
+    captured?;
+    let png = png.map_err(|e| {
+        error!("failed to read screen capture: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to read screen capture: {}", e)})),
+        )
+    })?;
+
+    let options = ScreenshotOptions {
+        include_screenshot: true,
+        screenshot_max_width: request.max_width,
+        screenshot_quality: request.quality,
+    };
+    let (image, format) = shrink_screenshot(png, &options).await.map_err(|e| {
+        error!("failed to downscale screen capture: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to downscale screen capture: {}", e)})),
+        )
+    })?;
+
+    Ok(JsonResponse(CaptureScreenResponse {
+        format: format.to_string(),
+        image_base64: STANDARD.encode(image),
+        region,
+        display,
+        marked_indices: marks.map(|marks| marks.iter().map(|mark| mark.index).collect()),
+    }))
+}
+
+// Boxes of the cached listing's elements, numbered with the indices by-index tools take
+async fn cached_marks(
+    state: &AppState,
+    request: &CaptureScreenRequest,
+    region: Option<ElementBounds>,
+) -> Result<Vec<ScreenMark>, (StatusCode, JsonResponse<Value>)> {
+    let listing_app = request.bundle_id.as_deref().or(request.app_name.as_deref());
+    let listing = state.element_cache.lock().await.get(listing_app);
+    let Some((elements, _, cache_key)) = listing else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": "no element cache found - call listInteractableElementsByIndex first so there are indices to annotate"
+            })),
+        ));
+    };
+
+    let mut candidates = Vec::with_capacity(elements.len());
+    for (index, element) in elements.iter().enumerate() {
+        let interactable = if request.annotate_interactable_only {
+            let owner = element_owner(state, index, &cache_key).await;
+            !is_non_interactable(&state.config, &owner, &element.role())
+        } else {
+            true
+        };
+        candidates.push((index, element.bounds().ok(), interactable));
+    }
+    Ok(select_marks(candidates, region, request.annotate_max_elements.unwrap_or(DEFAULT_MAX_MARKS)))
+}
+
+// Keep visible, interactable candidates inside the captured region, lowest indices first
+fn select_marks(
+    candidates: Vec<(usize, Option<(f64, f64, f64, f64)>, bool)>,
+    region: Option<ElementBounds>,
+    max_marks: usize,
+) -> Vec<ScreenMark> {
+    candidates
+        .into_iter()
+        .filter(|(_, _, interactable)| *interactable)
+        .filter_map(|(index, bounds, _)| bounds.map(|(x, y, width, height)| ScreenMark { index, x, y, width, height }))
+        .filter(|mark| mark.width > 0.0 && mark.height > 0.0)
+        .filter(|mark| {
+            region.map_or(true, |r| {
+                mark.x < r.x + r.width && mark.x + mark.width > r.x && mark.y < r.y + r.height && mark.y + mark.height > r.y
+            })
+        })
+        .take(max_marks)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_keep_listing_indices_and_skip_hidden_elements() {
+        let window = ElementBounds { x: 100.0, y: 100.0, width: 400.0, height: 300.0 };
+        let candidates = vec![
+            (0, Some((120.0, 120.0, 50.0, 20.0)), true),
+            (1, None, true),
+            (2, Some((150.0, 150.0, 0.0, 0.0)), true),
+            (3, Some((900.0, 900.0, 50.0, 20.0)), true),
+            (4, Some((200.0, 200.0, 80.0, 20.0)), false),
+            (5, Some((300.0, 300.0, 80.0, 20.0)), true),
+            (6, Some((310.0, 310.0, 80.0, 20.0)), true),
+        ];
+
+        let indices = |marks: Vec<ScreenMark>| marks.iter().map(|mark| mark.index).collect::<Vec<_>>();
+        assert_eq!(indices(select_marks(candidates.clone(), Some(window), 10)), vec![0, 5, 6]);
+        assert_eq!(indices(select_marks(candidates.clone(), Some(window), 2)), vec![0, 5]);
+        assert_eq!(indices(select_marks(candidates, None, 10)), vec![0, 3, 5, 6]);
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    response::Json as JsonResponse,
+};
+use tracing::info;
+
+use crate::types::{AppState, ClearElementCacheRequest, ClearElementCacheResponse};
+use crate::handlers::utils::forget_listings;
+
+// Forget indexed listings on demand, for when the caller knows the UI changed behind the
+// cache's back; by-index calls then fail until the app is listed again instead of acting
+// on stale indices
+pub async fn clear_element_cache_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClearElementCacheRequest>,
+) -> JsonResponse<ClearElementCacheResponse> {
+    let app_name = request.app_name.filter(|name| !name.trim().is_empty());
+
+    let (cleared_caches, dropped_elements) = {
+        let mut cache = state.element_cache.lock().await;
+        let sizes: Vec<(String, usize)> = cache
+            .listings
+            .iter()
+            .map(|(key, listing)| (key.clone(), listing.elements.len()))
+            .collect();
+        let cleared = match &app_name {
+            Some(app_name) => cache.remove_app(&[app_name]),
+            None => cache.clear(),
+        };
+        let dropped = sizes
+            .iter()
+            .filter(|(key, _)| cleared.contains(key))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+        (cleared, dropped)
+    };
+    forget_listings(&state, &cleared_caches).await;
+
+    let message = match (&app_name, cleared_caches.is_empty()) {
+        (Some(app_name), true) => format!("no cached listing of {} to clear", app_name),
+        (None, true) => "element cache was already empty".to_string(),
+        _ => format!("dropped {} cached elements from {} listing(s)", dropped_elements, cleared_caches.len()),
+    };
+    info!("{}", message);
+
+    JsonResponse(ClearElementCacheResponse {
+        success: true,
+        message,
+        cleared_caches,
+        dropped_elements,
+    })
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use tracing::info;
+
+use crate::types::{AppState, ClickByIdRequest, ClickByIndexRequest};
+use crate::handlers::click_by_index::{click_by_index_handler, ClickByIndexWithElementsResponse};
+use crate::handlers::utils::cached_index_by_id;
+
+// Click an element by the element_id of its listing entry: the id is resolved to the
+// element's current index in the cached listing, and the click is clickByIndex's
+pub async fn click_by_id_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClickByIdRequest>,
+) -> Result<JsonResponse<ClickByIndexWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    let element_index = cached_index_by_id(&state, &request.element_id, request.app_name.as_deref()).await?;
+    info!("element id {} is at index {}", request.element_id, element_index);
+
+    click_by_index_handler(
+        State(state),
+        Json(ClickByIndexRequest {
+            element_index,
+            app_name: request.app_name,
+            click_count: request.click_count,
+            retry_if_unchanged: request.retry_if_unchanged,
+            options: request.options,
+        }),
+    )
+    .await
+}
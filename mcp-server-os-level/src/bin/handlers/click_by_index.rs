@@ -6,27 +6,48 @@ use axum::{
 };
 use serde::Serialize;
 use serde_json::json;
-use tracing::{debug, error};
-use computer_use_ai_sdk::Desktop;
+use tracing::{debug, error, info};
+use computer_use_ai_sdk::{Desktop, MouseButton, UIElement};
 
-use crate::types::{AppState, ClickByIndexRequest, ClickByIndexResponse, ListElementsAndAttributesResponse};
-use crate::refresh_elements_and_attributes_after_action;
+use crate::types::{AppState, ClickByIndexRequest, ClickByIndexResponse, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot, UiDiff};
+use crate::refresh_after_action;
+use crate::handlers::utils::{is_transient, retry_with_backoff, capture_action_screenshot, click_point, detect_ui_change, detect_ui_diff, element_owner, ensure_app_allowed, last_ui_fingerprint, last_ui_snapshot, resolve_application, send_mouse_click, FocusGuard};
 
 // Response type that combines both click result and elements
 #[derive(Serialize)]
 pub struct ClickByIndexWithElementsResponse {
     pub click: ClickByIndexResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the click (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // How many listed elements the click added, removed or changed, and a one-line summary
+    pub ui_changes: Option<UiDiff>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
 }
 
 pub async fn click_by_index_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ClickByIndexRequest>,
 ) -> Result<JsonResponse<ClickByIndexWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    let click_count = request.click_count.unwrap_or(1);
+    if !(1..=3).contains(&click_count) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": format!("click_count must be 1, 2 or 3, got {}", click_count)})),
+        ));
+    }
+
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
     // Get elements from cache
     let elements_opt = {
         let cache = state.element_cache.lock().await;
-        cache.clone()
+        cache.get(request.app_name.as_deref())
     };
 
     // Check if cache exists
@@ -40,111 +61,60 @@ pub async fn click_by_index_handler(
     }
 
     match elements_opt {
-        Some((elements, timestamp, app_name)) if timestamp.elapsed() < std::time::Duration::from_secs(30) => {
+        Some((elements, timestamp, app_name)) if timestamp.elapsed() < state.config.element_cache_ttl => {
+            // A cross-app listing acts on the element's own app
+            let app_name = element_owner(&state, request.element_index, &app_name).await;
+            ensure_app_allowed(&state.config, &app_name, None)?;
             // Use element_index directly
             if request.element_index < elements.len() {
                 let element = &elements[request.element_index];
                 
-                // Step 1: Try inputControl first (AppleScript) if bounds are available
-                let bounds = element.bounds();
-                let input_control_success = if let Ok((x, y, width, height)) = bounds {
-                    debug!("attempting to click element at position [{}, {}] using inputControl", 
-                          x + width/2.0, y + height/2.0);
-                    
-                    // Activate the app first
-                    debug!("activating app: {}", app_name);
-                    let desktop = match Desktop::new(false, true) {
-                        Ok(d) => d,
-                        Err(e) => {
-                            error!("failed to initialize desktop automation: {}", e);
-                            return Err((
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                JsonResponse(json!({
-                                    "error": format!("failed to initialize desktop automation: {}", e)
-                                })),
-                            ));
-                        }
-                    };
-
-                    // Get and activate the application
-                    let _ = match desktop.application(&app_name) {
-                        Ok(app) => app,
-                        Err(e) => {
-                            error!("application not found: {}", e);
-                            return Err((
-                                StatusCode::NOT_FOUND,
-                                JsonResponse(json!({
-                                    "error": format!("application not found: {}", e)
-                                })),
-                            ));
-                        }
-                    };
-                    
-                    // Calculate center of element
-                    let center_x = x + width/2.0;
-                    let center_y = y + height/2.0;
-                    
-                    use std::process::Command;
-                    
-                    // Use AppleScript to click at position
-                    let script = format!(
-                        "tell application \"System Events\" to click at {{round {}, round {}}}",
-                        center_x, center_y
-                    );
-                    
-                    match Command::new("osascript").arg("-e").arg(script).output() {
-                        Ok(_) => {
-                            debug!("successfully clicked element using inputControl at [{}, {}]",
-                                  center_x, center_y);
-                            true
-                        },
-                        Err(e) => {
-                            debug!("failed to click using inputControl: {} - falling back to accessibility API", e);
-                            false
-                        }
-                    }
-                } else {
-                    debug!("could not get element bounds - skipping inputControl approach");
-                    false
-                };
+                // Compare the listing before and after to tell whether the click did anything
+                let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+                let snapshot_before = last_ui_snapshot(&state, &app_name).await;
+                
+                // Not retried: a click that failed partway may already have landed
+                let mouse_click_success = click_element(element, &app_name, click_count).await?;
                 
-                // Step 2: If inputControl failed, use accessibility API as fallback
-                if !input_control_success {
-                    debug!("using accessibility API for clicking");
-                    match element.click() {
-                        Ok(_) => {
-                            debug!("successfully clicked element using accessibility API");
-                        },
-                        Err(e) => {
-                            error!("failed to click element with accessibility API: {}", e);
-                            return Err((
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                JsonResponse(json!({
-                                    "error": format!("failed to click element using both inputControl and accessibility API: {}", e)
-                                })),
-                            ));
-                        }
-                    }
+                // Get refreshed elements using the helper function
+                let mut elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+                let mut ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+                let mut ui_changes = detect_ui_diff(&state, &app_name, snapshot_before, elements_response.is_some()).await;
+                
+                // An unchanged UI usually means the click missed, so try once more if asked
+                let mut retried = false;
+                if request.retry_if_unchanged && ui_state_changed == Some(false) {
+                    info!("ui unchanged after clicking element {} - retrying the click once", request.element_index);
+                    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+                    let snapshot_before = last_ui_snapshot(&state, &app_name).await;
+                    click_element(element, &app_name, click_count).await?;
+                    elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+                    ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+                    ui_changes = detect_ui_diff(&state, &app_name, snapshot_before, elements_response.is_some()).await;
+                    retried = true;
                 }
                 
                 // Create the success response based on which method worked
-                let method_used = if input_control_success { "AppleScript" } else { "Accessibility API" };
+                let method_used = if mouse_click_success { "mouse event" } else { "Accessibility API" };
                 let click_response = ClickByIndexResponse {
                     success: true,
                     message: format!(
-                        "successfully clicked element with role: {} (using {} method)",
+                        "successfully {} element with role: {} (using {} method)",
+                        match click_count { 2 => "double-clicked", 3 => "triple-clicked", _ => "clicked" },
                         element.role(), method_used
                     ),
                     elements: None,  // add the missing field
+                    retried,
                 };
                 
-                // Get refreshed elements using the helper function
-                let elements_response = refresh_elements_and_attributes_after_action(state, app_name.clone(), 500).await;
-                
                 // Return combined response
                 Ok(JsonResponse(ClickByIndexWithElementsResponse {
                     click: click_response,
                     elements: elements_response,
+                    ui_state_changed,
+                    ui_changes,
+                    focus: focus_guard.report().await,
+                    screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
                 }))
             } else {
                 error!(
@@ -181,3 +151,90 @@ pub async fn click_by_index_handler(
         }
     }
 }
+
+// Click an element `click_count` times at its center with mouse events, falling back to AXPress
+// for a single click. Returns whether the coordinate click was used.
+async fn click_element(element: &UIElement, app_name: &str, click_count: u32) -> Result<bool, (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Step 1: Post a mouse click at the element's center if bounds are available
+    let bounds = element.bounds();
+    let mouse_click_success = if let Ok((x, y, width, height)) = bounds {
+        let point = click_point((x, y, width, height));
+        debug!("attempting to click element at position [{}, {}] with mouse events", 
+              point.x, point.y);
+        
+        let desktop = match Desktop::new(false, true) {
+            Ok(d) => d,
+            Err(e) => {
+                error!("failed to initialize desktop automation: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    JsonResponse(json!({
+                        "error": format!("failed to initialize desktop automation: {}", e)
+                    })),
+                ));
+            }
+        };
+
+        // Make sure the app is still running before clicking where its element was
+        let _ = match retry_with_backoff("looking up the app", is_transient, || async { resolve_application(&desktop, app_name, None) }).await {
+            Ok(app) => app,
+            Err(e) => {
+                error!("application not found: {}", e);
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    JsonResponse(json!({
+                        "error": format!("application not found: {}", e)
+                    })),
+                ));
+            }
+        };
+        
+        // One down/up pair per click, numbered 1..n so the app sees a double or triple click
+        match send_mouse_click(Some((point.x, point.y)), MouseButton::Left, click_count).await {
+            Ok(()) => {
+                debug!("successfully clicked element with mouse events at [{}, {}]",
+                      point.x, point.y);
+                true
+            },
+            Err(e) if e.0 == StatusCode::FORBIDDEN || click_count > 1 => {
+                // AXPress can't stand in for a double or triple click
+                return Err(e);
+            },
+            Err((_, e)) => {
+                debug!("failed to click with mouse events: {} - falling back to accessibility API", e.0);
+                false
+            }
+        }
+    } else if click_count > 1 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": "element has no bounds to double or triple click at"
+            })),
+        ));
+    } else {
+        debug!("could not get element bounds - skipping mouse click approach");
+        false
+    };
+    
+    // Step 2: If the mouse click failed, use accessibility API as fallback
+    if !mouse_click_success {
+        debug!("using accessibility API for clicking");
+        match element.click() {
+            Ok(_) => {
+                debug!("successfully clicked element using accessibility API");
+            },
+            Err(e) => {
+                error!("failed to click element with accessibility API: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    JsonResponse(json!({
+                        "error": format!("failed to click element using both mouse events and accessibility API: {}", e)
+                    })),
+                ));
+            }
+        }
+    }
+    
+    Ok(mouse_click_success)
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::types::{ActionOptions, AppState, ClickByIndexRequest, ClickByTextRequest, ClickByTextResponse, ListInteractableElementsRequest};
+use crate::handlers::click_by_index::click_by_index_handler;
+use crate::handlers::list_elements_and_attributes::list_elements_and_attributes_handler;
+use crate::handlers::utils::{ensure_app_allowed, role_matches, FocusGuard};
+
+// Candidates listed back to the caller when a query is ambiguous
+const MAX_CANDIDATES: usize = 10;
+
+// How well an element's text matches the query, higher is better
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchQuality {
+    Contains,
+    Prefix,
+    Exact,
+}
+
+pub async fn click_by_text_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClickByTextRequest>,
+) -> Result<JsonResponse<ClickByTextResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.restore_focus).await;
+
+    info!("clicking element matching '{}' in {}", request.query, request.app_name);
+
+    if request.query.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "query must not be empty"})),
+        ));
+    }
+    ensure_app_allowed(&state.config, &request.app_name, request.bundle_id.as_deref())?;
+
+    // List every element so the match isn't hidden by the default cap; this also refreshes the cache
+    let list = list_elements_and_attributes_handler(
+        State(state.clone()),
+        Json(ListInteractableElementsRequest {
+            app_name: request.app_name.clone(),
+            bundle_id: request.bundle_id.clone(),
+            use_background_apps: Some(false),
+            activate_app: Some(true),
+            unbounded: Some(true),
+            ..Default::default()
+        }),
+    )
+    .await?
+    .0;
+
+    let exact_only = request.exact.unwrap_or(false);
+    let matches: Vec<(MatchQuality, &Value)> = list
+        .elements
+        .iter()
+        .filter(|e| request.role.as_deref().map_or(true, |role| role_matches(e, role)))
+        .filter_map(|e| {
+            let text = e["text"].as_str()?;
+            match_quality(text, &request.query)
+                .filter(|quality| !exact_only || *quality == MatchQuality::Exact)
+                .map(|quality| (quality, e))
+        })
+        .collect();
+
+    let best = match matches.iter().map(|(quality, _)| *quality).max() {
+        Some(best) => best,
+        None => {
+            warn!("no element matching '{}' in {}", request.query, request.app_name);
+            return Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({
+                    "error": format!("no element matching '{}' found in {}", request.query, request.app_name)
+                })),
+            ));
+        }
+    };
+
+    let candidates: Vec<&Value> = matches
+        .iter()
+        .filter(|(quality, _)| *quality == best)
+        .map(|(_, e)| *e)
+        .collect();
+
+    if candidates.len() > 1 {
+        let listed: Vec<String> = candidates
+            .iter()
+            .take(MAX_CANDIDATES)
+            .map(|e| format!("[{}] {} '{}'", e["index"], e["role"].as_str().unwrap_or_default(), e["text"].as_str().unwrap_or_default()))
+            .collect();
+        warn!("query '{}' is ambiguous: {} matches", request.query, candidates.len());
+        return Err((
+            StatusCode::CONFLICT,
+            JsonResponse(json!({
+                "error": format!(
+                    "query '{}' is ambiguous, {} elements match: {} - pass role, exact, or use clickByIndex",
+                    request.query, candidates.len(), listed.join(", ")
+                ),
+                "candidates": candidates.iter().take(MAX_CANDIDATES).collect::<Vec<_>>()
+            })),
+        ));
+    }
+
+    let element = candidates[0].clone();
+    let element_index = element["index"].as_u64().unwrap_or_default() as usize;
+    info!("clicking element {} matching '{}'", element_index, request.query);
+
+    // The listing above filled the cache, so the index can be clicked directly
+    let clicked = click_by_index_handler(
+        State(state),
+        Json(ClickByIndexRequest {
+            element_index,
+            // The listing just made is the latest
+            app_name: None,
+            click_count: None,
+            retry_if_unchanged: request.retry_if_unchanged,
+            // Restoring is handled by our own guard
+            options: ActionOptions::default(),
+        }),
+    )
+    .await?
+    .0;
+
+    Ok(JsonResponse(ClickByTextResponse {
+        element_index,
+        element,
+        click: clicked.click,
+        elements: clicked.elements,
+        ui_state_changed: clicked.ui_state_changed,
+        focus: focus_guard.report().await,
+    }))
+}
+
+fn match_quality(text: &str, query: &str) -> Option<MatchQuality> {
+    let text = text.trim().to_lowercase();
+    let query = query.trim().to_lowercase();
+    if text == query {
+        Some(MatchQuality::Exact)
+    } else if text.starts_with(&query) {
+        Some(MatchQuality::Prefix)
+    } else if text.contains(&query) {
+        Some(MatchQuality::Contains)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, Selector};
+use serde_json::{json, Value};
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, ClickMenuBarItemRequest, ClickMenuBarItemResponse};
+use crate::handlers::list_elements_and_attributes::menu_item_flags;
+use crate::handlers::list_menu_bar_items::app_names_by_pid;
+use crate::handlers::utils::{ensure_app_allowed, forget_listings, settle};
+
+// Menu bar items are listed far less often than app elements, so keep them around longer
+pub const MENU_BAR_CACHE_TTL: Duration = Duration::from_secs(120);
+
+pub async fn click_menu_bar_item_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClickMenuBarItemRequest>,
+) -> Result<JsonResponse<ClickMenuBarItemResponse>, (StatusCode, JsonResponse<Value>)> {
+    let item = {
+        let cache = state.menu_bar_cache.lock().await;
+        match &*cache {
+            Some((items, timestamp)) if timestamp.elapsed() < MENU_BAR_CACHE_TTL => match items.get(request.item_index) {
+                Some(item) => item.clone(),
+                None => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        JsonResponse(json!({
+                            "error": format!("menu bar item index out of bounds: {} (max: {})",
+                                            request.item_index, items.len().saturating_sub(1))
+                        })),
+                    ));
+                }
+            },
+            Some(_) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    JsonResponse(json!({"error": "menu bar items expired, please list them again"})),
+                ));
+            }
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    JsonResponse(json!({
+                        "error": "no menu bar items found - call listMenuBarItems first"
+                    })),
+                ));
+            }
+        }
+    };
+
+    let desktop = match Desktop::new(true, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    let owner_pid = item.process_id().ok();
+    let owner_app = owner_pid.and_then(|pid| app_names_by_pid(&desktop).remove(&pid));
+
+    ensure_app_allowed(&state.config, owner_app.as_deref().unwrap_or_default(), None)?;
+
+    info!("clicking menu bar item {} owned by {:?}", request.item_index, owner_app);
+    if let Err(e) = item.click() {
+        error!("failed to click menu bar item: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({
+                "error": format!("failed to click menu bar item: {}", e)
+            })),
+        ));
+    }
+
+    // Give the menu a moment to open before reading it
+    settle(state.config.inter_action_delay(None)).await;
+
+    let menu_elements = Selector::query("role=AXMenuItem")
+        .and_then(|selector| desktop.locator(selector).within(item.clone()).all())
+        .unwrap_or_else(|e| {
+            debug!("failed to read the revealed menu: {}", e);
+            Vec::new()
+        });
+
+    let menu_items: Vec<Value> = menu_elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            let attrs = element.attributes();
+            let mut item_data = json!({
+                "index": index,
+                "role": attrs.properties.get("AXRole").and_then(|v| v.clone()).unwrap_or_else(|| json!(attrs.role)),
+                "text": attrs.label.clone().unwrap_or_default(),
+                "enabled": attrs.properties.get("AXEnabled").and_then(|v| v.clone()).unwrap_or(json!(true)),
+            });
+            if let Some(flags) = menu_item_flags(&attrs) {
+                for (key, value) in flags {
+                    item_data[key] = Value::Bool(value);
+                }
+            }
+            item_data
+        })
+        .collect();
+
+    info!("menu bar item revealed {} menu items", menu_items.len());
+
+    // Cache the menu like a listing so its items can be clicked with clickByIndex, under the
+    // owner's bundle id like its other listings (skipped when the owner is unknown)
+    let owner_key = owner_pid
+        .and_then(|pid| desktop.bundle_id(pid).ok().flatten())
+        .or_else(|| owner_app.clone());
+    if let Some(key) = owner_key.filter(|_| !menu_elements.is_empty()) {
+        let owner = owner_app.clone().unwrap_or_else(|| key.clone());
+        state.element_cache.lock().await.insert(key.clone(), owner, menu_elements);
+        // The ids and fingerprints of the app's replaced listing don't describe the menu
+        forget_listings(&state, &[key]).await;
+    }
+
+    Ok(JsonResponse(ClickMenuBarItemResponse {
+        success: true,
+        message: format!("clicked menu bar item {}", request.item_index),
+        owner_app,
+        menu_items,
+    }))
+}
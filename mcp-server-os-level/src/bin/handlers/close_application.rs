@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::Desktop;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, CloseApplicationRequest, CloseApplicationResponse};
+use crate::handlers::utils::{app_identity, command_output, ensure_app_allowed, escape_applescript_string, forget_listings, resolve_application, run_osascript};
+
+// Quit an app (or kill it with `force`) and forget its cached elements, whose
+// handles would point at a dead process
+pub async fn close_application_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CloseApplicationRequest>,
+) -> Result<JsonResponse<CloseApplicationResponse>, (StatusCode, JsonResponse<Value>)> {
+    if request.bundle_id.is_none() && request.app_name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either app_name or bundle_id must be provided"})),
+        ));
+    }
+    ensure_app_allowed(&state.config, &request.app_name, request.bundle_id.as_deref())?;
+
+    // Look the app up without activating it; there's no point bringing it forward to quit it
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    let app = match resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
+        Ok(app) => app,
+        Err(e) => {
+            error!("application not running: {}", e);
+            return Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": format!("application not running: {}", e)})),
+            ));
+        }
+    };
+    let (resolved_name, bundle_id) = app_identity(&desktop, &app);
+    let pid = app.process_id().ok();
+    let display_name = resolved_name.clone().unwrap_or_else(|| request.app_name.clone());
+    info!("closing {} (pid {:?}, force: {})", display_name, pid, request.force);
+
+    if request.force {
+        let Some(pid) = pid else {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("could not get the process id of {}", display_name)})),
+            ));
+        };
+        match command_output("kill", &["-KILL", &pid.to_string()]).await {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                error!("failed to kill {}: {}", display_name, stderr.trim());
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    JsonResponse(json!({"error": format!("failed to kill {}: {}", display_name, stderr.trim())})),
+                ));
+            }
+            Err(e) => {
+                error!("failed to run kill: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    JsonResponse(json!({"error": format!("failed to run kill: {}", e)})),
+                ));
+            }
+        }
+    } else {
+        let target = match &bundle_id {
+            Some(bundle_id) => format!("application id \"{}\"", escape_applescript_string(bundle_id)),
+            None => format!("application \"{}\"", escape_applescript_string(&display_name)),
+        };
+        if let Err(e) = run_osascript(&format!("tell {} to quit", target)).await {
+            error!("failed to quit {}: {}", display_name, e);
+            return Err(e.to_response());
+        }
+    }
+
+    // Indices into the app's listings would now act on dead elements
+    let identities: Vec<String> = [Some(request.app_name.clone()), request.bundle_id.clone(), resolved_name.clone(), bundle_id.clone()]
+        .into_iter()
+        .flatten()
+        .filter(|identity| !identity.is_empty())
+        .collect();
+    let cleared_caches = state.element_cache.lock().await.remove_app(&identities);
+    forget_listings(&state, &cleared_caches).await;
+
+    Ok(JsonResponse(CloseApplicationResponse {
+        success: true,
+        message: format!("{} {}", if request.force { "killed" } else { "quit" }, display_name),
+        app_name: resolved_name,
+        pid,
+        bundle_id,
+        cleared_caches,
+    }))
+}
@@ -0,0 +1,275 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{UIElement, UIElementAttributes};
+use serde_json::Value;
+use tracing::info;
+
+use crate::types::{AppState, DescribeByIndexRequest, DescribeByIndexResponse};
+use crate::handlers::utils::cached_element_by_index;
+
+// How far up the tree to look for a labelled container or the window
+const MAX_ANCESTORS: usize = 8;
+
+// Longest label quoted in a description before it is cut off
+const MAX_LABEL_CHARS: usize = 40;
+
+// One-line description of an element, assembled from its attributes and its ancestors
+// (no model involved, the same element always reads the same)
+pub async fn describe_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DescribeByIndexRequest>,
+) -> Result<JsonResponse<DescribeByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+
+    let description = describe_element(&element);
+    info!("described element {} in {}: {}", request.element_index, app_name, description);
+
+    Ok(JsonResponse(DescribeByIndexResponse {
+        element_index: request.element_index,
+        description,
+    }))
+}
+
+struct ElementFacts {
+    noun: String,
+    label: Option<String>,
+    enabled: Option<bool>,
+    focused: bool,
+    checked: bool,
+    position: Option<&'static str>,
+    // Nearest labelled ancestor, as (noun, label)
+    container: Option<(String, String)>,
+}
+
+fn describe_element(element: &UIElement) -> String {
+    let attrs = element.attributes();
+    let flag = |name: &str| attrs.properties.get(name).and_then(|v| v.as_ref()).and_then(|v| v.as_bool());
+
+    // Walk up once, remembering the first labelled container and the window
+    let mut container = None;
+    let mut window_bounds = None;
+    let mut current = element.parent().ok().flatten();
+    for _ in 0..MAX_ANCESTORS {
+        let Some(ancestor) = current else { break };
+        let ancestor_attrs = ancestor.attributes();
+        if raw_role(&ancestor_attrs) == "AXWindow" {
+            window_bounds = ancestor.bounds().ok();
+            break;
+        }
+        if container.is_none() {
+            if let Some(label) = display_label(&ancestor_attrs) {
+                container = Some((role_noun(&ancestor_attrs), label));
+            }
+        }
+        current = ancestor.parent().ok().flatten();
+    }
+
+    let position = match (element.bounds(), window_bounds) {
+        (Ok(bounds), Some(window)) => Some(relative_position(bounds, window)),
+        _ => None,
+    };
+
+    let checked = attrs
+        .properties
+        .get("AXMenuItemMarkChar")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .map_or(false, |mark| !mark.trim().is_empty());
+
+    assemble_description(&ElementFacts {
+        noun: role_noun(&attrs),
+        label: display_label(&attrs),
+        enabled: flag("AXEnabled"),
+        focused: flag("AXFocused").unwrap_or(false),
+        checked,
+        position,
+        container,
+    })
+}
+
+fn assemble_description(facts: &ElementFacts) -> String {
+    let mut words = Vec::new();
+    match facts.enabled {
+        Some(true) => words.push("enabled".to_string()),
+        Some(false) => words.push("disabled".to_string()),
+        None => {}
+    }
+    if facts.focused {
+        words.push("focused".to_string());
+    }
+    if facts.checked {
+        words.push("checked".to_string());
+    }
+    if let Some(label) = &facts.label {
+        words.push(format!("'{}'", label));
+    }
+    words.push(facts.noun.clone());
+
+    let mut description = words.join(" ");
+    if let Some(position) = facts.position {
+        description.push_str(&format!(" at {} of the window", position));
+    }
+    if let Some((noun, label)) = &facts.container {
+        description.push_str(&format!(", part of the '{}' {}", label, noun));
+    }
+    description
+}
+
+// The untranslated AX role; role() maps some of them (menu items report as buttons)
+fn raw_role(attrs: &UIElementAttributes) -> &str {
+    attrs
+        .properties
+        .get("AXRole")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .unwrap_or(&attrs.role)
+}
+
+// "button", "text field", ... preferring the system's own role description
+fn role_noun(attrs: &UIElementAttributes) -> String {
+    let role_description = attrs
+        .properties
+        .get("AXRoleDescription")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .filter(|description| !description.is_empty());
+    if let Some(description) = role_description {
+        return description.to_lowercase();
+    }
+
+    // AXPopUpButton -> "pop up button"
+    let mut noun = String::new();
+    for c in raw_role(attrs).trim_start_matches("AX").chars() {
+        if c.is_uppercase() && !noun.is_empty() {
+            noun.push(' ');
+        }
+        noun.extend(c.to_lowercase());
+    }
+    noun
+}
+
+fn display_label(attrs: &UIElementAttributes) -> Option<String> {
+    let label = [&attrs.label, &attrs.value, &attrs.description]
+        .into_iter()
+        .flatten()
+        .map(|text| text.trim())
+        .find(|text| !text.is_empty())?;
+
+    if label.chars().count() > MAX_LABEL_CHARS {
+        let cut: String = label.chars().take(MAX_LABEL_CHARS).collect();
+        Some(format!("{}…", cut.trim_end()))
+    } else {
+        Some(label.to_string())
+    }
+}
+
+// Which ninth of the window the element's center falls into
+fn relative_position(bounds: (f64, f64, f64, f64), window: (f64, f64, f64, f64)) -> &'static str {
+    let (x, y, width, height) = bounds;
+    let (window_x, window_y, window_width, window_height) = window;
+    let third = |offset: f64, size: f64| {
+        let fraction = if size > 0.0 { offset / size } else { 0.5 };
+        if fraction < 1.0 / 3.0 {
+            0
+        } else if fraction < 2.0 / 3.0 {
+            1
+        } else {
+            2
+        }
+    };
+    let column = third(x + width / 2.0 - window_x, window_width);
+    let row = third(y + height / 2.0 - window_y, window_height);
+
+    match (row, column) {
+        (0, 0) => "top-left",
+        (0, 1) => "top",
+        (0, _) => "top-right",
+        (1, 0) => "left",
+        (1, 1) => "center",
+        (1, _) => "right",
+        (_, 0) => "bottom-left",
+        (_, 1) => "bottom",
+        _ => "bottom-right",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn attrs(role: &str, label: Option<&str>, properties: Vec<(&str, Value)>) -> UIElementAttributes {
+        UIElementAttributes {
+            role: role.to_string(),
+            label: label.map(str::to_string),
+            value: None,
+            description: None,
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), Some(value)))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn describes_a_toolbar_button() {
+        let facts = ElementFacts {
+            noun: "button".to_string(),
+            label: Some("Send".to_string()),
+            enabled: Some(true),
+            focused: false,
+            checked: false,
+            position: Some("top-right"),
+            container: Some(("toolbar".to_string(), "Compose".to_string())),
+        };
+        assert_eq!(
+            assemble_description(&facts),
+            "enabled 'Send' button at top-right of the window, part of the 'Compose' toolbar"
+        );
+    }
+
+    #[test]
+    fn leaves_out_unknown_facts() {
+        let facts = ElementFacts {
+            noun: "text field".to_string(),
+            label: None,
+            enabled: None,
+            focused: true,
+            checked: false,
+            position: None,
+            container: None,
+        };
+        assert_eq!(assemble_description(&facts), "focused text field");
+    }
+
+    #[test]
+    fn places_elements_in_window_ninths() {
+        let window = (100.0, 100.0, 900.0, 600.0);
+        assert_eq!(relative_position((950.0, 110.0, 30.0, 20.0), window), "top-right");
+        assert_eq!(relative_position((500.0, 380.0, 100.0, 40.0), window), "center");
+        assert_eq!(relative_position((110.0, 650.0, 50.0, 20.0), window), "bottom-left");
+    }
+
+    #[test]
+    fn prefers_the_system_role_description() {
+        let described = attrs("button", None, vec![("AXRole", json!("AXMenuItem")), ("AXRoleDescription", json!("Menu Item"))]);
+        assert_eq!(role_noun(&described), "menu item");
+
+        let undescribed = attrs("AXPopUpButton", None, vec![]);
+        assert_eq!(role_noun(&undescribed), "pop up button");
+    }
+
+    #[test]
+    fn shortens_long_labels() {
+        let text = "word ".repeat(20);
+        let long = attrs("AXStaticText", Some(text.as_str()), vec![]);
+        let label = display_label(&long).unwrap();
+        assert!(label.ends_with('…'));
+        assert!(label.chars().count() <= MAX_LABEL_CHARS + 1);
+    }
+}
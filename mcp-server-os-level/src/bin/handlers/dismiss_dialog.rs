@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::Desktop;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, DismissDialogRequest, DismissDialogResponse};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{ensure_app_allowed, resolve_application, send_key_combo, settle};
+
+// Press Return or Escape in the app, which answers the frontmost sheet or dialog
+// with its default or cancel button. Cheaper than listing just to find the button.
+pub async fn dismiss_dialog_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DismissDialogRequest>,
+) -> Result<JsonResponse<DismissDialogResponse>, (StatusCode, JsonResponse<Value>)> {
+    let app_name = request.bundle_id.clone().unwrap_or_else(|| request.app_name.clone());
+    let key_name = request.mode.key();
+    info!("dismissing dialog in {} with {}", app_name, key_name);
+
+    ensure_app_allowed(&state.config, &request.app_name, request.bundle_id.as_deref())?;
+
+    // The key goes to whichever app is frontmost, so bring this one forward first
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    if let Err(e) = resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({
+                "error": format!("application not found: {}", e)
+            })),
+        ));
+    }
+    settle(Duration::from_millis(200)).await;
+
+    if let Err(e) = send_key_combo(key_name).await {
+        error!("failed to press {}", key_name);
+        return Err(e);
+    }
+
+    let elements = refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), 300).await;
+
+    Ok(JsonResponse(DismissDialogResponse {
+        success: true,
+        mode: request.mode,
+        message: format!("pressed {} in {}", key_name, app_name),
+        elements,
+    }))
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+use computer_use_ai_sdk::Desktop;
+
+use crate::types::{AppState, DragByIndexRequest, DragByIndexResponse, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot, ScreenPoint};
+use crate::refresh_after_action;
+use crate::handlers::utils::{cached_element_by_index, capture_action_screenshot, click_point, detect_ui_change, ensure_app_allowed, last_ui_fingerprint, resolve_application, FocusGuard};
+
+// Response type that combines both results
+#[derive(Serialize)]
+pub struct DragByIndexWithElementsResponse {
+    pub drag: DragByIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the drag (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
+}
+
+// Press on an element's center, drag to another element's center (or a screen point) and
+// release, for reordering list items, moving files between folders and the like
+pub async fn drag_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DragByIndexRequest>,
+) -> Result<JsonResponse<DragByIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let point = match (request.target_index, request.x, request.y) {
+        (Some(_), None, None) | (None, Some(_), Some(_)) => request.x.zip(request.y),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({"error": "provide either target_index or both x and y as the drop location"})),
+            ));
+        }
+    };
+
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
+    let (source, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    let from = match source.bounds() {
+        Ok(bounds) => click_point(bounds),
+        Err(e) => {
+            error!("failed to get bounds of drag source {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({
+                    "error": format!("element {} has no position on screen to drag from: {}", request.element_index, e)
+                })),
+            ));
+        }
+    };
+
+    let (to, target_description) = match (request.target_index, point) {
+        (Some(target_index), _) => {
+            let (target, _) = cached_element_by_index(&state, target_index, request.app_name.as_deref()).await?;
+            match target.bounds() {
+                Ok(bounds) => (click_point(bounds), format!("element {} ({})", target_index, target.role())),
+                Err(e) => {
+                    error!("failed to get bounds of drop target {}: {}", target_index, e);
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        JsonResponse(json!({
+                            "error": format!("element {} has no position on screen to drop on: {}", target_index, e)
+                        })),
+                    ));
+                }
+            }
+        }
+        (None, Some((x, y))) => (ScreenPoint { x, y }, format!("[{}, {}]", x, y)),
+        (None, None) => unreachable!("drop location validated above"),
+    };
+    info!("dragging element {} in {} onto {}", request.element_index, app_name, target_description);
+
+    // Compare the listing before and after to tell whether anything moved
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
+    // The drag is real mouse input, so the app has to be in front
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    if let Err(e) = resolve_application(&desktop, &app_name, None) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("application not found: {}", e)})),
+        ));
+    }
+
+    debug!("dragging from [{}, {}] to [{}, {}]", from.x, from.y, to.x, to.y);
+    if let Err(e) = desktop.drag_mouse((from.x, from.y), (to.x, to.y)) {
+        error!("failed to drag element: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to drag element: {}", e)})),
+        ));
+    }
+
+    let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(DragByIndexWithElementsResponse {
+        drag: DragByIndexResponse {
+            success: true,
+            message: format!("dragged element with role: {} onto {}", source.role(), target_description),
+            from,
+            to,
+        },
+        elements: elements_response,
+        ui_state_changed,
+        focus: focus_guard.report().await,
+        screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
+    }))
+}
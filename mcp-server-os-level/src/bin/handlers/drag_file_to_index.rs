@@ -0,0 +1,157 @@
+use std::path::Path;
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, DragFileToIndexRequest, DragFileToIndexResponse, FocusChange, ListElementsAndAttributesResponse};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{cached_element_by_index, click_point, detect_ui_change, ensure_app_allowed, escape_applescript_string, last_ui_fingerprint, run_osascript, settle, FocusGuard};
+
+// Response type that combines both the drop result and elements
+#[derive(Serialize)]
+pub struct DragFileToIndexWithElementsResponse {
+    pub drag: DragFileToIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the drop (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+}
+
+// A process without a window can't start a drag session of its own, so the file is revealed
+// in Finder and its icon dragged onto the target with real mouse events. Finder then builds
+// the file pasteboard exactly like a manual drag would. The Finder window must not cover the
+// drop target.
+pub async fn drag_file_to_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DragFileToIndexRequest>,
+) -> Result<JsonResponse<DragFileToIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let path = match Path::new(&request.path).canonicalize() {
+        Ok(path) => path,
+        Err(e) => {
+            error!("file not found: {} ({})", request.path, e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({"error": format!("file not found: {} ({})", request.path, e)})),
+            ));
+        }
+    };
+
+    let (target, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    let drop_point = match target.bounds() {
+        Ok(bounds) => click_point(bounds),
+        Err(e) => {
+            error!("failed to get bounds of element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                JsonResponse(json!({"error": format!("drop target has no bounds: {}", e)})),
+            ));
+        }
+    };
+
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.restore_focus).await;
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
+    info!("dragging {} onto element {} in {}", path.display(), request.element_index, app_name);
+
+    // Reveal selects the file in a Finder window, which gives us an icon to pick up
+    let script = format!(
+        "tell application \"Finder\"\nreveal POSIX file \"{}\"\nactivate\nend tell",
+        escape_applescript_string(&path.to_string_lossy())
+    );
+    if let Err(e) = run_osascript(&script).await {
+        error!("failed to reveal file in finder: {}", e);
+        return Err(e.to_response());
+    }
+    settle(Duration::from_millis(800)).await;
+
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let icon = find_finder_icon(&desktop, &path).ok_or_else(|| {
+        error!("could not find the icon of {} in finder", path.display());
+        (
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("could not find the icon of {} in finder", path.display())})),
+        )
+    })?;
+    let drag_start = match icon.bounds() {
+        Ok(bounds) => click_point(bounds),
+        Err(e) => {
+            error!("failed to get bounds of the finder icon: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to get bounds of the finder icon: {}", e)})),
+            ));
+        }
+    };
+
+    debug!("dragging from [{}, {}] to [{}, {}]", drag_start.x, drag_start.y, drop_point.x, drop_point.y);
+    if let Err(e) = desktop.drag_mouse((drag_start.x, drag_start.y), (drop_point.x, drop_point.y)) {
+        error!("failed to drag file: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to drag file: {}", e)})),
+        ));
+    }
+
+    // Imports usually take a moment to show up in the target
+    let elements_response = refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), 1000).await;
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(DragFileToIndexWithElementsResponse {
+        drag: DragFileToIndexResponse {
+            success: true,
+            message: format!(
+                "dropped {} on element with role: {}",
+                path.display(),
+                target.role()
+            ),
+            drag_start,
+            drop_point,
+        },
+        elements: elements_response,
+        ui_state_changed,
+        focus: focus_guard.report().await,
+    }))
+}
+
+// The revealed file's icon (or list row title) in Finder. Finder hides extensions by
+// default, so the bare file stem matches too.
+fn find_finder_icon(desktop: &Desktop, path: &Path) -> Option<UIElement> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let file_stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string());
+
+    let finder = desktop.application("Finder").ok()?;
+    let elements = finder.locator("").ok()?.all().ok()?;
+
+    elements.into_iter().find(|element| {
+        let attrs = element.attributes();
+        let names_file = [attrs.label.as_deref(), attrs.value.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|text| text == file_name || Some(text) == file_stem.as_deref());
+        names_file && element.bounds().map_or(false, |(_, _, width, height)| width > 0.0 && height > 0.0)
+    })
+}
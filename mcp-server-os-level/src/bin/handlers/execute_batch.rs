@@ -0,0 +1,303 @@
+use std::{collections::HashMap, sync::Arc};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{
+    ActionOptions, AppState, BatchAction, BatchStepResult, ClickByIndexRequest, ExecuteBatchRequest, ExecuteBatchResponse,
+    InputAction, PressKeyByIndexRequest, RoleCount, ScreenshotOptions, StepElementSummary,
+    TypeByIndexRequest,
+};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::click_by_index::click_by_index_handler;
+use crate::handlers::input_control::perform_input_action;
+use crate::handlers::press_key_by_index::press_key_by_index_handler;
+use crate::handlers::type_by_index::type_by_index_handler;
+use crate::handlers::utils::{capture_action_screenshot, detect_ui_change, last_ui_fingerprint, settle};
+
+// Longest batch accepted, so one call can't tie up the desktop indefinitely
+const MAX_BATCH_STEPS: usize = 50;
+
+// debug_capture screenshots are kept small, since a batch can return fifty of them
+const DEBUG_SCREENSHOT_MAX_WIDTH: u32 = 640;
+const DEBUG_SCREENSHOT_QUALITY: u8 = 50;
+// Roles listed in a step's element summary
+const DEBUG_SUMMARY_ROLES: usize = 5;
+
+// Run a sequence of actions in one request: click a field, type, press Return. Each step
+// is what its own endpoint does, minus the listing each of those makes afterwards; the
+// app is listed once at the end (or after every step with refresh_after_each). Stops at
+// the first failing step and reports which one it was.
+pub async fn execute_batch_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ExecuteBatchRequest>,
+) -> Result<JsonResponse<ExecuteBatchResponse>, (StatusCode, JsonResponse<Value>)> {
+    if request.actions.is_empty() || request.actions.len() > MAX_BATCH_STEPS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("a batch takes 1 to {} actions, got {}", MAX_BATCH_STEPS, request.actions.len())
+            })),
+        ));
+    }
+
+    // The listing the by-index steps address is the one to refresh afterwards
+    let listed_app = state
+        .element_cache
+        .lock()
+        .await
+        .get(request.app_name.as_deref())
+        .map(|(_, _, cache_key)| cache_key);
+    let fingerprint_before = match &listed_app {
+        Some(app_name) => last_ui_fingerprint(&state, app_name).await,
+        None => None,
+    };
+
+    let delay = state.config.inter_action_delay(request.inter_action_delay_ms);
+    let step_count = request.actions.len();
+    let mut steps = Vec::with_capacity(step_count);
+    let mut failed_step = None;
+
+    for (step, action) in request.actions.into_iter().enumerate() {
+        if step > 0 {
+            settle(delay).await;
+        }
+
+        let name = action_name(&action);
+        info!("batch step {}/{}: {}", step + 1, step_count, name);
+        let (message, error) = match run_step(&state, request.app_name.as_deref(), action).await {
+            Ok(message) => (Some(message), None),
+            Err((status, JsonResponse(body))) => {
+                error!("batch step {} ({}) failed with {}: {}", step, name, status, body);
+                failed_step = Some(step);
+                (None, Some(body))
+            }
+        };
+
+        // The listing after the last step that runs is the batch's own, made below
+        let last = failed_step.is_some() || step + 1 == step_count;
+        let listing = match &listed_app {
+            Some(app_name) if !last && (request.refresh_after_each || request.debug_capture) => {
+                refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), state.config.refresh_delay_ms(request.delay_ms)).await
+            }
+            _ => None,
+        };
+        let (screenshot, element_summary) = match &listed_app {
+            Some(app_name) if request.debug_capture && !last => {
+                (capture_action_screenshot(app_name, &debug_screenshot_options()).await, listing.as_ref().map(|listing| step_summary(&listing.elements)))
+            }
+            _ => (None, None),
+        };
+        steps.push(BatchStepResult {
+            step,
+            action: name.to_string(),
+            success: error.is_none(),
+            message,
+            error,
+            elements: listing.filter(|_| request.refresh_after_each),
+            screenshot,
+            element_summary,
+        });
+        if failed_step.is_some() {
+            break;
+        }
+    }
+
+    // Earlier steps may have changed the UI even when a later one failed
+    let (elements, ui_state_changed) = match listed_app {
+        Some(app_name) => {
+            let elements = refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), state.config.refresh_delay_ms(request.delay_ms)).await;
+            let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements.is_some()).await;
+            if request.debug_capture {
+                if let Some(last) = steps.last_mut() {
+                    last.screenshot = capture_action_screenshot(&app_name, &debug_screenshot_options()).await;
+                    last.element_summary = elements.as_ref().map(|listing| step_summary(&listing.elements));
+                }
+            }
+            (elements, ui_state_changed)
+        }
+        None => (None, None),
+    };
+
+    let completed_steps = steps.iter().filter(|step| step.success).count();
+    Ok(JsonResponse(ExecuteBatchResponse {
+        success: failed_step.is_none(),
+        completed_steps,
+        failed_step,
+        steps,
+        elements,
+        ui_state_changed,
+    }))
+}
+
+fn debug_screenshot_options() -> ScreenshotOptions {
+    ScreenshotOptions {
+        include_screenshot: true,
+        screenshot_max_width: Some(DEBUG_SCREENSHOT_MAX_WIDTH),
+        screenshot_quality: Some(DEBUG_SCREENSHOT_QUALITY),
+    }
+}
+
+// The element count, most common roles and focused element of a listing
+fn step_summary(elements: &[Value]) -> StepElementSummary {
+    let mut role_counts: HashMap<&str, usize> = HashMap::new();
+    for element in elements {
+        *role_counts.entry(element["role"].as_str().unwrap_or("unknown")).or_default() += 1;
+    }
+    let mut roles: Vec<RoleCount> = role_counts
+        .into_iter()
+        .map(|(role, count)| RoleCount { role: role.to_string(), count })
+        .collect();
+    roles.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.role.cmp(&b.role)));
+    roles.truncate(DEBUG_SUMMARY_ROLES);
+
+    StepElementSummary {
+        element_count: elements.len(),
+        roles,
+        focused: elements
+            .iter()
+            .find(|element| element["focused"].as_bool().unwrap_or(false))
+            .cloned(),
+    }
+}
+
+// Run one step through the same code as its own endpoint, without that endpoint's refresh
+async fn run_step(
+    state: &Arc<AppState>,
+    app_name: Option<&str>,
+    action: BatchAction,
+) -> Result<String, (StatusCode, JsonResponse<Value>)> {
+    // A step is the tool it stands for, so DISABLED_TOOLS applies to it as well
+    let tool = step_tool(&action);
+    if !state.config.is_tool_enabled(tool) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            JsonResponse(json!({
+                "error": format!("METHOD_DISABLED: tool {} is disabled on this server", tool),
+                "code": "METHOD_DISABLED"
+            })),
+        ));
+    }
+
+    let input = match action {
+        BatchAction::KeyPress(key) => InputAction::KeyPress(key),
+        BatchAction::MouseMove { x, y } => InputAction::MouseMove { x, y },
+        BatchAction::MouseClick(button) => InputAction::MouseClick(button),
+        BatchAction::WriteText(text) => InputAction::WriteText(text),
+        BatchAction::KeyDown(key_code) => InputAction::KeyDown(key_code),
+        BatchAction::KeyUp(key_code) => InputAction::KeyUp(key_code),
+        BatchAction::ClickByIndex { element_index, click_count } => {
+            let response = click_by_index_handler(
+                State(state.clone()),
+                Json(ClickByIndexRequest {
+                    element_index,
+                    app_name: app_name.map(str::to_string),
+                    click_count,
+                    retry_if_unchanged: false,
+                    options: ActionOptions { refresh_elements: Some(false), ..Default::default() },
+                }),
+            )
+            .await?;
+            return Ok(response.0.click.message);
+        }
+        BatchAction::TypeByIndex { element_index, text, confirm_after, method } => {
+            let response = type_by_index_handler(
+                State(state.clone()),
+                Json(TypeByIndexRequest {
+                    element_index,
+                    app_name: app_name.map(str::to_string),
+                    text,
+                    confirm_after,
+                    inter_action_delay_ms: None,
+                    strict_focus: false,
+                    method,
+                    focus_method: Default::default(),
+                    options: ActionOptions { refresh_elements: Some(false), ..Default::default() },
+                }),
+            )
+            .await?;
+            return Ok(response.0.type_action.message);
+        }
+        BatchAction::PressKeyByIndex { element_index, key_combo } => {
+            let response = press_key_by_index_handler(
+                State(state.clone()),
+                Json(PressKeyByIndexRequest {
+                    element_index,
+                    app_name: app_name.map(str::to_string),
+                    key_combo,
+                    inter_action_delay_ms: None,
+                    focus_method: Default::default(),
+                    options: ActionOptions { refresh_elements: Some(false), ..Default::default() },
+                }),
+            )
+            .await?;
+            return Ok(response.0.press_key.message);
+        }
+    };
+
+    perform_input_action(&state.config, input).await?;
+    Ok("input sent".to_string())
+}
+
+fn action_name(action: &BatchAction) -> &'static str {
+    match action {
+        BatchAction::KeyPress(_) => "KeyPress",
+        BatchAction::MouseMove { .. } => "MouseMove",
+        BatchAction::MouseClick(_) => "MouseClick",
+        BatchAction::WriteText(_) => "WriteText",
+        BatchAction::KeyDown(_) => "KeyDown",
+        BatchAction::KeyUp(_) => "KeyUp",
+        BatchAction::ClickByIndex { .. } => "ClickByIndex",
+        BatchAction::TypeByIndex { .. } => "TypeByIndex",
+        BatchAction::PressKeyByIndex { .. } => "PressKeyByIndex",
+    }
+}
+
+// The tool whose switch governs a step; raw input steps are inputControl's
+fn step_tool(action: &BatchAction) -> &'static str {
+    match action {
+        BatchAction::ClickByIndex { .. } => "clickByIndex",
+        BatchAction::TypeByIndex { .. } => "typeByIndex",
+        BatchAction::PressKeyByIndex { .. } => "pressKeyByIndex",
+        _ => "inputControl",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::utils::test_state;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn refuses_steps_of_disabled_tools() {
+        let mut state = test_state();
+        state.config.disabled_tools = ["clickByIndex".to_string(), "inputControl".to_string()].into_iter().collect();
+        let state = Arc::new(state);
+
+        let click = BatchAction::ClickByIndex { element_index: 0, click_count: None };
+        let (status, JsonResponse(body)) = run_step(&state, None, click).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "METHOD_DISABLED");
+
+        let key = BatchAction::KeyPress("return".to_string());
+        let (_, JsonResponse(body)) = run_step(&state, None, key).await.unwrap_err();
+        assert_eq!(body["code"], "METHOD_DISABLED");
+    }
+
+    #[test]
+    fn summarizes_step_listings_by_role() {
+        let mut elements: Vec<Value> = (0..7).map(|index| json!({"index": index, "role": "AXButton"})).collect();
+        elements.push(json!({"index": 7, "role": "AXTextField", "focused": true}));
+        elements.extend((8..14).map(|index| json!({"index": index, "role": format!("AXRole{}", index)})));
+
+        let summary = step_summary(&elements);
+        assert_eq!(summary.element_count, 14);
+        assert_eq!(summary.roles.len(), DEBUG_SUMMARY_ROLES);
+        assert_eq!(summary.roles[0], RoleCount { role: "AXButton".to_string(), count: 7 });
+        assert_eq!(summary.focused.unwrap()["index"], 7);
+    }
+}
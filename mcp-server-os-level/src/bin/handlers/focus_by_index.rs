@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{error, info};
+use computer_use_ai_sdk::Desktop;
+
+use crate::types::{AppState, FocusByIndexRequest, FocusByIndexResponse, FocusMethod, TypingMethod, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot};
+use crate::refresh_after_action;
+use crate::handlers::press_key_by_index::convert_key_combo_to_applescript;
+use crate::handlers::set_field_by_index::enter_text;
+use crate::handlers::utils::{cached_element_by_index, capture_action_screenshot, detect_ui_change, ensure_app_allowed, focus_element, last_ui_fingerprint, resolve_application, run_osascript, settle, FocusGuard};
+
+// Response type that combines both results
+#[derive(Serialize)]
+pub struct FocusByIndexWithElementsResponse {
+    pub focus_action: FocusByIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the action (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
+}
+
+// Focus an element through the accessibility API instead of clicking it, for elements a
+// click would also act on (buttons, links, checkboxes), then optionally type or press a key.
+// Fails without sending any input when the element doesn't take the focus.
+pub async fn focus_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<FocusByIndexRequest>,
+) -> Result<JsonResponse<FocusByIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+
+    // Compare the listing before and after to tell whether the action did anything
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
+    // Keystrokes go to the frontmost app, so activate it first
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    if let Err(e) = resolve_application(&desktop, &app_name, None) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("application not found: {}", e)})),
+        ));
+    }
+
+    if let Err(e) = focus_element(&element, FocusMethod::Accessibility) {
+        error!("failed to focus element {}: {}", request.element_index, e);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("element {} does not accept accessibility focus: {} - nothing was sent; use typeByIndex or pressKeyByIndex with focus_method click instead",
+                                request.element_index, e)
+            })),
+        ));
+    }
+    info!("focused element {} ({}) in {}", request.element_index, element.role(), app_name);
+
+    let wants_input = request.text.is_some() || request.key_combo.is_some();
+    if wants_input {
+        // Let the focus change (and any animation) settle before sending input
+        settle(state.config.inter_action_delay(request.inter_action_delay_ms)).await;
+    }
+
+    if let Some(text) = &request.text {
+        let typed = match request.method.resolve(text) {
+            TypingMethod::Accessibility => element.type_text(text).map_err(|e| e.to_string()),
+            method => enter_text(text, method == TypingMethod::Paste).await.map_err(|e| e.to_string()),
+        };
+        if let Err(e) = typed {
+            error!("failed to type into element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("focused element {} but failed to type: {}", request.element_index, e)})),
+            ));
+        }
+    }
+
+    if let Some(key_combo) = &request.key_combo {
+        if let Err(e) = run_osascript(&convert_key_combo_to_applescript(key_combo)).await {
+            error!("failed to press {} on element {}: {}", key_combo, request.element_index, e);
+            return Err(e.to_response());
+        }
+    }
+
+    let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(FocusByIndexWithElementsResponse {
+        focus_action: FocusByIndexResponse {
+            success: true,
+            message: format!("focused element with role: {} without clicking", element.role()),
+            typed: request.text.is_some(),
+            key_pressed: request.key_combo.is_some(),
+        },
+        elements: elements_response,
+        ui_state_changed,
+        focus: focus_guard.report().await,
+        screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
+    }))
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, GetAppMetricsRequest, GetAppMetricsResponse};
+use crate::handlers::list_elements_and_attributes::is_non_interactable;
+use crate::handlers::utils::{app_identity, resolve_application};
+
+// Stop counting here; an app this big needs filters whatever the exact number is
+const MAX_COUNTED_ELEMENTS: usize = 50_000;
+
+// Counts of an app's tree from a walk that reads nothing but each element's role and
+// children, so it costs a fraction of a listing (no attributes, text or bounds)
+pub async fn get_app_metrics_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetAppMetricsRequest>,
+) -> Result<JsonResponse<GetAppMetricsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let start = Instant::now();
+    info!("measuring accessibility tree of {}", request.bundle_id.as_deref().unwrap_or(&request.app_name));
+
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let app = match resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
+        Ok(app) => app,
+        Err(e) => {
+            error!("application not found: {}", e);
+            return Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({
+                    "error": format!("application not found: {}", e)
+                })),
+            ));
+        }
+    };
+    let (app_name, bundle_id) = app_identity(&desktop, &app);
+    let config_app_name = app_name.clone().unwrap_or_else(|| request.app_name.clone());
+
+    let metrics = measure_tree(&app, |role| !is_non_interactable(&state.config, &config_app_name, role));
+    info!("{}: {} elements, depth {}, {} windows, {} interactable (truncated: {})",
+          config_app_name, metrics.element_count, metrics.max_depth, metrics.window_count,
+          metrics.interactable_count, metrics.truncated);
+
+    Ok(JsonResponse(GetAppMetricsResponse {
+        app_name,
+        bundle_id,
+        element_count: metrics.element_count,
+        max_depth: metrics.max_depth,
+        window_count: metrics.window_count,
+        interactable_count: metrics.interactable_count,
+        truncated: metrics.truncated,
+        processing_time_ms: start.elapsed().as_millis() as u64,
+    }))
+}
+
+#[derive(Debug, Default)]
+struct TreeMetrics {
+    element_count: usize,
+    max_depth: usize,
+    window_count: usize,
+    interactable_count: usize,
+    truncated: bool,
+}
+
+// Depth-first walk below the application element; an element reachable twice (some apps
+// report a window both as a child and through a parent link) is counted once
+fn measure_tree(app: &UIElement, is_interactable: impl Fn(&str) -> bool) -> TreeMetrics {
+    let mut metrics = TreeMetrics::default();
+    let mut seen: HashSet<UIElement> = HashSet::new();
+    let mut stack: Vec<(UIElement, usize)> = app
+        .children()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| (child, 1))
+        .collect();
+
+    while let Some((element, depth)) = stack.pop() {
+        if !seen.insert(element.clone()) {
+            continue;
+        }
+        if metrics.element_count >= MAX_COUNTED_ELEMENTS {
+            metrics.truncated = true;
+            break;
+        }
+
+        let role = element.role();
+        metrics.element_count += 1;
+        metrics.max_depth = metrics.max_depth.max(depth);
+        if role == "AXWindow" {
+            metrics.window_count += 1;
+        }
+        if is_interactable(&role) {
+            metrics.interactable_count += 1;
+        }
+
+        stack.extend(element.children().unwrap_or_default().into_iter().map(|child| (child, depth + 1)));
+    }
+
+    metrics
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::UIElement;
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, ElementBounds, GetCaretByIndexRequest, GetCaretByIndexResponse, TextCaret};
+use crate::handlers::utils::cached_element_by_index;
+
+// The insertion point of a text field or text area: its offset from AXSelectedTextRange
+// and, where the app supports AXBoundsForRange, where it is on screen
+pub async fn get_caret_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetCaretByIndexRequest>,
+) -> Result<JsonResponse<GetCaretByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+
+    let range = match element.selected_text_range() {
+        Ok(range) => range,
+        Err(e) => {
+            error!("failed to read the selected text range of element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to read the caret of element {}: {}", request.element_index, e)
+                })),
+            ));
+        }
+    };
+
+    let caret = range.map(|(offset, selection_length)| TextCaret {
+        offset,
+        selection_length,
+        bounds: caret_bounds(&element, offset, selection_length)
+            .map(|(x, y, width, height)| ElementBounds { x, y, width, height }),
+    });
+    info!("caret of element {} in {}: {:?}", request.element_index, app_name, caret);
+
+    Ok(JsonResponse(GetCaretByIndexResponse {
+        element_index: request.element_index,
+        role: element.role(),
+        caret,
+    }))
+}
+
+// Bounds of the selection, or of a plain caret. Many apps report nothing (or an empty rect)
+// for a zero-length range, so fall back to the right edge of the character before it.
+fn caret_bounds(element: &UIElement, offset: usize, selection_length: usize) -> Option<(f64, f64, f64, f64)> {
+    let reported = element.bounds_for_range(offset, selection_length).ok().flatten();
+    if let Some(bounds) = reported.filter(|(_, _, _, height)| *height > 0.0) {
+        return Some(bounds);
+    }
+    if offset == 0 {
+        return None;
+    }
+    debug!("no bounds for the caret itself, using the character before it");
+    element
+        .bounds_for_range(offset - 1, 1)
+        .ok()
+        .flatten()
+        .filter(|(_, _, _, height)| *height > 0.0)
+        .map(|(x, y, width, height)| (x + width, y, 0.0, height))
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, ElementBounds, GetClickPointByIndexRequest, GetClickPointByIndexResponse};
+use crate::handlers::utils::{cached_element_by_index, click_point};
+
+// Report where clickByIndex would click an element, without clicking it
+pub async fn get_click_point_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetClickPointByIndexRequest>,
+) -> Result<JsonResponse<GetClickPointByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+
+    let (x, y, width, height) = match element.bounds() {
+        Ok(bounds) => bounds,
+        Err(e) => {
+            error!("failed to get bounds of element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                JsonResponse(json!({
+                    "error": format!("element has no bounds, clickByIndex would fall back to AXPress: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let point = click_point((x, y, width, height));
+    info!("click point for element {} in {}: [{}, {}]", request.element_index, app_name, point.x, point.y);
+
+    Ok(JsonResponse(GetClickPointByIndexResponse {
+        element_index: request.element_index,
+        role: element.role(),
+        bounds: ElementBounds { x, y, width, height },
+        click_point: point,
+    }))
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::Value;
+use tracing::info;
+
+use crate::types::{AppState, ElementBounds, GetElementByIndexRequest, GetElementByIndexResponse};
+use crate::handlers::utils::cached_element_by_index;
+
+// Full dump of one cached element, for working out why an action on it misbehaves.
+// Listings drop parent/child/window references and fold the texts into one field to stay
+// small; this returns every attribute as the accessibility API reports it instead.
+pub async fn get_element_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetElementByIndexRequest>,
+) -> Result<JsonResponse<GetElementByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+
+    let attrs = element.attributes();
+    info!("element {} in {} has {} attributes", request.element_index, app_name, attrs.properties.len());
+
+    Ok(JsonResponse(GetElementByIndexResponse {
+        element_index: request.element_index,
+        app_name,
+        role: attrs.role,
+        label: attrs.label,
+        value: attrs.value,
+        description: attrs.description,
+        properties: attrs.properties.into_iter().collect(),
+        bounds: element.bounds().ok().map(|(x, y, width, height)| ElementBounds { x, y, width, height }),
+        enabled: element.is_enabled().ok(),
+        focused: element.is_focused().ok(),
+        visible: element.is_visible().ok(),
+        actions: element.available_actions().unwrap_or_default(),
+    }))
+}
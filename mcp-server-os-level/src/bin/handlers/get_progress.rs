@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, Selector, UIElement};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, GetProgressRequest, GetProgressResponse, ProgressIndicatorInfo};
+use crate::handlers::utils::resolve_application;
+
+const PROGRESS_QUERY: &str = "role=AXProgressIndicator|AXBusyIndicator";
+
+pub async fn get_progress_handler(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<GetProgressRequest>,
+) -> Result<JsonResponse<GetProgressResponse>, (StatusCode, JsonResponse<Value>)> {
+    let app_name = request.bundle_id.as_deref().unwrap_or(&request.app_name);
+    info!("reading progress indicators for app: {}", app_name);
+
+    // Don't activate the app, polling progress shouldn't steal focus
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let app = match resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
+        Ok(app) => app,
+        Err(e) => {
+            error!("application not found: {}", e);
+            return Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({
+                    "error": format!("application not found: {}", e)
+                })),
+            ));
+        }
+    };
+
+    // Only walk for indicator roles instead of listing everything and filtering
+    let elements = match Selector::query(PROGRESS_QUERY)
+        .and_then(|selector| app.locator(selector))
+        .and_then(|locator| locator.all())
+    {
+        Ok(elements) => elements,
+        Err(e) => {
+            error!("failed to get elements: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to get elements: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let indicators: Vec<ProgressIndicatorInfo> = elements.iter().map(progress_info).collect();
+
+    let busy = indicators.iter().any(|indicator| {
+        indicator.indeterminate || indicator.fraction.map_or(false, |fraction| fraction < 1.0)
+    });
+
+    info!("found {} progress indicators in {} (busy: {})", indicators.len(), app_name, busy);
+
+    Ok(JsonResponse(GetProgressResponse { indicators, busy }))
+}
+
+fn progress_info(element: &UIElement) -> ProgressIndicatorInfo {
+    let attrs = element.attributes();
+    let number = |name: &str| {
+        attrs
+            .properties
+            .get(name)
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_f64())
+    };
+
+    let value = number("AXValue");
+    let min_value = number("AXMinValue");
+    let max_value = number("AXMaxValue");
+
+    let fraction = match (value, min_value, max_value) {
+        (Some(value), Some(min), Some(max)) if max > min => Some(((value - min) / (max - min)).clamp(0.0, 1.0)),
+        _ => None,
+    };
+
+    // Busy indicators are always spinners; progress bars are indeterminate when they report no value
+    let indeterminate = attrs.role == "AXBusyIndicator" || value.is_none();
+
+    ProgressIndicatorInfo {
+        role: attrs.role.clone(),
+        label: attrs.label.clone().or_else(|| attrs.description.clone()),
+        value,
+        min_value,
+        max_value,
+        fraction,
+        indeterminate,
+    }
+}
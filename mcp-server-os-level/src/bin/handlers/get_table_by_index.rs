@@ -0,0 +1,170 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::UIElement;
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, GetTableByIndexRequest, GetTableByIndexResponse};
+use crate::handlers::utils::cached_element_by_index;
+
+const TABLE_ROLES: [&str; 3] = ["AXTable", "AXOutline", "AXList"];
+const DEFAULT_MAX_ROWS: usize = 200;
+const MAX_ROWS_LIMIT: usize = 1000;
+// Cells often wrap their text in a static text or a text field and an image
+const CELL_TEXT_DEPTH: usize = 4;
+
+// Read a table as rows of cell text, instead of the flat list of cells the
+// listing gives, keeping the header row and the nesting of outline rows
+pub async fn get_table_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetTableByIndexRequest>,
+) -> Result<JsonResponse<GetTableByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+
+    let Some(table) = find_table(&element) else {
+        error!("element {} is not a table, outline or list: {}", request.element_index, element.role());
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("element {} is a {}, not a table, outline or list", request.element_index, element.role())
+            })),
+        ));
+    };
+    let role = table.role();
+
+    let children = match table.children() {
+        Ok(children) => children,
+        Err(e) => {
+            error!("failed to read table rows: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to read table rows: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let max_rows = request.max_rows.unwrap_or(DEFAULT_MAX_ROWS).min(MAX_ROWS_LIMIT);
+    let mut header = None;
+    let mut column_titles = Vec::new();
+    let mut rows = Vec::new();
+    let mut depths = Vec::new();
+    let mut truncated = false;
+
+    for child in &children {
+        match child.role().as_str() {
+            // The header row: a group of column header buttons
+            "AXGroup" if header.is_none() && rows.is_empty() && role != "AXList" => {
+                let titles = cells_text(child);
+                if titles.iter().any(|title| !title.is_empty()) {
+                    header = Some(titles);
+                }
+            }
+            // Columns carry no cells, but their titles stand in for a missing header row
+            "AXColumn" => column_titles.push(child.attributes().label.unwrap_or_default()),
+            _ => {
+                if rows.len() >= max_rows {
+                    truncated = true;
+                    break;
+                }
+                rows.push(row_cells(child, &role));
+                depths.push(disclosure_level(child));
+            }
+        }
+    }
+
+    if header.is_none() && column_titles.iter().any(|title| !title.is_empty()) {
+        header = Some(column_titles);
+    }
+
+    let column_count = pad_rows(&mut rows, header.as_ref().map_or(0, Vec::len));
+    debug!("read {} rows x {} columns from {} (truncated: {})", rows.len(), column_count, role, truncated);
+    info!("read {} element {} in {} as {} rows", role, request.element_index, app_name, rows.len());
+
+    Ok(JsonResponse(GetTableByIndexResponse {
+        element_index: request.element_index,
+        depths: (role == "AXOutline").then_some(depths),
+        role,
+        header,
+        rows,
+        column_count,
+        truncated,
+    }))
+}
+
+// The element itself if it is a table, or a table directly inside it (tables are
+// usually listed through their scroll area)
+fn find_table(element: &UIElement) -> Option<UIElement> {
+    if TABLE_ROLES.contains(&element.role().as_str()) {
+        return Some(element.clone());
+    }
+    element
+        .children()
+        .ok()?
+        .into_iter()
+        .find(|child| TABLE_ROLES.contains(&child.role().as_str()))
+}
+
+// A row's cells; list items without cells read as a single column
+fn row_cells(row: &UIElement, table_role: &str) -> Vec<String> {
+    if table_role == "AXList" && row.role() != "AXRow" {
+        return vec![cell_text(row)];
+    }
+    let cells = cells_text(row);
+    if cells.is_empty() {
+        vec![cell_text(row)]
+    } else {
+        cells
+    }
+}
+
+fn cells_text(element: &UIElement) -> Vec<String> {
+    element
+        .children()
+        .unwrap_or_default()
+        .iter()
+        .map(cell_text)
+        .collect()
+}
+
+fn cell_text(cell: &UIElement) -> String {
+    cell.text(CELL_TEXT_DEPTH).map(|text| text.trim().to_string()).unwrap_or_default()
+}
+
+fn disclosure_level(row: &UIElement) -> usize {
+    row.attributes()
+        .properties
+        .get("AXDisclosureLevel")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize
+}
+
+// Pad every row to the widest row (or the header), returning the column count
+fn pad_rows(rows: &mut [Vec<String>], header_width: usize) -> usize {
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0).max(header_width);
+    for row in rows.iter_mut() {
+        row.resize(column_count, String::new());
+    }
+    column_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_rows_to_the_widest_row_or_header() {
+        let mut rows = vec![vec!["a".to_string()], vec!["b".to_string(), "c".to_string()]];
+        assert_eq!(pad_rows(&mut rows, 0), 2);
+        assert_eq!(rows[0], vec!["a".to_string(), String::new()]);
+
+        assert_eq!(pad_rows(&mut rows, 3), 3);
+        assert!(rows.iter().all(|row| row.len() == 3));
+    }
+}
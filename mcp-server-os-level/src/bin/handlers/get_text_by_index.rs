@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, GetTextByIndexRequest, GetTextByIndexResponse};
+use crate::handlers::utils::cached_element_by_index;
+
+const DEFAULT_DEPTH: usize = 10;
+const MAX_DEPTH: usize = 25;
+
+// "Read this whole region": the listing only carries each element's own text, so a
+// message bubble or a card reads as its container with the text spread over children.
+// Also the explicit check after typing: the field's own value, and whether it holds expected_text
+pub async fn get_text_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetTextByIndexRequest>,
+) -> Result<JsonResponse<GetTextByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    let depth = request.include_children.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+
+    let text = match element.text(depth) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("failed to read text of element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to read text of element {}: {}", request.element_index, e)
+                })),
+            ));
+        }
+    };
+
+    let value = element.attributes().value;
+    let contains_expected = request.expected_text.as_deref().map(|expected| {
+        value.as_deref().map_or(false, |value| value.contains(expected)) || text.contains(expected)
+    });
+
+    info!("read {} chars of text from element {} in {} (depth {})", text.len(), request.element_index, app_name, depth);
+
+    Ok(JsonResponse(GetTextByIndexResponse {
+        element_index: request.element_index,
+        role: element.role(),
+        text,
+        depth,
+        value,
+        contains_expected,
+    }))
+}
@@ -0,0 +1,46 @@
+use axum::{http::StatusCode, response::Json as JsonResponse};
+
+use crate::types::{HealthResponse, ReadyResponse};
+
+// Liveness for supervisors: answers as long as the process serves HTTP, without touching
+// the accessibility APIs
+pub async fn health_handler() -> JsonResponse<HealthResponse> {
+    JsonResponse(HealthResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+// Readiness: whether the server can actually automate right now. 503 while accessibility
+// permissions are missing, so "process up" and "process up but useless" can be told apart
+pub async fn ready_handler() -> (StatusCode, JsonResponse<ReadyResponse>) {
+    let (accessibility_granted, error) = accessibility_status();
+    let status = if accessibility_granted { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        status,
+        JsonResponse(ReadyResponse {
+            ready: accessibility_granted,
+            accessibility_granted,
+            error,
+        }),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn accessibility_status() -> (bool, Option<String>) {
+    use computer_use_ai_sdk::platforms::macos::check_accessibility_permissions;
+
+    match check_accessibility_permissions(false) {
+        Ok(granted) => (granted, None),
+        Err(e) => {
+            tracing::warn!("accessibility permissions check failed: {}", e);
+            (false, Some(e.to_string()))
+        }
+    }
+}
+
+// Other platforms have no permission to grant
+#[cfg(not(target_os = "macos"))]
+fn accessibility_status() -> (bool, Option<String>) {
+    (true, None)
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{error, info};
+use computer_use_ai_sdk::Desktop;
+
+use crate::types::{AppState, HoverByIndexRequest, HoverByIndexResponse, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{cached_element_by_index, capture_action_screenshot, click_point, detect_ui_change, ensure_app_allowed, last_ui_fingerprint, resolve_application, settle, FocusGuard};
+
+const DEFAULT_DWELL_MS: u64 = 500;
+const MAX_DWELL_MS: u64 = 10_000;
+
+// Response type that combines both results
+#[derive(Serialize)]
+pub struct HoverByIndexWithElementsResponse {
+    pub hover: HoverByIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the hover (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
+}
+
+// Move the cursor onto an element's center and leave it there, then list again so tooltips,
+// hover menus and other mouse-over UI get indices
+pub async fn hover_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<HoverByIndexRequest>,
+) -> Result<JsonResponse<HoverByIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    // The dwell is the wait before listing again, so delay_ms means the same here
+    let dwell_ms = request.dwell_ms.or(request.options.delay_ms).unwrap_or(DEFAULT_DWELL_MS).min(MAX_DWELL_MS);
+
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    let point = match element.bounds() {
+        Ok(bounds) => click_point(bounds),
+        Err(e) => {
+            error!("failed to get bounds of element {}: {}", request.element_index, e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({
+                    "error": format!("element {} has no position on screen to hover over: {}", request.element_index, e)
+                })),
+            ));
+        }
+    };
+    info!("hovering over element {} in {} at [{}, {}] for {}ms", request.element_index, app_name, point.x, point.y, dwell_ms);
+
+    // Compare the listing before and after to tell whether anything appeared
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
+    // Most apps only track the mouse in their windows while they're in front
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    if let Err(e) = resolve_application(&desktop, &app_name, None) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("application not found: {}", e)})),
+        ));
+    }
+
+    // A real mouse-moved event, which hover tracking reacts to (unlike setting the position)
+    if let Err(e) = desktop.move_mouse((point.x, point.y)) {
+        error!("failed to move the cursor: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to move the cursor: {}", e)})),
+        ));
+    }
+
+    // The dwell doubles as the wait before listing again
+    let elements_response = if request.options.refresh_elements == Some(false) {
+        settle(Duration::from_millis(dwell_ms)).await;
+        None
+    } else {
+        refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), dwell_ms).await
+    };
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(HoverByIndexWithElementsResponse {
+        hover: HoverByIndexResponse {
+            success: true,
+            message: format!("hovered over element with role: {} for {}ms", element.role(), dwell_ms),
+            point,
+        },
+        elements: elements_response,
+        ui_state_changed,
+        focus: focus_guard.report().await,
+        screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
+    }))
+}
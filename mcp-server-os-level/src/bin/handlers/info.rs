@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use axum::{extract::State, response::Json as JsonResponse};
+
+use crate::handlers::mcp::tool_definitions;
+use crate::types::{AppState, ServerInfoResponse};
+
+pub async fn info_handler(State(state): State<Arc<AppState>>) -> JsonResponse<ServerInfoResponse> {
+    let (enabled_tools, disabled_tools): (Vec<String>, Vec<String>) = tool_definitions()
+        .into_iter()
+        .map(|tool| tool.name)
+        .partition(|name| state.config.is_tool_enabled(name));
+
+    JsonResponse(ServerInfoResponse {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        enabled_tools,
+        disabled_tools,
+        default_browser: state.config.default_browser.clone(),
+        safe_mode_allowed_apps: state.config.safe_mode_allowed_apps.as_ref().map(|apps| {
+            let mut apps: Vec<String> = apps.iter().cloned().collect();
+            apps.sort();
+            apps
+        }),
+    })
+}
@@ -4,13 +4,15 @@ use axum::{
     response::Json as JsonResponse,
 };
 use serde_json;
-use std::process::Command;
 use std::sync::Arc;
-use tokio::time::Duration;
 use tracing::{error, info};
+use computer_use_ai_sdk::{Desktop, MouseButton};
 
 use crate::types::*;
 use crate::AppState;
+use crate::config::ServerConfig;
+use crate::handlers::press_key_by_index::special_key_code;
+use crate::handlers::utils::{detect_ui_change, ensure_app_allowed, frontmost_app_name, last_ui_fingerprint, refresh_elements_and_attributes_after_action, run_osascript, send_key_combo, send_mouse_click, send_mouse_move, send_text};
 
 // Define the handler for input control
 pub async fn input_control_handler(
@@ -18,46 +20,79 @@ pub async fn input_control_handler(
     Json(payload): Json<InputControlRequest>,
 ) -> Result<JsonResponse<InputControlWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
     info!("input control handler {:?}", payload);
+
+    // The app from the last listing is the one the input is assumed to go to
+    let cached_app_name = {
+        let cache = state.element_cache.lock().await;
+        cache.latest_key().map(str::to_string)
+    };
+    let fingerprint_before = match &cached_app_name {
+        Some(app_name) => last_ui_fingerprint(&state, app_name).await,
+        None => None,
+    };
+    
+    perform_input_action(&state.config, payload.action).await?;
+
+    // Refresh the elements of the app from the last listing (without holding the cache lock,
+    // the listing takes it too)
+    let (elements_response, ui_state_changed) = match cached_app_name {
+        Some(app_name) => {
+            info!("refreshing elements for app: {}", app_name);
+            let elements_response = refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), state.config.refresh_delay_ms(payload.delay_ms)).await;
+            let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+            (elements_response, ui_state_changed)
+        }
+        None => {
+            // No cache available, don't try to refresh elements
+            info!("no element cache found, skipping element refresh");
+            (None, None)
+        }
+    };
     
+    // Return combined response
+    Ok(JsonResponse(InputControlWithElementsResponse {
+        input: InputControlResponse { success: true },
+        elements: elements_response,
+        ui_state_changed,
+    }))
+}
+
+// Send one raw input event; shared with executeBatch, which sends several before refreshing
+pub async fn perform_input_action(config: &ServerConfig, action: InputAction) -> Result<(), (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Raw events go to whatever is frontmost, so that's the app safe mode has to allow
+    if config.safe_mode_allowed_apps.is_some() {
+        let frontmost = frontmost_app_name().await.unwrap_or_default();
+        ensure_app_allowed(config, &frontmost, None)?;
+    }
+
     // Execute appropriate input action
-    match payload.action {
+    match action {
         InputAction::KeyPress(key) => {
-            // Add key name to key code mapping
-            let key_code = match key.as_str() {
-                "Tab" => "48",      // Tab key code
-                "Return" => "36",   // Enter/Return key code
-                "Space" => "49",    // Space key code
-                "Escape" => "53",   // Escape key code
-                // Add more key mappings as needed
-                _ => key.as_str(),  // Use as-is if it's already a number
-            };
-            
-            let script = format!("tell application \"System Events\" to key code {}", key_code);
-            info!("executing key press script: {}", script);
-            if let Err(e) = Command::new("osascript").arg("-e").arg(script).output() {
-                error!("failed to press key: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    JsonResponse(serde_json::json!({"error": format!("failed to press key: {}", e)})),
-                ));
-            }
-        }
-        InputAction::MouseMove { x, y } => {
-            // Implement mouse move
-            let script = format!("tell application \"System Events\" to set mouse position to {{{}, {}}}", x, y);
-            if let Err(e) = Command::new("osascript").arg("-e").arg(script).output() {
-                error!("failed to move mouse: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    JsonResponse(serde_json::json!({"error": format!("failed to move mouse: {}", e)})),
-                ));
+            // A raw key code only as "code:5", so a digit like "5" types that digit; otherwise
+            // a key or a combo like "cmd+shift+4" the way pressKeyByIndex takes it
+            let key = key.trim();
+            let main_key = key.rsplit('+').next().unwrap_or_default().trim();
+            match explicit_key_code(key) {
+                Some(key_code) => press_key_code(key_code).await?,
+                None if main_key.chars().count() == 1 || special_key_code(main_key).is_some() => {
+                    info!("pressing {}", key);
+                    send_key_combo(key).await?;
+                }
+                None => {
+                    error!("unknown key: {}", key);
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        JsonResponse(serde_json::json!({"error": format!("unknown key: {} - use a key name such as Return, F5 or PageDown, a single character, or a key code like code:49, optionally with modifiers like cmd+shift+4", key)})),
+                    ));
+                }
             }
         }
+        InputAction::MouseMove { x, y } => send_mouse_move((x as f64, y as f64)).await?,
         InputAction::MouseClick(button) => {
-            // Implement mouse click
-            let button_num = match button.as_str() {
-                "left" => 1,
-                "right" => 2,
+            let button = match button.as_str() {
+                "left" => MouseButton::Left,
+                "right" => MouseButton::Right,
+                "middle" => MouseButton::Middle,
                 _ => {
                     error!("unsupported mouse button: {}", button);
                     return Err((
@@ -66,78 +101,71 @@ pub async fn input_control_handler(
                     ));
                 }
             };
-            
-            let script = format!("tell application \"System Events\" to click button {}", button_num);
-            if let Err(e) = Command::new("osascript").arg("-e").arg(script).output() {
-                error!("failed to click mouse: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    JsonResponse(serde_json::json!({"error": format!("failed to click mouse: {}", e)})),
-                ));
-            }
-        }
-        InputAction::WriteText(text) => {
-            // Implement text writing
-            let script = format!("tell application \"System Events\" to keystroke \"{}\"", text);
-            if let Err(e) = Command::new("osascript").arg("-e").arg(script).output() {
-                error!("failed to write text: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    JsonResponse(serde_json::json!({"error": format!("failed to write text: {}", e)})),
-                ));
-            }
+            // At the cursor, wherever a MouseMove left it
+            send_mouse_click(None, button, 1).await?;
         }
+        InputAction::KeyDown(key_code) => post_raw_key(key_code, true)?,
+        InputAction::KeyUp(key_code) => post_raw_key(key_code, false)?,
+        InputAction::WriteText(text) => send_text(&text).await?,
     }
 
-    // Get elements from cache to find the active application
-    let elements_response = {
-        let cache = state.element_cache.lock().await;
-        match &*cache {
-            Some((_, _, cached_app_name)) => {
-                // We have a cached app name, so let's refresh elements
-                info!("refreshing elements for app: {}", cached_app_name);
-                refresh_elements_and_attributes_after_action(state.clone(), cached_app_name.clone(), 500).await
-            }
-            None => {
-                // No cache available, don't try to refresh elements
-                info!("no element cache found, skipping element refresh");
-                None
-            }
-        }
-    };
-    
-    // Return combined response
-    Ok(JsonResponse(InputControlWithElementsResponse {
-        input: InputControlResponse { success: true },
-        elements: elements_response,
-    }))
+    Ok(())
 }
 
-// Updated helper function to refresh elements after an action
-async fn refresh_elements_and_attributes_after_action(
-    state: Arc<AppState>, 
-    app_name: String,
-    delay_ms: u64
-) -> Option<ListElementsAndAttributesResponse> {
-    // Small delay to allow UI to update after action
-    info!("waiting for ui to update after action before listing elements and attributes");
-    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
-    
-    // Create request to refresh the elements list
-    let elements_request = ListInteractableElementsRequest {
-        app_name,
-        max_elements: None,
-        use_background_apps: Some(false),
-        activate_app: Some(true),
-    };
-    
-    // Call the new list elements handler
-    match crate::handlers::list_elements_and_attributes::list_elements_and_attributes_handler(State(state), Json(elements_request)).await {
-        Ok(response) => Some(response.0),
+// The key code in an explicit "code:N" key, if that's what was sent
+fn explicit_key_code(key: &str) -> Option<u16> {
+    key.strip_prefix("code:")?.trim().parse().ok()
+}
+
+// Press and release a raw key code: a macOS key code through System Events there, a
+// virtual-key code elsewhere
+async fn press_key_code(key_code: u16) -> Result<(), (StatusCode, JsonResponse<serde_json::Value>)> {
+    info!("pressing key code {}", key_code);
+    if cfg!(target_os = "macos") {
+        let script = format!("tell application \"System Events\" to key code {}", key_code);
+        return run_osascript(&script).await.map(|_| ()).map_err(|e| {
+            error!("failed to press key: {}", e);
+            e.to_response()
+        });
+    }
+    post_raw_key(key_code, true)?;
+    post_raw_key(key_code, false)
+}
+
+// Post a key event straight to the HID event stream; System Events can't separate down from up
+fn post_raw_key(key_code: u16, key_down: bool) -> Result<(), (StatusCode, JsonResponse<serde_json::Value>)> {
+    let desktop = match Desktop::new(false, false) {
+        Ok(desktop) => desktop,
         Err(e) => {
-            // Log the error but don't fail the whole request
-            error!("failed to list elements and attributes after action: {:?}", e);
-            None
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(serde_json::json!({"error": format!("failed to initialize desktop automation: {}", e)})),
+            ));
         }
+    };
+
+    info!("posting key {} for key code {}", if key_down { "down" } else { "up" }, key_code);
+    desktop.post_key_event(key_code, key_down).map_err(|e| {
+        error!("failed to post key event: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(serde_json::json!({"error": format!("failed to post key event: {}", e)})),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_are_keys_not_key_codes() {
+        assert_eq!(explicit_key_code("5"), None);
+        assert_eq!(explicit_key_code("1"), None);
+        assert_eq!(explicit_key_code("cmd+5"), None);
+        assert_eq!(explicit_key_code("code:5"), Some(5));
+        assert_eq!(explicit_key_code("code: 49"), Some(49));
+        assert_eq!(explicit_key_code("code:x"), None);
     }
 }
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, LaunchViaSpotlightRequest, LaunchViaSpotlightResponse};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{app_not_allowed, frontmost_app_name, send_key_combo, send_text, settle};
+
+// Cmd+Space, the default Spotlight shortcut; on Windows the Start menu's search plays its part
+const OPEN_SPOTLIGHT_KEYS: &str = if cfg!(target_os = "macos") { "cmd+space" } else { "win" };
+const DEFAULT_RESULTS_DELAY_MS: u64 = 800;
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Spotlight's overlay isn't a regular app window, so it can't be listed or clicked by
+// index. Drive it entirely with global keystrokes instead: open it, type the query,
+// confirm the top hit and wait for whatever it launched to come to the front.
+// Requires Spotlight to be enabled with its default Cmd+Space shortcut (on Windows, the
+// Start menu search opened with the Windows key).
+pub async fn launch_via_spotlight_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LaunchViaSpotlightRequest>,
+) -> Result<JsonResponse<LaunchViaSpotlightResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Spotlight launches whatever the query finds, so there's no app safe mode could check
+    if state.config.safe_mode_allowed_apps.is_some() {
+        return Err(app_not_allowed(&state.config, "launchViaSpotlight (it can launch any app)"));
+    }
+
+    let query = request.query.trim();
+    if query.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "query must not be empty"})),
+        ));
+    }
+    let confirm = request.confirm.unwrap_or(true);
+    info!("launching '{}' via spotlight (confirm: {})", query, confirm);
+
+    let frontmost_before = frontmost_app_name().await;
+
+    if let Err(e) = send_key_combo(OPEN_SPOTLIGHT_KEYS).await {
+        error!("failed to open spotlight");
+        return Err(e);
+    }
+    // The overlay takes a moment to appear and grab keyboard focus
+    settle(Duration::from_millis(300)).await;
+
+    if let Err(e) = send_text(query).await {
+        error!("failed to type spotlight query");
+        return Err(e);
+    }
+
+    settle(Duration::from_millis(request.results_delay_ms.unwrap_or(DEFAULT_RESULTS_DELAY_MS))).await;
+
+    if !confirm {
+        return Ok(JsonResponse(LaunchViaSpotlightResponse {
+            success: true,
+            message: format!("typed '{}' into spotlight without confirming", query),
+            confirmed: false,
+            frontmost_app: frontmost_app_name().await,
+            elements: None,
+        }));
+    }
+
+    if let Err(e) = send_key_combo("return").await {
+        error!("failed to confirm spotlight result");
+        return Err(e);
+    }
+
+    // Wait for the launched app to take the front; an app that was already
+    // frontmost (or a slow launch) just runs into the timeout
+    let timeout = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let started = Instant::now();
+    let mut frontmost = frontmost_app_name().await;
+    while frontmost == frontmost_before && started.elapsed() < timeout {
+        settle(POLL_INTERVAL).await;
+        frontmost = frontmost_app_name().await;
+    }
+    debug!("frontmost app after spotlight launch: {:?} (before: {:?})", frontmost, frontmost_before);
+
+    let changed = frontmost != frontmost_before;
+    let message = match (&frontmost, changed) {
+        (Some(app), true) => format!("launched '{}' via spotlight, {} is now frontmost", query, app),
+        (Some(app), false) => format!("confirmed '{}' in spotlight but {} is still frontmost", query, app),
+        (None, _) => format!("confirmed '{}' in spotlight but could not read the frontmost app", query),
+    };
+    info!("{}", message);
+
+    let elements = match &frontmost {
+        Some(app) if changed => refresh_elements_and_attributes_after_action(state.clone(), app.clone(), 500).await,
+        _ => None,
+    };
+
+    Ok(JsonResponse(LaunchViaSpotlightResponse {
+        success: changed && frontmost.is_some(),
+        message,
+        confirmed: true,
+        frontmost_app: frontmost,
+        elements,
+    }))
+}
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+use std::time::Instant;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{sort_elements, AutomationError, Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::types::{AppState, ElementCacheInfo, ListAllElementsRequest, ListAllElementsResponse};
+use crate::handlers::list_elements_and_attributes::{is_listed, is_non_interactable, listing_entry};
+use crate::handlers::utils::{app_identity, ALL_APPS_KEY};
+
+// A full walk of every app can take minutes and return tens of thousands of
+// elements, so both the number of apps and of elements are capped hard
+const DEFAULT_MAX_APPS: usize = 8;
+const MAX_APPS_LIMIT: usize = 20;
+const DEFAULT_MAX_ELEMENTS: usize = 1000;
+const MAX_ELEMENTS_LIMIT: usize = 3000;
+// How many elements to walk per element still wanted; most of a tree is groups and
+// decorations the listing skips
+const WALK_BUDGET_FACTOR: usize = 4;
+
+pub async fn list_all_elements_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ListAllElementsRequest>,
+) -> Result<JsonResponse<ListAllElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let start_time = Instant::now();
+    let max_apps = request.max_apps.unwrap_or(DEFAULT_MAX_APPS).min(MAX_APPS_LIMIT);
+    let max_elements = request.max_elements.unwrap_or(DEFAULT_MAX_ELEMENTS).min(MAX_ELEMENTS_LIMIT);
+
+    // Don't activate anything, a global listing shouldn't shuffle windows around
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let applications = match desktop.applications() {
+        Ok(applications) => applications,
+        Err(e) => {
+            error!("failed to list applications: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to list applications: {}", e)
+                })),
+            ));
+        }
+    };
+
+    // Keep the requested apps only, matched by name or bundle id
+    let mut targets = Vec::new();
+    for app in applications {
+        let (name, bundle_id) = app_identity(&desktop, &app);
+        let Some(name) = name else { continue };
+        let wanted = match &request.app_names {
+            Some(wanted) => wanted.iter().any(|wanted| {
+                wanted.eq_ignore_ascii_case(&name) || bundle_id.as_deref() == Some(wanted.as_str())
+            }),
+            None => true,
+        };
+        if wanted {
+            targets.push((app, name, bundle_id));
+        }
+    }
+    let mut truncated = targets.len() > max_apps;
+    targets.truncate(max_apps);
+
+    let mut apps = Vec::new();
+    let mut elements = Vec::new();
+    let mut cached = Vec::new();
+    let mut owners = Vec::new();
+
+    'apps: for (app, name, bundle_id) in targets {
+        // Only walk as much of the tree as the remaining budget can use
+        let budget = (max_elements - cached.len()) * WALK_BUDGET_FACTOR;
+        let walked = tokio::task::spawn_blocking(move || walk_elements(&app, budget)).await;
+        let (app_elements, walk_cut_short) = match walked {
+            Ok(Ok(walked)) => walked,
+            Ok(Err(e)) => {
+                warn!("skipping {}: failed to get elements: {}", name, e);
+                continue;
+            }
+            Err(e) => {
+                warn!("skipping {}: element walk failed: {}", name, e);
+                continue;
+            }
+        };
+        truncated |= walk_cut_short;
+        info!("found {} elements in {} (walk cut short: {})", app_elements.len(), name, walk_cut_short);
+        apps.push(name.clone());
+
+        // By-index actions activate and refresh the owning app through this key
+        let app_key = bundle_id.unwrap_or_else(|| name.clone());
+        for element in app_elements {
+            if cached.len() >= max_elements {
+                truncated = true;
+                break 'apps;
+            }
+
+            let attrs = element.attributes();
+            let mut entry = listing_entry(&element, &attrs, cached.len(), request.format);
+            let has_text = entry.get("text").is_some();
+            let non_interactable = is_non_interactable(&state.config, &name, &attrs.role);
+            if !is_listed(request.classification, non_interactable, has_text, &attrs.role) {
+                continue;
+            }
+
+            entry["app_name"] = Value::String(name.clone());
+            elements.push(entry);
+            cached.push(element);
+            owners.push(app_key.clone());
+        }
+    }
+
+    info!("listed {} elements across {} apps (truncated: {})", elements.len(), apps.len(), truncated);
+
+    let ttl_seconds = state.config.element_cache_ttl.as_secs();
+    let element_count = cached.len();
+    // The owners are read while the cache lock is held, so never hold both here
+    {
+        let mut cache = state.element_cache.lock().await;
+        cache.insert(ALL_APPS_KEY.to_string(), ALL_APPS_KEY.to_string(), cached);
+    }
+    {
+        let mut element_owners = state.element_owners.lock().await;
+        *element_owners = owners;
+    }
+
+    let now = chrono::Utc::now();
+    let cache_info = ElementCacheInfo {
+        cache_id: Uuid::new_v4().to_string(),
+        timestamp: now.to_rfc3339(),
+        expires_at: (now + chrono::Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        element_count,
+        ttl_seconds,
+    };
+
+    Ok(JsonResponse(ListAllElementsResponse {
+        apps,
+        elements,
+        cache_info,
+        truncated,
+        processing_time_seconds: format!("{:.2}", start_time.elapsed().as_secs_f64()),
+    }))
+}
+
+// Walk the app's tree depth-first, stopping once `budget` elements have been read, and sort
+// what was read the way listings are. Also returns whether the budget cut the walk short.
+fn walk_elements(app: &UIElement, budget: usize) -> Result<(Vec<UIElement>, bool), AutomationError> {
+    let mut walked = Vec::new();
+    let mut pending = app.children()?;
+    pending.reverse();
+    let mut cut_short = false;
+    while let Some(element) = pending.pop() {
+        if walked.len() >= budget {
+            cut_short = true;
+            break;
+        }
+        if let Ok(mut children) = element.children() {
+            children.reverse();
+            pending.extend(children);
+        }
+        walked.push(element);
+    }
+    sort_elements(&mut walked);
+    Ok((walked, cut_short))
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, ListApplicationsRequest, ListApplicationsResponse, RunningApplication};
+use crate::handlers::utils::run_jxa;
+
+// Every running app from NSWorkspace; activation policy 0 is a regular app with a Dock icon
+const RUNNING_APPLICATIONS_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+function run(argv) {
+    const apps = $.NSWorkspace.sharedWorkspace.runningApplications;
+    const result = [];
+    for (let i = 0; i < apps.count; i++) {
+        const app = apps.objectAtIndex(i);
+        const name = ObjC.unwrap(app.localizedName);
+        if (!name) {
+            continue;
+        }
+        result.push({
+            name: name,
+            bundle_id: ObjC.unwrap(app.bundleIdentifier) || null,
+            pid: app.processIdentifier,
+            frontmost: app.active,
+            hidden: app.hidden,
+            background: app.activationPolicy !== 0
+        });
+    }
+    return JSON.stringify(result);
+}
+"#;
+
+// The apps an agent can target, so it can pass their exact name or bundle id to
+// openApplication or listInteractableElementsByIndex instead of guessing
+pub async fn list_applications_handler(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<ListApplicationsRequest>,
+) -> Result<JsonResponse<ListApplicationsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let raw = match run_jxa(RUNNING_APPLICATIONS_SCRIPT, &[]).await {
+        Ok(raw) => raw,
+        Err(e) => {
+            error!("failed to list running applications: {}", e);
+            return Err(e.to_response());
+        }
+    };
+    debug!("running applications output: {} bytes", raw.len());
+    let running: Vec<RunningApplication> = serde_json::from_str(&raw).map_err(|e| {
+        error!("unexpected running applications output: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("unexpected running applications output: {}", e)})),
+        )
+    })?;
+
+    let applications = targetable_applications(running, request.include_background.unwrap_or(false));
+    let frontmost_app = applications.iter().find(|app| app.frontmost).map(|app| app.name.clone());
+    info!("found {} running applications", applications.len());
+
+    Ok(JsonResponse(ListApplicationsResponse { applications, frontmost_app }))
+}
+
+// Drop background apps unless asked for, and sort by name
+fn targetable_applications(running: Vec<RunningApplication>, include_background: bool) -> Vec<RunningApplication> {
+    let mut applications: Vec<RunningApplication> = running
+        .into_iter()
+        .filter(|app| include_background || !app.background)
+        .collect();
+    applications.sort_by_key(|app| app.name.to_lowercase());
+    applications
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, background: bool) -> RunningApplication {
+        RunningApplication {
+            name: name.to_string(),
+            bundle_id: None,
+            pid: 1,
+            frontmost: false,
+            hidden: false,
+            background,
+        }
+    }
+
+    #[test]
+    fn lists_regular_apps_by_name() {
+        let running = || vec![app("Safari", false), app("Spotlight", true), app("calendar", false)];
+        let names = |apps: Vec<RunningApplication>| apps.into_iter().map(|app| app.name).collect::<Vec<_>>();
+        assert_eq!(names(targetable_applications(running(), false)), ["calendar", "Safari"]);
+        assert_eq!(names(targetable_applications(running(), true)), ["calendar", "Safari", "Spotlight"]);
+    }
+}
@@ -1,19 +1,22 @@
 use axum::{
     extract::{Json, State},
-    http::StatusCode,
-    response::Json as JsonResponse,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json as JsonResponse, Response},
 };
-use computer_use_ai_sdk::Desktop;
-use std::collections::HashMap;
+use computer_use_ai_sdk::{Desktop, Selector, UIElement, UIElementAttributes};
+use std::collections::{hash_map::DefaultHasher, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use chrono;
 use serde_json::{self, json, Value};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::types::*;
 use crate::AppState;
+use crate::config::ServerConfig;
+use crate::handlers::utils::{app_identity, enable_enhanced_accessibility, resolve_application, role_matches};
 use crate::types::ElementStatistics;
 use crate::types::ListElementsAndAttributesResponse;
 
@@ -24,7 +27,14 @@ pub async fn list_elements_and_attributes_handler(
     // Record start time at the beginning of the handler
     let start_time = std::time::Instant::now();
     
-    info!("listing all elements and attributes for app: {}", request.app_name);
+    info!("listing all elements and attributes for app: {}", request.bundle_id.as_deref().unwrap_or(&request.app_name));
+    
+    if request.app_name.is_empty() && request.bundle_id.as_deref().map_or(true, str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either app_name or bundle_id must be provided"})),
+        ));
+    }
     
     // Create desktop automation engine
     let desktop = match Desktop::new(
@@ -44,7 +54,7 @@ pub async fn list_elements_and_attributes_handler(
     };
 
     // Get application
-    let app = match desktop.application(&request.app_name) {
+    let app = match resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
         Ok(app) => app,
         Err(e) => {
             error!("application not found: {}", e);
@@ -57,8 +67,38 @@ pub async fn list_elements_and_attributes_handler(
         }
     };
 
+    // Display names change with localization and updates, so the caches are keyed by bundle id
+    let (resolved_name, bundle_id) = app_identity(&desktop, &app);
+    let app_name = resolved_name.clone().unwrap_or_else(|| request.app_name.clone());
+    let app_key = bundle_id.clone().unwrap_or_else(|| app_name.clone());
+
+    let enhanced_accessibility = if request.enable_enhanced_accessibility.unwrap_or(false) {
+        let enabled = enable_enhanced_accessibility(&app).await;
+        info!("enhanced accessibility for {}: {}", app_name, if enabled { "enabled" } else { "not supported" });
+        Some(enabled)
+    } else {
+        None
+    };
+
+    // Narrow the search server-side when the caller passed a query
+    let selector = match request.locator.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(query) => match Selector::query(query) {
+            Ok(selector) => selector,
+            Err(e) => {
+                error!("invalid locator query '{}': {}", query, e);
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    JsonResponse(json!({
+                        "error": format!("invalid locator query: {}", e)
+                    })),
+                ));
+            }
+        },
+        None => Selector::from(""),
+    };
+
     // Get elements from the application
-    let locator = match app.locator("") {
+    let locator = match app.locator(selector) {
         Ok(locator) => locator,
         Err(e) => {
             error!("failed to get elements: {}", e);
@@ -71,7 +111,9 @@ pub async fn list_elements_and_attributes_handler(
         }
     };
 
-    let elements = match locator.all() {
+    // Sort before assigning indices so an unchanged UI lists identically every time
+    let walk_start = Instant::now();
+    let elements = match locator.all_sorted() {
         Ok(elements) => elements,
         Err(e) => {
             error!("failed to get elements: {}", e);
@@ -84,107 +126,135 @@ pub async fn list_elements_and_attributes_handler(
         }
     };
 
-    info!("found {} elements in {}", elements.len(), request.app_name);
+    let walk_time = walk_start.elapsed();
+    info!("found {} elements in {}", elements.len(), app_name);
 
-    // Define non-interactable roles
-    let non_interactable_roles = [
-        "AXGroup", "AXStaticText", "AXUnknown", "AXSeparator", 
-        "AXHeading", "AXLayoutArea", "AXHelpTag", "AXGrowArea"
-    ];
+    // Cut down to the requested region before indexing, so indices count only what's in it
+    let (elements, excluded_by_region) = match &request.region {
+        Some(region) => {
+            let walked = elements.len();
+            let inside: Vec<UIElement> = elements
+                .into_iter()
+                .filter(|element| element.bounds().map_or(false, |bounds| region.intersects(bounds)))
+                .collect();
+            info!("{} of {} elements are within the requested region", inside.len(), walked);
+            let excluded = walked - inside.len();
+            (inside, excluded)
+        }
+        None => (elements, 0),
+    };
+
+    // Where each element sits, for its id and the window it's listed under
+    let placements: Vec<Placement> = elements.iter().map(placement).collect();
+
+    // Same for a window: scope the listing, and its indices, to the windows with this title
+    let (elements, placements, excluded_by_window) = match request.window_name.as_deref().map(str::to_lowercase) {
+        Some(window_name) => {
+            let walked = elements.len();
+            let (inside, placements): (Vec<UIElement>, Vec<Placement>) = elements
+                .into_iter()
+                .zip(placements)
+                .filter(|(_, placement)| {
+                    placement.window.as_deref().map_or(false, |title| title.to_lowercase().contains(&window_name))
+                })
+                .unzip();
+            info!("{} of {} elements are in a window titled like {:?}", inside.len(), walked, window_name);
+            let excluded = walked - inside.len();
+            (inside, placements, excluded)
+        }
+        None => (elements, placements, 0),
+    };
+
+    // Keep indices from the previous list of this app where the same elements are still present
+    let element_ids = unique_element_ids(
+        elements
+            .iter()
+            .zip(&placements)
+            .map(|(element, placement)| stable_element_id(element, &placement.ancestor_roles))
+            .collect(),
+    );
+    let windowed: Vec<(UIElement, Option<String>)> = elements
+        .into_iter()
+        .zip(placements.into_iter().map(|placement| placement.window))
+        .collect();
+    let preserve_start = Instant::now();
+    let previous_ids = state.element_ids.lock().await.get(&app_key).cloned();
+    let (windowed, element_ids, index_stability) = match previous_ids {
+        Some(previous_ids) => {
+            let (windowed, element_ids, stability) = preserve_previous_indices(windowed, element_ids, &previous_ids);
+            info!("index stability: {} preserved, {} changed, {} new, renumbered: {}",
+                  stability.preserved_count, stability.changed_indices.len(), stability.new_count, stability.renumbered);
+            (windowed, element_ids, Some(stability))
+        }
+        None => (windowed, element_ids, None),
+    };
+    let (elements, element_windows): (Vec<UIElement>, Vec<Option<String>>) = windowed.into_iter().unzip();
+    let mut filter_time = preserve_start.elapsed();
+    let mut attributes_time = Duration::ZERO;
 
     // Collect all elements with their filtered attributes
     let mut result_elements = Vec::new();
     let mut excluded_count = 0;
     let mut excluded_non_interactable_count = 0;
     let mut excluded_no_text_count = 0;
+    let mut excluded_by_score = 0;
+    let mut excluded_by_filter = 0;
+    let text_contains = request.text_contains.as_deref().map(str::to_lowercase).filter(|t| !t.is_empty());
+    let mut interactable_count = 0;
+    let mut windows: Vec<(usize, Option<String>, (f64, f64, f64, f64))> = Vec::new();
+    // Raw role and interactability of each listed element, for a summary
+    let mut listed_meta: Vec<(String, bool)> = Vec::new();
+    // State of each listed element by element id, to say what an action changed
+    let mut snapshot: HashMap<String, u64> = HashMap::new();
     
     for (i, element) in elements.iter().enumerate() {
         // Extract complete attributes from element
+        let attributes_start = Instant::now();
         let attrs = element.attributes();
+        let element_data = listing_entry(element, &attrs, i, request.format);
+        let has_text = element_data.get("text").is_some();
+        attributes_time += attributes_start.elapsed();
         
-        // Create a complete attributes object - removed id field
-        let mut element_data = json!({
-            "index": i,
-            "role": attrs.role,
-            // "id" field removed as requested
-        });
-        
-        // Check if we have role description in properties and modify role field
-        let role_without_ax = attrs.role.trim_start_matches("AX");
-        
-        if let Some(props) = attrs.properties.get("AXRoleDescription") {
-            // First check if the property exists, then if it's a string value
-            if let Some(role_desc) = props.as_ref().and_then(|v| v.as_str()) {
-                // Only include role description if it's different from the role (after removing AX prefix)
-                if !role_desc.eq_ignore_ascii_case(role_without_ax) {
-                    element_data["role"] = Value::String(format!("{} ({})", attrs.role, role_desc));
-                }
-            }
-        }
-        
-        // Collect all text content for the combined field
-        let mut text_parts = Vec::new();
-        
-        // Collect value, label, description for text_parts
-        if let Some(value) = &attrs.value {
-            if !value.is_empty() {
-                text_parts.push(value.clone());
-            }
+        let filter_start = Instant::now();
+        let is_non_interactable = is_non_interactable(&state.config, &app_name, &attrs.role);
+        if !is_non_interactable {
+            interactable_count += 1;
         }
-        
-        if let Some(label) = &attrs.label {
-            if !label.is_empty() {
-                text_parts.push(label.clone());
+        if attrs.role == "AXWindow" {
+            if let Ok(bounds) = element.bounds() {
+                windows.push((i, attrs.label.clone(), bounds));
             }
         }
         
-        if let Some(desc) = &attrs.description {
-            if !desc.is_empty() {
-                text_parts.push(desc.clone());
-            }
-        }
-        
-        // Add text values from properties if they exist
-        for (key, value_opt) in &attrs.properties {
-            // Skip properties that are likely to be non-human-readable
-            if key.contains("Parent") || 
-               key.contains("Children") || 
-               key == "AXRoleDescription" || 
-               key == "AXRole" || 
-               key == "AXTopLevelUIElement" || 
-               key == "AXWindow" {
+        // Include element if the requested profile keeps it (by default: interactable OR has text)
+        if is_listed(request.classification, is_non_interactable, has_text, &attrs.role) {
+            if !passes_filters(&request, text_contains.as_deref(), is_non_interactable, &element_data) {
+                excluded_by_filter += 1;
+                filter_time += filter_start.elapsed();
                 continue;
             }
-            
-            if let Some(value) = value_opt {
-                if let Some(text_value) = value.as_str() {
-                    if !text_value.is_empty() {
-                        text_parts.push(text_value.to_string());
-                    }
-                }
+            let mut element_data = element_data;
+            let score = interactability_score(
+                &element.available_actions().unwrap_or_default(),
+                !is_non_interactable,
+                attrs.properties.get("AXEnabled").and_then(|v| v.as_ref()).and_then(|v| v.as_bool()),
+                element.bounds().ok(),
+            );
+            if request.min_score.map_or(false, |min_score| score < min_score) {
+                excluded_by_score += 1;
+                filter_time += filter_start.elapsed();
+                continue;
             }
-        }
-        
-        // Create the text field with all content
-        let has_text = !text_parts.is_empty();
-        if has_text {
-            let combined_text = text_parts.join(" ");
-            
-            if i < 5 {  
-                info!("element {}: text field created: '{}'", i, &combined_text);
+            element_data["score"] = json!(score);
+            element_data["element_id"] = json!(element_ids[i]);
+            if let Some(window) = &element_windows[i] {
+                element_data["window"] = json!(window);
             }
-            
-            element_data["text"] = Value::String(combined_text);
-        }
-        
-        // Check if element is non-interactable based on its role
-        let role = attrs.role.as_str();
-        let is_non_interactable = non_interactable_roles.contains(&role);
-        
-        // Include element if it's either interactable OR has text
-        if !is_non_interactable || has_text {
+
             // Add element to result
+            snapshot.insert(element_ids[i].clone(), element_state_hash(&attrs, element_data["text"].as_str().unwrap_or_default()));
             result_elements.push(element_data);
+            listed_meta.push((attrs.role.clone(), !is_non_interactable));
         } else {
             // Count excluded elements
             excluded_count += 1;
@@ -198,31 +268,134 @@ pub async fn list_elements_and_attributes_handler(
                 excluded_no_text_count += 1;
             }
         }
+        filter_time += filter_start.elapsed();
     }
 
     info!("excluded {} elements (non-interactable: {}, no text: {})", 
           excluded_count, excluded_non_interactable_count, excluded_no_text_count);
+    if excluded_by_score > 0 {
+        info!("excluded {} more elements scoring below {:?}", excluded_by_score, request.min_score);
+    }
+    if excluded_by_filter > 0 {
+        info!("excluded {} more elements by role_filter {:?} / text_contains {:?} / interactable_only {:?}",
+              excluded_by_filter, request.role_filter, request.text_contains, request.interactable_only);
+    }
+
+    // Most likely targets first; sorting before truncation keeps the best-scored ones
+    if request.sort_by_score.unwrap_or(false) {
+        let mut scored: Vec<(Value, (String, bool))> = result_elements.into_iter().zip(listed_meta).collect();
+        scored.sort_by(|(a, _), (b, _)| b["score"].as_f64().unwrap_or(0.0).total_cmp(&a["score"].as_f64().unwrap_or(0.0)));
+        (result_elements, listed_meta) = scored.into_iter().unzip();
+    }
 
-    // Apply max_elements limit if specified
-    if let Some(max) = request.max_elements {
+    let largest_window = windows
+        .iter()
+        .map(|(_, _, (_, _, width, height))| (*width, *height))
+        .max_by(|(w1, h1), (w2, h2)| (w1 * h1).total_cmp(&(w2 * h2)));
+
+    // A locator or region narrows the walk on purpose, so an empty result there says nothing
+    let accessibility_warning = if request.locator.is_none() && request.region.is_none() {
+        accessibility_warning(elements.len(), interactable_count, largest_window, enhanced_accessibility.is_some())
+    } else {
+        None
+    };
+    if let Some(warning) = &accessibility_warning {
+        warn!("{}: {}", app_name, warning);
+    }
+
+    // Remember what the UI looked like so action handlers can tell whether they changed it
+    state.ui_fingerprint.lock().await.insert(app_key.clone(), ui_fingerprint(&result_elements));
+    state.ui_snapshot.lock().await.insert(app_key.clone(), snapshot);
+    state.listing_requests.lock().await.insert(app_key.clone(), request.clone());
+
+    // A huge listing is summarized rather than truncated, unless the caller asked for everything
+    let summarize_above = if request.unbounded.unwrap_or(false) || request.format == ListFormat::PathMap {
+        None
+    } else {
+        match request.summarize_above {
+            Some(0) => None,
+            Some(ceiling) => Some(ceiling),
+            None => state.config.summarize_above_elements,
+        }
+    };
+    let summary = match summarize_above {
+        Some(ceiling) if result_elements.len() > ceiling => {
+            info!("summarizing {} listed elements (ceiling {})", result_elements.len(), ceiling);
+            let items: Vec<SummaryItem> = result_elements
+                .iter()
+                .zip(&listed_meta)
+                .map(|(entry, (role, interactable))| SummaryItem {
+                    entry,
+                    role,
+                    interactable: *interactable,
+                    bounds: entry["index"]
+                        .as_u64()
+                        .and_then(|index| elements.get(index as usize))
+                        .and_then(|element| element.bounds().ok()),
+                })
+                .collect();
+            Some(summarize_listing(&items, &windows, ceiling, state.config.summary_prominent_elements))
+        }
+        _ => None,
+    };
+
+    // Apply max_elements, falling back to the server's default cap unless the caller asked for everything
+    let truncate_start = Instant::now();
+    let total_available = result_elements.len();
+    let max_elements = if request.unbounded.unwrap_or(false) || summary.is_some() {
+        None
+    } else {
+        request.max_elements.or(state.config.default_max_elements)
+    };
+    let mut truncated = false;
+    if let Some(max) = max_elements {
         if result_elements.len() > max {
+            info!("truncating element list from {} to {}", result_elements.len(), max);
             result_elements.truncate(max);
+            truncated = true;
         }
     }
+    filter_time += truncate_start.elapsed();
 
     // Generate element statistics
-    let element_stats = generate_element_statistics(&result_elements, excluded_count, 
+    let stats_start = Instant::now();
+    let mut element_stats = generate_element_statistics(&result_elements, excluded_count, 
                                                    excluded_non_interactable_count, excluded_no_text_count);
+    element_stats.classification = request.classification;
+    element_stats.excluded_by_region = excluded_by_region;
+    element_stats.excluded_by_window = excluded_by_window;
+    element_stats.excluded_by_score = excluded_by_score;
+    element_stats.excluded_by_filter = excluded_by_filter;
+    element_stats.truncated = truncated;
+    element_stats.total_available = total_available;
+    let stats_time = stats_start.elapsed();
     info!("generated statistics: {} different roles found", element_stats.top_roles.len());
 
+    let etag = listing_etag(request.format, &element_ids, &result_elements);
+
+    // The summary stands in for the elements, which still went into the stats and etag
+    if summary.is_some() {
+        result_elements.clear();
+    }
+
+    // Same elements, keyed by where they sit instead of listed by index
+    let path_map = if request.format == ListFormat::PathMap {
+        Some(build_path_map(&elements, std::mem::take(&mut result_elements)))
+    } else {
+        None
+    };
+
     // Generate a cache ID and store elements in cache
     let cache_id = Uuid::new_v4().to_string();
-    let cache_timestamp = Instant::now();
-    let ttl_seconds: u64 = 30;
+    let ttl_seconds = state.config.element_cache_ttl.as_secs();
 
     {
         let mut cache = state.element_cache.lock().await;
-        *cache = Some((elements.clone(), cache_timestamp, request.app_name.clone()));
+        cache.insert(app_key.clone(), app_name.clone(), elements.clone());
+    }
+    {
+        let mut ids_cache = state.element_ids.lock().await;
+        ids_cache.insert(app_key, element_ids);
     }
 
     // Create cache info for response
@@ -244,13 +417,679 @@ pub async fn list_elements_and_attributes_handler(
     info!("processed request in {} seconds", elapsed_formatted);
     
     Ok(JsonResponse(ListElementsAndAttributesResponse {
+        app_name: resolved_name,
+        bundle_id,
         elements: result_elements,
         cache_info,
         stats: element_stats,
         processing_time_seconds: elapsed_formatted,
+        timing: ListTiming {
+            walk_ms: walk_time.as_millis() as u64,
+            attributes_ms: attributes_time.as_millis() as u64,
+            filter_ms: filter_time.as_millis() as u64,
+            stats_ms: stats_time.as_millis() as u64,
+        },
+        index_stability,
+        truncated,
+        total_available,
+        path_map,
+        enhanced_accessibility,
+        etag,
+        accessibility_warning,
+        summary,
     }))
 }
 
+// HTTP entry point for listings: sends the listing's hash as an ETag and answers
+// 304 Not Modified without a body when it matches the request's If-None-Match.
+// The app is still walked (that's how an unchanged UI is recognised) and the element
+// cache refreshed, so the saving is the response itself: in a polling loop over a
+// static UI the client re-uses its copy instead of receiving the same listing again.
+// Anything that changes a role, a text, a bound or an index changes the tag, so
+// this only pays off when the UI is truly static between calls.
+pub async fn list_elements_and_attributes_route(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ListInteractableElementsRequest>,
+) -> Result<Response, (StatusCode, JsonResponse<serde_json::Value>)> {
+    let listing = list_elements_and_attributes_handler(State(state), Json(request)).await?.0;
+    let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", listing.etag)) else {
+        return Ok(JsonResponse(listing).into_response());
+    };
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| etag_matches(value, &listing.etag));
+    if not_modified {
+        info!("listing unchanged since etag {}, answering 304", listing.etag);
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    Ok(([(header::ETAG, etag)], JsonResponse(listing)).into_response())
+}
+
+// Hash of everything a listing returns per element, plus the stable ids its indices are
+// kept by, so equal tags mean an identical listing
+fn listing_etag(format: ListFormat, element_ids: &[String], entries: &[Value]) -> String {
+    let mut hasher = DefaultHasher::new();
+    format.hash(&mut hasher);
+    element_ids.hash(&mut hasher);
+    for entry in entries {
+        entry.to_string().hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+// Whether an If-None-Match header (a list of tags, possibly weak) names this tag. "*" isn't
+// honoured: a listing always exists, so it would answer every such request with a 304
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag.trim_start_matches("W/").trim_matches('"') == etag)
+}
+
+// Where an element sits: the roles of its ancestors below the application, outermost first,
+// and the title of the window it's in (a window is in itself)
+struct Placement {
+    ancestor_roles: Vec<String>,
+    window: Option<String>,
+}
+
+fn placement(element: &UIElement) -> Placement {
+    let mut window = if element.role() == "AXWindow" { window_title(element) } else { None };
+    let mut ancestor_roles = Vec::new();
+    let mut current = element.parent().ok().flatten();
+    for _ in 0..MAX_PATH_DEPTH {
+        let Some(ancestor) = current else { break };
+        let role = ancestor.role();
+        if role == "AXApplication" {
+            break;
+        }
+        if role == "AXWindow" && window.is_none() {
+            window = window_title(&ancestor);
+        }
+        ancestor_roles.push(role);
+        current = ancestor.parent().ok().flatten();
+    }
+    ancestor_roles.reverse();
+    Placement { ancestor_roles, window }
+}
+
+fn window_title(window: &UIElement) -> Option<String> {
+    window.attributes().label.map(|title| title.trim().to_string()).filter(|title| !title.is_empty())
+}
+
+// Id of an element that survives a refresh as long as it keeps its role, its name and its
+// place in the tree: a hash of those, unlike the index, which shifts when anything before
+// it appears or disappears. The value is left out because typing into a field changes it
+fn stable_element_id(element: &UIElement, ancestor_roles: &[String]) -> String {
+    let attrs = element.attributes();
+    let text = [&attrs.label, &attrs.description]
+        .into_iter()
+        .flatten()
+        .find(|text| !text.trim().is_empty())
+        .map(|text| text.trim().to_string())
+        .unwrap_or_default();
+
+    element_id_hash(&attrs.role, &text, ancestor_roles)
+}
+
+fn element_id_hash(role: &str, text: &str, ancestor_roles: &[String]) -> String {
+    let mut hasher = DefaultHasher::new();
+    role.hash(&mut hasher);
+    text.hash(&mut hasher);
+    ancestor_roles.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// Identical siblings hash alike, so later ones get "-2", "-3", ... in walk order
+fn unique_element_ids(ids: Vec<String>) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    ids.into_iter()
+        .map(|id| {
+            let count = seen.entry(id.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 { id } else { format!("{}-{}", id, count) }
+        })
+        .collect()
+}
+
+// Deepest ancestor walk for a path; anything deeper is rooted at the cut-off
+const MAX_PATH_DEPTH: usize = 12;
+
+// Longest label kept in a path segment
+const MAX_SEGMENT_LABEL_CHARS: usize = 30;
+
+// Map "Window[Inbox]/Toolbar/Button[Send]" -> {index, bounds, enabled} for each listed entry
+fn build_path_map(elements: &[UIElement], entries: Vec<Value>) -> serde_json::Map<String, Value> {
+    let mut path_map = serde_json::Map::new();
+    for entry in entries {
+        let Some(element) = entry["index"].as_u64().and_then(|index| elements.get(index as usize)) else {
+            continue;
+        };
+
+        let mut segments = vec![path_segment(&element.attributes())];
+        let mut current = element.parent().ok().flatten();
+        for _ in 0..MAX_PATH_DEPTH {
+            let Some(ancestor) = current else { break };
+            let attrs = ancestor.attributes();
+            // The application is the same for every path
+            if attrs.properties.get("AXRole").and_then(|v| v.as_ref()).and_then(|v| v.as_str()) == Some("AXApplication") {
+                break;
+            }
+            segments.push(path_segment(&attrs));
+            current = ancestor.parent().ok().flatten();
+        }
+        segments.reverse();
+
+        let mut summary = json!({"index": entry["index"]});
+        for key in ["bounds", "enabled"] {
+            if let Some(value) = entry.get(key) {
+                summary[key] = value.clone();
+            }
+        }
+        insert_unique(&mut path_map, segments.join("/"), summary);
+    }
+    path_map
+}
+
+// "Button[Send]", or just "Button" for unlabelled elements
+fn path_segment(attrs: &UIElementAttributes) -> String {
+    let role = attrs
+        .properties
+        .get("AXRole")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .unwrap_or(&attrs.role)
+        .trim_start_matches("AX")
+        .to_string();
+
+    let label = [&attrs.label, &attrs.description, &attrs.value]
+        .into_iter()
+        .flatten()
+        .map(|text| text.replace(['/', '[', ']'], " ").trim().to_string())
+        .find(|text| !text.is_empty());
+
+    match label {
+        Some(label) => {
+            let label: String = label.chars().take(MAX_SEGMENT_LABEL_CHARS).collect();
+            format!("{}[{}]", role, label.trim_end())
+        }
+        None => role,
+    }
+}
+
+// Identical siblings would collide, so later ones get "#2", "#3", ...
+fn insert_unique(path_map: &mut serde_json::Map<String, Value>, path: String, value: Value) {
+    if !path_map.contains_key(&path) {
+        path_map.insert(path, value);
+        return;
+    }
+    let mut n = 2;
+    while path_map.contains_key(&format!("{}#{}", path, n)) {
+        n += 1;
+    }
+    path_map.insert(format!("{}#{}", path, n), value);
+}
+
+// Roles that don't respond to input; listed only when they carry text
+const NON_INTERACTABLE_ROLES: [&str; 8] = [
+    "AXGroup", "AXStaticText", "AXUnknown", "AXSeparator", 
+    "AXHeading", "AXLayoutArea", "AXHelpTag", "AXGrowArea"
+];
+
+// The request's role_filter, text_contains (already lowercased) and interactable_only, applied
+// to a listing entry before truncation so max_elements counts only what the caller asked for
+fn passes_filters(
+    request: &ListInteractableElementsRequest,
+    text_contains: Option<&str>,
+    non_interactable: bool,
+    entry: &Value,
+) -> bool {
+    if request.interactable_only.unwrap_or(false) && non_interactable {
+        return false;
+    }
+    if let Some(roles) = request.role_filter.as_ref().filter(|roles| !roles.is_empty()) {
+        if !roles.iter().any(|role| role_matches(entry, role)) {
+            return false;
+        }
+    }
+    match text_contains {
+        Some(needle) => entry["text"].as_str().map_or(false, |text| text.to_lowercase().contains(needle)),
+        None => true,
+    }
+}
+
+// Check if element is non-interactable based on its role, letting the
+// per-app overrides from the config correct the default heuristics
+pub fn is_non_interactable(config: &ServerConfig, app_name: &str, role: &str) -> bool {
+    match config.interactability_override(app_name, role) {
+        Some(Interactability::None) => true,
+        Some(Interactability::Definite) | Some(Interactability::Sometimes) => false,
+        None => NON_INTERACTABLE_ROLES.contains(&role),
+    }
+}
+
+// At most this many interactable elements in a large window suggests the content isn't
+// exposed: a window's close/minimize/zoom/full screen buttons alone make four
+const NEAR_EMPTY_INTERACTABLES: usize = 4;
+
+// Windows smaller than this (in square points) may really be near empty, e.g. a palette
+const SUBSTANTIAL_WINDOW_AREA: f64 = 300.0 * 200.0;
+
+// An informational note when a listing looks like an app that doesn't implement
+// accessibility rather than one with no UI: nothing at all, or next to no interactable
+// elements in a substantial window
+fn accessibility_warning(
+    walked: usize,
+    interactable: usize,
+    largest_window: Option<(f64, f64)>,
+    enhanced_tried: bool,
+) -> Option<String> {
+    let hint = if enhanced_tried {
+        "the app may not support accessibility; check that it is not a canvas/game-style UI, and fall back to screenshots and coordinates"
+    } else {
+        "the app may not support accessibility, or (Chrome, Electron apps like VS Code or Slack) may need enable_enhanced_accessibility: true"
+    };
+    if walked == 0 {
+        return Some(format!("no accessibility elements found at all - the app may have no open window, or {}", hint));
+    }
+    match largest_window {
+        Some((width, height)) if width * height >= SUBSTANTIAL_WINDOW_AREA && interactable <= NEAR_EMPTY_INTERACTABLES => Some(format!(
+            "only {} interactable elements in a {:.0}x{:.0} window - {}",
+            interactable, width, height, hint
+        )),
+        _ => None,
+    }
+}
+
+// Layout containers, which are large without being worth pointing an agent at
+const CONTAINER_ROLES: [&str; 10] = [
+    "AXWindow", "AXSheet", "AXScrollArea", "AXSplitGroup", "AXTabGroup",
+    "AXWebArea", "AXList", "AXOutline", "AXTable", "AXBrowser",
+];
+
+// What a summary needs to know about one listed element
+struct SummaryItem<'a> {
+    entry: &'a Value,
+    role: &'a str,
+    interactable: bool,
+    bounds: Option<(f64, f64, f64, f64)>,
+}
+
+// Overview of a listing too big to return: counts per role, each window with how many listed
+// elements sit in it, and the largest interactable controls (top-left first among equals)
+fn summarize_listing(
+    items: &[SummaryItem],
+    windows: &[(usize, Option<String>, (f64, f64, f64, f64))],
+    ceiling: usize,
+    prominent_count: usize,
+) -> ListingSummary {
+    let mut role_counts: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        *role_counts.entry(item.entry["role"].as_str().unwrap_or(item.role)).or_default() += 1;
+    }
+    let mut roles: Vec<RoleCount> = role_counts
+        .into_iter()
+        .map(|(role, count)| RoleCount { role: role.to_string(), count })
+        .collect();
+    roles.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.role.cmp(&b.role)));
+
+    let windows = windows
+        .iter()
+        .map(|(index, title, (x, y, width, height))| {
+            let element_count = items
+                .iter()
+                .filter_map(|item| item.bounds)
+                .filter(|(ix, iy, iw, ih)| {
+                    let (cx, cy) = (ix + iw / 2.0, iy + ih / 2.0);
+                    cx >= *x && cx <= x + width && cy >= *y && cy <= y + height
+                })
+                .count();
+            WindowSummary {
+                index: *index,
+                title: title.clone().filter(|title| !title.is_empty()),
+                bounds: ElementBounds { x: *x, y: *y, width: *width, height: *height },
+                element_count,
+            }
+        })
+        .collect();
+
+    let mut candidates: Vec<(&SummaryItem, (f64, f64, f64, f64))> = items
+        .iter()
+        .filter(|item| item.interactable && !CONTAINER_ROLES.contains(&item.role))
+        .filter_map(|item| item.bounds.map(|bounds| (item, bounds)))
+        .filter(|(_, (_, _, width, height))| width * height > 0.0)
+        .collect();
+    candidates.sort_by(|(_, (ax, ay, aw, ah)), (_, (bx, by, bw, bh))| {
+        (bw * bh).total_cmp(&(aw * ah)).then(ay.total_cmp(by)).then(ax.total_cmp(bx))
+    });
+    let prominent = candidates
+        .into_iter()
+        .take(prominent_count)
+        .map(|(item, _)| item.entry.clone())
+        .collect();
+
+    ListingSummary {
+        element_count: items.len(),
+        ceiling,
+        roles,
+        windows,
+        prominent,
+        hint: format!(
+            "{} elements is above the summary ceiling of {} - narrow the listing with region (e.g. a window's bounds), \
+             locator (e.g. role=AXButton) or classification: strict, or pass summarize_above: 0 (or unbounded: true) for the plain listing",
+            items.len(), ceiling
+        ),
+    }
+}
+
+// Actions that are what you do to a control, as opposed to AXShowMenu/AXScrollToVisible,
+// which nearly every element offers
+const PRIMARY_ACTIONS: [&str; 6] = ["AXConfirm", "AXPick", "AXIncrement", "AXDecrement", "AXOpen", "AXRaise"];
+
+// How likely clicking an element does something, from 0 to 1, as the sum of:
+// - 0.45 for supporting AXPress (0.3 for another primary action, nothing for AXShowMenu only)
+// - 0.25 for an interactable role (see is_non_interactable)
+// - 0.15 unless AXEnabled is false
+// - 0.15 for a non-zero size, since zero-sized elements are hidden or collapsed
+pub fn interactability_score(
+    actions: &[String],
+    interactable_role: bool,
+    enabled: Option<bool>,
+    bounds: Option<(f64, f64, f64, f64)>,
+) -> f64 {
+    let mut score = 0.0;
+    if actions.iter().any(|action| action == "AXPress") {
+        score += 0.45;
+    } else if actions.iter().any(|action| PRIMARY_ACTIONS.contains(&action.as_str())) {
+        score += 0.3;
+    }
+    if interactable_role {
+        score += 0.25;
+    }
+    if enabled != Some(false) {
+        score += 0.15;
+    }
+    if bounds.map_or(false, |(_, _, width, height)| width > 0.0 && height > 0.0) {
+        score += 0.15;
+    }
+    (score * 100.0_f64).round() / 100.0
+}
+
+// Roles whose purpose is showing text, kept by the text_inclusive profile even while empty
+const TEXT_ROLES: [&str; 2] = ["AXStaticText", "AXHeading"];
+
+// Whether an element belongs in a listing made with the given classification profile
+pub fn is_listed(profile: ClassificationProfile, non_interactable: bool, has_text: bool, role: &str) -> bool {
+    match profile {
+        ClassificationProfile::Strict => !non_interactable,
+        ClassificationProfile::Loose => !non_interactable || has_text,
+        ClassificationProfile::TextInclusive => !non_interactable || has_text || TEXT_ROLES.contains(&role),
+    }
+}
+
+// The JSON object for one listed element, with the fields `format` asks for
+pub fn listing_entry(element: &UIElement, attrs: &UIElementAttributes, index: usize, format: ListFormat) -> Value {
+    // Create a complete attributes object - removed id field
+    let mut element_data = json!({
+        "index": index,
+        "role": attrs.role,
+        // "id" field removed as requested
+    });
+    
+    // Check if we have role description in properties and modify role field
+    let role_without_ax = attrs.role.trim_start_matches("AX");
+    
+    if let Some(props) = attrs.properties.get("AXRoleDescription") {
+        // First check if the property exists, then if it's a string value
+        if let Some(role_desc) = props.as_ref().and_then(|v| v.as_str()) {
+            // Only include role description if it's different from the role (after removing AX prefix)
+            if !role_desc.eq_ignore_ascii_case(role_without_ax) {
+                element_data["role"] = Value::String(format!("{} ({})", attrs.role, role_desc));
+            }
+        }
+    }
+    
+    // Collect all text content for the combined field
+    let mut text_parts = Vec::new();
+    
+    // Collect value, label, description for text_parts
+    if let Some(value) = &attrs.value {
+        if !value.is_empty() {
+            text_parts.push(value.clone());
+        }
+    }
+    
+    if let Some(label) = &attrs.label {
+        if !label.is_empty() {
+            text_parts.push(label.clone());
+        }
+    }
+    
+    if let Some(desc) = &attrs.description {
+        if !desc.is_empty() {
+            text_parts.push(desc.clone());
+        }
+    }
+    
+    // Add text values from properties if they exist
+    for (key, value_opt) in &attrs.properties {
+        // Skip properties that are likely to be non-human-readable
+        if key.contains("Parent") || 
+           key.contains("Children") || 
+           key == "AXRoleDescription" || 
+           key == "AXRole" || 
+           key == "AXTopLevelUIElement" || 
+           key == "AXWindow" ||
+           key == "AXMenuItemMarkChar" {
+            continue;
+        }
+        
+        if let Some(value) = value_opt {
+            if let Some(text_value) = value.as_str() {
+                if !text_value.is_empty() {
+                    text_parts.push(text_value.to_string());
+                }
+            }
+        }
+    }
+    
+    // Create the text field with all content
+    let has_text = !text_parts.is_empty();
+    if has_text {
+        let combined_text = join_text_parts(&text_parts);
+        
+        if index < 5 {  
+            info!("element {}: text field created: '{}'", index, &combined_text);
+        }
+        
+        element_data["text"] = Value::String(combined_text);
+        
+        // Keep the raw parts around for mixed-direction text so callers don't have to strip isolates
+        if format != ListFormat::Compact && text_parts.iter().any(|part| contains_rtl(part)) {
+            element_data["text_parts"] = json!(text_parts);
+        }
+    }
+    
+    if format != ListFormat::Compact {
+        // Menu items report their toggle/submenu state so agents can check before acting
+        if let Some(flags) = menu_item_flags(attrs) {
+            for (key, value) in flags {
+                element_data[key] = Value::Bool(value);
+            }
+        }
+        
+        let flag = |name: &str| attrs.properties.get(name).and_then(|v| v.as_ref()).and_then(|v| v.as_bool());
+        if let Some(enabled) = flag("AXEnabled") {
+            element_data["enabled"] = Value::Bool(enabled);
+        }
+        if let Some(focused) = flag("AXFocused") {
+            element_data["focused"] = Value::Bool(focused);
+        }
+        if let Ok((x, y, width, height)) = element.bounds() {
+            element_data["bounds"] = json!(ElementBounds { x, y, width, height });
+        }
+    }
+    
+    // Everything the accessibility api reported, for debugging
+    if format == ListFormat::Verbose {
+        for (key, field) in [("label", &attrs.label), ("value", &attrs.value), ("description", &attrs.description)] {
+            if let Some(field) = field {
+                element_data[key] = Value::String(field.clone());
+            }
+        }
+        element_data["properties"] = json!(attrs.properties);
+    }
+    
+    element_data
+}
+
+// Hash of every listed element's role and text, in index order
+fn ui_fingerprint(elements: &[Value]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for element in elements {
+        element["role"].as_str().unwrap_or_default().hash(&mut hasher);
+        element["text"].as_str().unwrap_or_default().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// Hash of what an action can change about an element: its role, text, value and whether it's
+// enabled. Bounds, score and the fields that depend on the listing's format are left out.
+fn element_state_hash(attrs: &UIElementAttributes, text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    attrs.role.hash(&mut hasher);
+    text.hash(&mut hasher);
+    attrs.value.hash(&mut hasher);
+    attrs
+        .properties
+        .get("AXEnabled")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_bool())
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+// checked / has_submenu / is_separator for menu items, read from the AX properties
+// (the role itself is mapped to "button", so look at the raw AXRole)
+pub fn menu_item_flags(attrs: &UIElementAttributes) -> Option<[(&'static str, bool); 3]> {
+    let property = |name: &str| attrs.properties.get(name).and_then(|v| v.as_ref());
+    if property("AXRole").and_then(|v| v.as_str()) != Some("AXMenuItem") {
+        return None;
+    }
+
+    let checked = property("AXMenuItemMarkChar")
+        .and_then(|v| v.as_str())
+        .map_or(false, |mark| !mark.trim().is_empty());
+    // A submenu hangs off the item as its only child (an AXMenu)
+    let has_submenu = property("AXChildren")
+        .and_then(|v| v.as_array())
+        .map_or(false, |children| !children.is_empty());
+    // Separators are untitled, disabled items
+    let untitled = attrs.label.as_deref().map_or(true, |label| label.is_empty());
+    let enabled = property("AXEnabled").and_then(|v| v.as_bool()).unwrap_or(true);
+    let is_separator = untitled && !enabled && !has_submenu;
+
+    Some([("checked", checked), ("has_submenu", has_submenu), ("is_separator", is_separator)])
+}
+
+// Share of the previous elements that must still be present to keep their indices.
+// Below this the ui changed structurally and the list is renumbered in traversal order.
+const MIN_PRESERVED_RATIO: f64 = 0.5;
+
+// Reorder freshly listed elements so that elements with the same stable id as in the
+// previous list keep their previous index. Genuinely new elements first fill the slots
+// of removed elements and are then appended at the end.
+fn preserve_previous_indices<T>(
+    elements: Vec<T>,
+    ids: Vec<String>,
+    previous_ids: &[String],
+) -> (Vec<T>, Vec<String>, IndexStabilityInfo) {
+    // Stable ids are not guaranteed unique, so keep a queue of previous positions per id
+    let mut previous_positions: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (i, id) in previous_ids.iter().enumerate() {
+        previous_positions.entry(id.as_str()).or_default().push_back(i);
+    }
+
+    let assignments: Vec<Option<usize>> = ids
+        .iter()
+        .map(|id| previous_positions.get_mut(id.as_str()).and_then(|positions| positions.pop_front()))
+        .collect();
+    let matched_count = assignments.iter().filter(|a| a.is_some()).count();
+
+    // Previous indices whose element is gone from this listing
+    let mut removed_indices: Vec<usize> = previous_positions.values().flatten().copied().collect();
+    removed_indices.sort_unstable();
+
+    // Previous indices that now point at another element, or past the end of the list
+    let changed_against_previous = |ordered_ids: &[String]| -> Vec<usize> {
+        previous_ids
+            .iter()
+            .enumerate()
+            .filter(|(i, id)| ordered_ids.get(*i) != Some(*id))
+            .map(|(i, _)| i)
+            .collect()
+    };
+
+    // Structural change - renumber from scratch
+    if previous_ids.is_empty() || (matched_count as f64) < (previous_ids.len() as f64) * MIN_PRESERVED_RATIO {
+        let changed_indices = changed_against_previous(&ids);
+        let stability = IndexStabilityInfo {
+            preserved_count: previous_ids.len() - changed_indices.len(),
+            changed_indices,
+            removed_indices,
+            new_count: ids.len().saturating_sub(previous_ids.len()),
+            renumbered: true,
+        };
+        return (elements, ids, stability);
+    }
+
+    let mut slots: Vec<Option<(T, String)>> = (0..previous_ids.len()).map(|_| None).collect();
+    let mut new_entries = Vec::new();
+    for ((element, id), assignment) in elements.into_iter().zip(ids).zip(assignments) {
+        match assignment {
+            Some(previous_index) => slots[previous_index] = Some((element, id)),
+            None => new_entries.push((element, id)),
+        }
+    }
+
+    let mut new_entries = new_entries.into_iter();
+    let mut ordered: Vec<(T, String)> = Vec::with_capacity(slots.len());
+    // Whether a removed element's slot stayed empty with elements after it, which then
+    // move up one index each
+    let mut shifted = false;
+    let mut open_slot = false;
+    for slot in slots {
+        match slot {
+            Some(entry) => {
+                shifted |= open_slot;
+                ordered.push(entry);
+            }
+            // Slot of a removed element - reuse it for a new element if there is one
+            None => match new_entries.next() {
+                Some(entry) => ordered.push(entry),
+                None => open_slot = true,
+            },
+        }
+    }
+    ordered.extend(new_entries);
+
+    let (elements, ids): (Vec<T>, Vec<String>) = ordered.into_iter().unzip();
+    let changed_indices = changed_against_previous(&ids);
+    let stability = IndexStabilityInfo {
+        preserved_count: previous_ids.len() - changed_indices.len(),
+        changed_indices,
+        removed_indices,
+        new_count: ids.len() - matched_count,
+        renumbered: shifted,
+    };
+
+    (elements, ids, stability)
+}
+
 // Function to generate statistics about the elements
 fn generate_element_statistics(
     elements: &[serde_json::Value], 
@@ -366,6 +1205,13 @@ fn generate_element_statistics(
         without_text_count,
         top_roles,
         properties,
+        truncated: false,
+        total_available: elements.len(),
+        classification: ClassificationProfile::default(),
+        excluded_by_region: 0,
+        excluded_by_window: 0,
+        excluded_by_score: 0,
+        excluded_by_filter: 0,
     }
 }
 
@@ -392,4 +1238,290 @@ curl -X POST http://localhost:8080/api/list-elements-and-attributes \
     "  expires_at: \(.cache_info.expires_at)",
     "  element_count: \(.cache_info.element_count)",
     "  ttl_seconds: \(.cache_info.ttl_seconds)"'
-*/
\ No newline at end of file
+*/
+
+// Unicode first-strong isolate / pop directional isolate
+const FSI: char = '\u{2068}';
+const PDI: char = '\u{2069}';
+
+// Join value/label/description for the text field. Plain spaces are enough for
+// left-to-right text, but when any part is right-to-left each part is wrapped in
+// its own isolate so the parts keep their logical order instead of being
+// reordered by the bidi algorithm when rendered.
+fn join_text_parts(parts: &[String]) -> String {
+    if !parts.iter().any(|part| contains_rtl(part)) {
+        return parts.join(" ");
+    }
+
+    parts
+        .iter()
+        .map(|part| format!("{}{}{}", FSI, part, PDI))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Hebrew, Arabic, Syriac, Thaana, NKo and the RTL presentation forms
+fn contains_rtl(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c,
+            '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu_item(label: &str, properties: Vec<(&str, Value)>) -> UIElementAttributes {
+        let mut all: HashMap<String, Option<Value>> = properties
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), Some(value)))
+            .collect();
+        all.insert("AXRole".to_string(), Some(json!("AXMenuItem")));
+        UIElementAttributes {
+            role: "button".to_string(),
+            label: Some(label.to_string()),
+            value: None,
+            description: None,
+            properties: all,
+        }
+    }
+
+    #[test]
+    fn reads_menu_item_flags() {
+        let checked = menu_item("as Icons", vec![("AXMenuItemMarkChar", json!("✓")), ("AXEnabled", json!(true))]);
+        assert_eq!(menu_item_flags(&checked), Some([("checked", true), ("has_submenu", false), ("is_separator", false)]));
+
+        let submenu = menu_item("Sort By", vec![("AXChildren", json!(["1234"])), ("AXEnabled", json!(true))]);
+        assert_eq!(menu_item_flags(&submenu), Some([("checked", false), ("has_submenu", true), ("is_separator", false)]));
+
+        let separator = menu_item("", vec![("AXEnabled", json!(false))]);
+        assert_eq!(menu_item_flags(&separator), Some([("checked", false), ("has_submenu", false), ("is_separator", true)]));
+    }
+
+    #[test]
+    fn element_state_ignores_format_dependent_fields() {
+        let item = menu_item("Save", vec![("AXEnabled", json!(true))]);
+        let moved = menu_item("Save", vec![("AXEnabled", json!(true)), ("AXPosition", json!({"x": 10, "y": 20}))]);
+        assert_eq!(element_state_hash(&item, "Save"), element_state_hash(&moved, "Save"));
+
+        let disabled = menu_item("Save", vec![("AXEnabled", json!(false))]);
+        assert_ne!(element_state_hash(&item, "Save"), element_state_hash(&disabled, "Save"));
+        assert_ne!(element_state_hash(&item, "Save"), element_state_hash(&item, "Save As"));
+    }
+
+    #[test]
+    fn ignores_non_menu_items() {
+        let mut button = menu_item("OK", vec![]);
+        button.properties.insert("AXRole".to_string(), Some(json!("AXButton")));
+        assert_eq!(menu_item_flags(&button), None);
+    }
+
+    fn element(role: &str, label: Option<&str>) -> UIElementAttributes {
+        UIElementAttributes {
+            role: role.to_string(),
+            label: label.map(str::to_string),
+            value: None,
+            description: None,
+            properties: HashMap::from([("AXRole".to_string(), Some(json!(role)))]),
+        }
+    }
+
+    #[test]
+    fn builds_path_segments_from_role_and_label() {
+        assert_eq!(path_segment(&element("AXButton", Some("Send"))), "Button[Send]");
+        assert_eq!(path_segment(&element("AXToolbar", None)), "Toolbar");
+        assert_eq!(path_segment(&element("AXRow", Some("Clients/2024 [old]"))), "Row[Clients 2024  old]");
+    }
+
+    #[test]
+    fn numbers_duplicate_paths() {
+        let mut path_map = serde_json::Map::new();
+        for index in 0..3 {
+            insert_unique(&mut path_map, "Window/Button[OK]".to_string(), json!({"index": index}));
+        }
+        assert_eq!(path_map["Window/Button[OK]"]["index"], 0);
+        assert_eq!(path_map["Window/Button[OK]#2"]["index"], 1);
+        assert_eq!(path_map["Window/Button[OK]#3"]["index"], 2);
+    }
+
+    #[test]
+    fn matches_if_none_match_lists_and_weak_tags() {
+        assert!(etag_matches("\"abc\"", "abc"));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "abc"));
+        assert!(!etag_matches("*", "abc"));
+        assert!(!etag_matches("\"abd\"", "abc"));
+    }
+
+    #[test]
+    fn etag_changes_with_content() {
+        let ids = vec!["1".to_string()];
+        let entries = vec![json!({"index": 0, "role": "AXButton", "text": "Send"})];
+        let edited = vec![json!({"index": 0, "role": "AXButton", "text": "Sent"})];
+        let tag = listing_etag(ListFormat::Standard, &ids, &entries);
+        assert_eq!(tag, listing_etag(ListFormat::Standard, &ids, &entries));
+        assert_ne!(tag, listing_etag(ListFormat::Standard, &ids, &edited));
+        assert_ne!(tag, listing_etag(ListFormat::Compact, &ids, &entries));
+    }
+
+    #[test]
+    fn region_keeps_overlapping_bounds() {
+        let sidebar = ElementBounds { x: 0.0, y: 100.0, width: 200.0, height: 600.0 };
+        assert!(sidebar.intersects((10.0, 120.0, 50.0, 20.0)));
+        // Straddling the edge still counts, touching it doesn't
+        assert!(sidebar.intersects((190.0, 120.0, 50.0, 20.0)));
+        assert!(!sidebar.intersects((200.0, 120.0, 50.0, 20.0)));
+        assert!(!sidebar.intersects((10.0, 0.0, 50.0, 100.0)));
+    }
+
+    #[test]
+    fn warns_about_near_empty_windows_only() {
+        assert!(accessibility_warning(0, 0, None, false).is_some());
+        assert!(accessibility_warning(12, 4, Some((1200.0, 800.0)), false).is_some());
+        // A real UI, or a small palette that simply has little in it
+        assert!(accessibility_warning(80, 30, Some((1200.0, 800.0)), false).is_none());
+        assert!(accessibility_warning(6, 2, Some((150.0, 100.0)), false).is_none());
+        assert!(accessibility_warning(6, 2, None, false).is_none());
+
+        let tried = accessibility_warning(12, 4, Some((1200.0, 800.0)), true).unwrap();
+        assert!(!tried.contains("enable_enhanced_accessibility"));
+    }
+
+    #[test]
+    fn classification_profiles_nest() {
+        // An icon button, a text-only label and an empty label
+        assert!(is_listed(ClassificationProfile::Strict, false, false, "AXButton"));
+        assert!(!is_listed(ClassificationProfile::Strict, true, true, "AXStaticText"));
+        assert!(is_listed(ClassificationProfile::Loose, true, true, "AXStaticText"));
+        assert!(!is_listed(ClassificationProfile::Loose, true, false, "AXStaticText"));
+        assert!(is_listed(ClassificationProfile::TextInclusive, true, false, "AXStaticText"));
+        assert!(!is_listed(ClassificationProfile::TextInclusive, true, false, "AXGroup"));
+    }
+
+    #[test]
+    fn joins_ltr_parts_with_spaces() {
+        let parts = vec!["Save".to_string(), "Save the document".to_string()];
+        assert_eq!(join_text_parts(&parts), "Save Save the document");
+    }
+
+    #[test]
+    fn isolates_each_part_of_mixed_arabic_and_english() {
+        let parts = vec!["حفظ".to_string(), "Save file".to_string(), "ملف PDF".to_string()];
+        let joined = join_text_parts(&parts);
+
+        assert_eq!(
+            joined,
+            "\u{2068}حفظ\u{2069} \u{2068}Save file\u{2069} \u{2068}ملف PDF\u{2069}"
+        );
+
+        // Stripping the isolates gives back the parts in logical order
+        let stripped: String = joined.chars().filter(|c| *c != FSI && *c != PDI).collect();
+        assert_eq!(stripped, parts.join(" "));
+    }
+
+    #[test]
+    fn detects_rtl_scripts() {
+        assert!(contains_rtl("שלום"));
+        assert!(contains_rtl("Open مستند"));
+        assert!(!contains_rtl("Open document"));
+    }
+
+    #[test]
+    fn scores_pressable_enabled_visible_controls_highest() {
+        let actions = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let visible = Some((0.0, 0.0, 80.0, 24.0));
+
+        assert_eq!(interactability_score(&actions(&["AXPress", "AXShowMenu"]), true, Some(true), visible), 1.0);
+        assert_eq!(interactability_score(&actions(&["AXIncrement"]), true, None, visible), 0.85);
+        // Disabled and collapsed
+        assert_eq!(interactability_score(&actions(&["AXPress"]), true, Some(false), Some((0.0, 0.0, 0.0, 0.0))), 0.7);
+        // A label that only offers the context menu
+        assert_eq!(interactability_score(&actions(&["AXShowMenu"]), false, None, visible), 0.3);
+    }
+
+    #[test]
+    fn summary_counts_roles_and_windows_and_ranks_controls_by_size() {
+        let entries: Vec<Value> = (0..5).map(|index| json!({"index": index, "role": "AXButton"})).collect();
+        let item = |i: usize, role, interactable, bounds| SummaryItem { entry: &entries[i], role, interactable, bounds };
+        let items = vec![
+            item(0, "AXWindow", true, Some((0.0, 0.0, 800.0, 600.0))),
+            item(1, "AXButton", true, Some((10.0, 10.0, 20.0, 20.0))),
+            item(2, "AXButton", true, Some((10.0, 50.0, 100.0, 30.0))),
+            item(3, "AXStaticText", false, Some((10.0, 90.0, 300.0, 40.0))),
+            item(4, "AXButton", true, Some((900.0, 10.0, 20.0, 20.0))),
+        ];
+        let windows = vec![(0, Some("Inbox".to_string()), (0.0, 0.0, 800.0, 600.0))];
+
+        let summary = summarize_listing(&items, &windows, 3, 2);
+
+        assert_eq!(summary.element_count, 5);
+        assert_eq!(summary.roles, vec![RoleCount { role: "AXButton".to_string(), count: 5 }]);
+        // Everything but the button off to the right of the window
+        assert_eq!(summary.windows[0].element_count, 4);
+        // Containers and static text aren't prominent, the larger button comes first
+        let prominent: Vec<u64> = summary.prominent.iter().map(|entry| entry["index"].as_u64().unwrap()).collect();
+        assert_eq!(prominent, vec![2, 1]);
+    }
+
+    #[test]
+    fn filters_by_role_text_and_interactability() {
+        let send = json!({"index": 3, "role": "AXButton", "text": "Send Message"});
+        let label = json!({"index": 4, "role": "AXStaticText", "text": "Sent yesterday"});
+        let request = ListInteractableElementsRequest {
+            role_filter: Some(vec!["button".to_string()]),
+            ..Default::default()
+        };
+        assert!(passes_filters(&request, Some("send"), false, &send));
+        assert!(!passes_filters(&request, Some("send"), true, &label));
+        assert!(!passes_filters(&request, Some("cancel"), false, &send));
+
+        let request = ListInteractableElementsRequest {
+            interactable_only: Some(true),
+            ..Default::default()
+        };
+        assert!(passes_filters(&request, None, false, &send));
+        assert!(!passes_filters(&request, None, true, &label));
+    }
+
+    #[test]
+    fn reports_removals_that_shift_indices() {
+        let ids = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let previous = ids(&["a", "b", "c", "d", "e"]);
+
+        // A new element takes the removed one's slot, so nothing else moves
+        let (_, ordered, stability) = preserve_previous_indices(vec![(); 5], ids(&["a", "c", "d", "e", "x"]), &previous);
+        assert_eq!(ordered, ids(&["a", "x", "c", "d", "e"]));
+        assert_eq!(stability.changed_indices, vec![1]);
+        assert_eq!(stability.removed_indices, vec![1]);
+        assert!(!stability.renumbered);
+
+        // Nothing fills the slot: everything after it moves up, and the last index is gone
+        let (_, ordered, stability) = preserve_previous_indices(vec![(); 4], ids(&["a", "c", "d", "e"]), &previous);
+        assert_eq!(ordered, ids(&["a", "c", "d", "e"]));
+        assert_eq!(stability.changed_indices, vec![1, 2, 3, 4]);
+        assert_eq!(stability.removed_indices, vec![1]);
+        assert_eq!(stability.preserved_count, 1);
+        assert!(stability.renumbered);
+
+        // Dropping the last element shifts nothing, but its index is reported
+        let (_, _, stability) = preserve_previous_indices(vec![(); 4], ids(&["a", "b", "c", "d"]), &previous);
+        assert_eq!(stability.changed_indices, vec![4]);
+        assert_eq!(stability.removed_indices, vec![4]);
+        assert!(!stability.renumbered);
+    }
+
+    #[test]
+    fn element_ids_depend_on_role_text_and_path_and_are_unique() {
+        let path = vec!["AXWindow".to_string(), "AXToolbar".to_string()];
+        let send = element_id_hash("AXButton", "Send", &path);
+        assert_eq!(send, element_id_hash("AXButton", "Send", &path));
+        assert_ne!(send, element_id_hash("AXButton", "Cancel", &path));
+        assert_ne!(send, element_id_hash("AXMenuItem", "Send", &path));
+        assert_ne!(send, element_id_hash("AXButton", "Send", &path[..1]));
+
+        let ids = unique_element_ids(vec!["a".to_string(), "b".to_string(), "a".to_string(), "a".to_string()]);
+        assert_eq!(ids, vec!["a", "b", "a-2", "a-3"]);
+    }
+}
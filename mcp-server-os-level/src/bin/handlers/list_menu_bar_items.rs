@@ -0,0 +1,99 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, ElementBounds, ListMenuBarItemsRequest, ListMenuBarItemsResponse, MenuBarItemInfo};
+
+pub async fn list_menu_bar_items_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ListMenuBarItemsRequest>,
+) -> Result<JsonResponse<ListMenuBarItemsResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Menu extras mostly belong to background agents, so include background apps
+    let desktop = match Desktop::new(true, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let extras = match desktop.menu_bar_extras() {
+        Ok(extras) => extras,
+        Err(e) => {
+            error!("failed to list menu bar extras: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to list menu bar extras: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let owners = app_names_by_pid(&desktop);
+    let owner_of = |element: &UIElement| element.process_id().ok().and_then(|pid| owners.get(&pid).cloned());
+
+    let extras: Vec<UIElement> = match &request.app_name {
+        Some(app_name) => extras
+            .into_iter()
+            .filter(|element| owner_of(element).map_or(false, |owner| owner.eq_ignore_ascii_case(app_name)))
+            .collect(),
+        None => extras,
+    };
+
+    let items = extras
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            let attrs = element.attributes();
+            MenuBarItemInfo {
+                index,
+                owner_app: owner_of(element),
+                pid: element.process_id().ok(),
+                // Status items are usually untitled and carry their name in the description
+                title: attrs
+                    .label
+                    .filter(|label| !label.is_empty())
+                    .or(attrs.description.filter(|description| !description.is_empty())),
+                bounds: element
+                    .bounds()
+                    .ok()
+                    .map(|(x, y, width, height)| ElementBounds { x, y, width, height }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    info!("found {} menu bar items", items.len());
+
+    {
+        let mut cache = state.menu_bar_cache.lock().await;
+        *cache = Some((extras, Instant::now()));
+    }
+
+    Ok(JsonResponse(ListMenuBarItemsResponse { items }))
+}
+
+// Names of the running apps, including background agents, keyed by pid
+pub fn app_names_by_pid(desktop: &Desktop) -> HashMap<i32, String> {
+    desktop
+        .applications()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|app| {
+            let pid = app.process_id().ok()?;
+            let name = app.attributes().label.filter(|name| !name.is_empty())?;
+            Some((pid, name))
+        })
+        .collect()
+}
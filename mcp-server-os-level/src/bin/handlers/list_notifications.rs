@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{AutomationError, Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, ListNotificationsRequest, ListNotificationsResponse, NotificationInfo};
+use crate::handlers::utils::resolve_application;
+
+const NOTIFICATION_CENTER_BUNDLE_ID: &str = "com.apple.notificationcenterui";
+
+// Banners and alerts are groups with these subroles
+const NOTIFICATION_SUBROLES: [&str; 2] = ["AXNotificationCenterBanner", "AXNotificationCenterAlert"];
+
+// Read the banners and alerts Notification Center is showing right now. Only what is on
+// screen is visible to accessibility: banners that already slid away, and notifications
+// collapsed into a stack below the top one, aren't listed.
+pub async fn list_notifications_handler(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<ListNotificationsRequest>,
+) -> Result<JsonResponse<ListNotificationsResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Don't activate anything, that would dismiss the banners we're reading
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let notification_center = match resolve_application(&desktop, "NotificationCenter", Some(NOTIFICATION_CENTER_BUNDLE_ID)) {
+        Ok(app) => app,
+        Err(e) => {
+            // It is launched on demand, so not running just means nothing is showing
+            info!("notification center is not running: {}", e);
+            return Ok(JsonResponse(ListNotificationsResponse {
+                notifications: Vec::new(),
+                message: Some("notification center is not running, so no notifications are showing".to_string()),
+            }));
+        }
+    };
+
+    let elements = match notification_center.locator("").and_then(|locator| locator.all()) {
+        Ok(elements) => elements,
+        Err(AutomationError::PermissionDenied(e)) => {
+            error!("not allowed to read notification center: {}", e);
+            return Err((
+                StatusCode::FORBIDDEN,
+                JsonResponse(json!({
+                    "error": format!("PERMISSION_DENIED: not allowed to read notification center: {}", e),
+                    "code": "PERMISSION_DENIED",
+                    "guidance": "grant this server Accessibility permission (System Settings > Privacy & Security > Accessibility), then retry"
+                })),
+            ));
+        }
+        Err(e) => {
+            // An empty notification window has no children to walk
+            debug!("failed to walk notification center: {}", e);
+            Vec::new()
+        }
+    };
+
+    let filter = request.app_name.as_deref().map(str::to_lowercase);
+    let notifications: Vec<NotificationInfo> = elements
+        .iter()
+        .filter(|element| is_notification(element))
+        .map(read_notification)
+        .filter(|notification| notification.title.is_some() || notification.body.is_some())
+        .filter(|notification| match (&filter, &notification.app) {
+            (Some(wanted), Some(app)) => app.to_lowercase().contains(wanted),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .collect();
+
+    info!("found {} notifications", notifications.len());
+
+    Ok(JsonResponse(ListNotificationsResponse {
+        notifications,
+        message: None,
+    }))
+}
+
+fn is_notification(element: &UIElement) -> bool {
+    element
+        .attributes()
+        .properties
+        .get("AXSubrole")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .map_or(false, |subrole| NOTIFICATION_SUBROLES.contains(&subrole))
+}
+
+fn read_notification(element: &UIElement) -> NotificationInfo {
+    let attrs = element.attributes();
+    let texts: Vec<(Option<String>, String)> = element
+        .locator("role=AXStaticText")
+        .and_then(|locator| locator.all())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|text| {
+            let text_attrs = text.attributes();
+            let value = text_attrs.value.or(text_attrs.label)?;
+            let identifier = text_attrs
+                .properties
+                .get("AXIdentifier")
+                .and_then(|v| v.as_ref())
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            Some((identifier, value))
+        })
+        .collect();
+    notification_from_parts(attrs.description.as_deref(), texts)
+}
+
+// Assemble a notification from its static texts. They are tagged "title", "subtitle"
+// and "body" on current macOS; untagged ones are taken in order. The group's
+// description reads "<app>, <title>, <body>", which is the only place the app shows up.
+fn notification_from_parts(description: Option<&str>, texts: Vec<(Option<String>, String)>) -> NotificationInfo {
+    let mut notification = NotificationInfo { app: None, title: None, subtitle: None, body: None };
+    let mut untagged = Vec::new();
+    for (identifier, text) in texts {
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        match identifier.as_deref() {
+            Some("title") => notification.title = Some(text),
+            Some("subtitle") => notification.subtitle = Some(text),
+            Some("body") => notification.body = Some(text),
+            _ => untagged.push(text),
+        }
+    }
+
+    let mut untagged = untagged.into_iter();
+    if notification.title.is_none() {
+        notification.title = untagged.next();
+    }
+    if notification.body.is_none() {
+        let rest: Vec<String> = untagged.collect();
+        if !rest.is_empty() {
+            notification.body = Some(rest.join("\n"));
+        }
+    }
+
+    notification.app = description
+        .and_then(|description| description.split(", ").next())
+        .map(str::trim)
+        .filter(|app| !app.is_empty() && Some(*app) != notification.title.as_deref())
+        .map(str::to_string);
+
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_tagged_texts_and_app_from_description() {
+        let texts = vec![
+            (Some("title".to_string()), "Download complete".to_string()),
+            (Some("body".to_string()), "report.pdf".to_string()),
+        ];
+        let notification = notification_from_parts(Some("Safari, Download complete, report.pdf"), texts);
+        assert_eq!(notification.app.as_deref(), Some("Safari"));
+        assert_eq!(notification.title.as_deref(), Some("Download complete"));
+        assert_eq!(notification.subtitle, None);
+        assert_eq!(notification.body.as_deref(), Some("report.pdf"));
+    }
+
+    #[test]
+    fn takes_untagged_texts_in_order() {
+        let texts = vec![
+            (None, "Alex".to_string()),
+            (None, "See you at 5".to_string()),
+            (None, "Bring the slides".to_string()),
+        ];
+        let notification = notification_from_parts(None, texts);
+        assert_eq!(notification.app, None);
+        assert_eq!(notification.title.as_deref(), Some("Alex"));
+        assert_eq!(notification.body.as_deref(), Some("See you at 5\nBring the slides"));
+    }
+}
@@ -1,16 +1,20 @@
 use axum::{
     extract::{Json, State},
+    http::StatusCode,
     response::Json as JsonResponse,
 };
 use serde_json::{self, json, Value};
 use std::sync::Arc;
 use tracing::{info, error};
 
+use crate::config::ServerConfig;
+
 use crate::types::{AppState, ExecuteToolFunctionParams, 
                    ListInteractableElementsRequest, MCPRequest, ServerCapabilities, 
                    ToolFunctionDefinition, ToolServerCapabilities,
                    ClickByIndexRequest, TypeByIndexRequest, PressKeyByIndexRequest,
-                   OpenApplicationRequest, InputControlRequest, OpenUrlRequest};
+                   OpenApplicationRequest, InputControlRequest, OpenUrlRequest,
+                   OpenAndFindRequest, GetProgressRequest, ScreenshotByIndexRequest, ClickByTextRequest, GetClickPointByIndexRequest, ListMenuBarItemsRequest, ClickMenuBarItemRequest, DragFileToIndexRequest, DescribeByIndexRequest, ListAllElementsRequest, GetZoomRequest, SetZoomRequest, PressUntilRequest, LaunchViaSpotlightRequest, GetTableByIndexRequest, StepByIndexRequest, RunAppleScriptRequest, ListNotificationsRequest, TabToFieldRequest, GetTextByIndexRequest, OcrScreenRequest, DismissDialogRequest, GetCaretByIndexRequest, GetAppMetricsRequest, SetFieldByIndexRequest, ResetInputStateRequest, RightClickByIndexRequest, ScrollByIndexRequest, CloseApplicationRequest, CaptureScreenRequest, WaitForElementRequest, DragByIndexRequest, HoverByIndexRequest, ClearElementCacheRequest, ClickByIdRequest, TypeByIdRequest, ExecuteBatchRequest, GetElementByIndexRequest, FocusByIndexRequest, ListApplicationsRequest};
 
 // Update handler imports
 use crate::handlers::list_elements_and_attributes::list_elements_and_attributes_handler;
@@ -18,8 +22,49 @@ use crate::handlers::click_by_index::click_by_index_handler;
 use crate::handlers::type_by_index::type_by_index_handler;
 use crate::handlers::press_key_by_index::press_key_by_index_handler;
 use crate::handlers::open_application::open_application_handler;
+use crate::handlers::open_and_find::open_and_find_handler;
 use crate::handlers::open_url::open_url_handler;
 use crate::handlers::input_control::input_control_handler;
+use crate::handlers::list_applications::list_applications_handler;
+use crate::handlers::focus_by_index::focus_by_index_handler;
+use crate::handlers::get_element_by_index::get_element_by_index_handler;
+use crate::handlers::execute_batch::execute_batch_handler;
+use crate::handlers::type_by_id::type_by_id_handler;
+use crate::handlers::click_by_id::click_by_id_handler;
+use crate::handlers::clear_element_cache::clear_element_cache_handler;
+use crate::handlers::hover_by_index::hover_by_index_handler;
+use crate::handlers::drag_by_index::drag_by_index_handler;
+use crate::handlers::wait_for_element::wait_for_element_handler;
+use crate::handlers::capture_screen::capture_screen_handler;
+use crate::handlers::close_application::close_application_handler;
+use crate::handlers::scroll_by_index::scroll_by_index_handler;
+use crate::handlers::right_click_by_index::right_click_by_index_handler;
+use crate::handlers::reset_input_state::reset_input_state_handler;
+use crate::handlers::set_field_by_index::set_field_by_index_handler;
+use crate::handlers::get_app_metrics::get_app_metrics_handler;
+use crate::handlers::get_caret_by_index::get_caret_by_index_handler;
+use crate::handlers::dismiss_dialog::dismiss_dialog_handler;
+use crate::handlers::ocr_screen::ocr_screen_handler;
+use crate::handlers::get_text_by_index::get_text_by_index_handler;
+use crate::handlers::tab_to_field::tab_to_field_handler;
+use crate::handlers::list_notifications::list_notifications_handler;
+use crate::handlers::run_applescript::run_applescript_handler;
+use crate::handlers::step_by_index::step_by_index_handler;
+use crate::handlers::get_table_by_index::get_table_by_index_handler;
+use crate::handlers::launch_via_spotlight::launch_via_spotlight_handler;
+use crate::handlers::cache_status::cache_status_handler;
+use crate::handlers::press_until::press_until_handler;
+use crate::handlers::zoom::set_zoom_handler;
+use crate::handlers::zoom::get_zoom_handler;
+use crate::handlers::list_all_elements::list_all_elements_handler;
+use crate::handlers::describe_by_index::describe_by_index_handler;
+use crate::handlers::drag_file_to_index::drag_file_to_index_handler;
+use crate::handlers::click_menu_bar_item::click_menu_bar_item_handler;
+use crate::handlers::list_menu_bar_items::list_menu_bar_items_handler;
+use crate::handlers::get_click_point_by_index::get_click_point_by_index_handler;
+use crate::handlers::click_by_text::click_by_text_handler;
+use crate::handlers::screenshot_by_index::screenshot_by_index_handler;
+use crate::handlers::get_progress::get_progress_handler;
 
 // MCP handler
 pub async fn mcp_handler(
@@ -27,10 +72,13 @@ pub async fn mcp_handler(
     Json(request): Json<MCPRequest>,
 ) -> JsonResponse<Value> {
     info!("received mcp request: {:?}", request);
-    
-    // Handle different MCP methods
+    dispatch_mcp_request(state, request).await
+}
+
+// Route one JSON-RPC request to its MCP method; shared by every transport
+pub async fn dispatch_mcp_request(state: Arc<AppState>, request: MCPRequest) -> JsonResponse<Value> {
     match request.method.as_str() {
-        "initialize" => handle_initialize(request.id),
+        "initialize" => handle_initialize(request.id, &state.config),
         "executeToolFunction" => {
             if let Some(params) = request.params {
                 handle_execute_tool_function(state, request.id, params).await
@@ -42,12 +90,48 @@ pub async fn mcp_handler(
     }
 }
 
+// JSON-RPC error code for calls to tools turned off in the server config
+const METHOD_DISABLED_CODE: i32 = -32003;
+
 // Handler for initialize method
-pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
+pub fn handle_initialize(id: Value, config: &ServerConfig) -> JsonResponse<Value> {
+    // Only advertise the tools this deployment exposes
+    let tool_functions: Vec<ToolFunctionDefinition> = tool_definitions()
+        .into_iter()
+        .filter(|tool| config.is_tool_enabled(&tool.name))
+        .collect();
+    
+    let capabilities = ServerCapabilities {
+        tools: Some(ToolServerCapabilities {
+            functions: tool_functions,
+        }),
+        resources: None, // Implement if needed
+    };
+    
+    JsonResponse(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": {
+            "capabilities": capabilities
+        }
+    }))
+}
+
+// Every tool the server knows about, regardless of config
+pub fn tool_definitions() -> Vec<ToolFunctionDefinition> {
     let click_by_index_schema = json!({
         "type": "object",
         "properties": {
-            "element_index": {"type": "integer"}
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "click_count": {"type": "integer", "description": "2 to double-click (e.g. open an item), 3 to triple-click (e.g. select a line of text); default 1"},
+            "retry_if_unchanged": {"type": "boolean", "description": "click once more if the element listing is unchanged after the first click, which usually means it missed"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "refresh_elements": {"type": "boolean", "description": "list the app again after the action and return the elements (default true); set false mid-sequence to skip the listing, elements is then null"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
         },
         "required": ["element_index"]
     });
@@ -56,7 +140,19 @@ pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
         "type": "object",
         "properties": {
             "element_index": {"type": "integer"},
-            "text": {"type": "string"}
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "text": {"type": "string"},
+            "confirm_after": {"type": "boolean", "description": "commit the value with AXConfirm/Return after typing (e.g. address bars, search fields); detected from the role when omitted"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between focusing the element and sending input; raise for animation-heavy apps (defaults to the server's INTER_ACTION_DELAY_MS)"},
+            "strict_focus": {"type": "boolean", "description": "fail with FOCUS_MISMATCH instead of typing when focusing the element put the keyboard focus somewhere else; without it a mismatch is only reported as focus_warning"},
+            "focus_method": {"type": "string", "enum": ["click", "accessibility"], "description": "how to focus the element first: click (default) or accessibility, which sets focus without clicking and so avoids the click's own effect (toggling, following a link)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "method": {"type": "string", "enum": ["auto", "keystroke", "paste", "accessibility"], "description": "how to enter the text (default auto: paste for non-ascii text, keystroke otherwise)"},
+            "refresh_elements": {"type": "boolean", "description": "list the app again after the action and return the elements (default true); set false mid-sequence to skip the listing, elements is then null"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
         },
         "required": ["element_index", "text"]
     });
@@ -65,7 +161,16 @@ pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
         "type": "object",
         "properties": {
             "element_index": {"type": "integer"},
-            "key_combo": {"type": "string"}
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "key_combo": {"type": "string"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between focusing the element and sending input; raise for animation-heavy apps (defaults to the server's INTER_ACTION_DELAY_MS)"},
+            "focus_method": {"type": "string", "enum": ["click", "accessibility"], "description": "how to focus the element first: click (default) or accessibility, which sets focus without clicking and so avoids the click's own effect (toggling, following a link)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "refresh_elements": {"type": "boolean", "description": "list the app again after the action and return the elements (default true); set false mid-sequence to skip the listing, elements is then null"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
         },
         "required": ["element_index", "key_combo"]
     });
@@ -73,16 +178,48 @@ pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
     let open_application_schema = json!({
         "type": "object",
         "properties": {
-            "app_name": {"type": "string"}
+            "app_name": {"type": "string", "description": "display name, or a bundle identifier such as com.apple.Safari (opened with open -b)"},
+            "bundle_id": {"type": "string", "description": "bundle identifier such as com.apple.Safari; preferred over app_name when the display name is localized or ambiguous"},
+            "retries": {"type": "integer", "description": "how often to re-list an app that opened without elements (default 1); raise for slow launchers like Xcode"},
+            "retry_delay_ms": {"type": "integer", "description": "wait before the first retry, doubling for each further one (default 1000)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
+        },
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let open_and_find_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "role": {"type": "string", "description": "role of the element to find, e.g. AXButton or button"},
+            "text": {"type": "string", "description": "case-insensitive substring of the element text"},
+            "timeout_ms": {"type": "integer", "description": "how long to wait for the element to appear (default 5000)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"}
         },
-        "required": ["app_name"]
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
     });
     
     let open_url_schema = json!({
         "type": "object",
         "properties": {
             "url": {"type": "string"},
-            "browser": {"type": "string"}
+            "browser": {"type": "string", "description": "browser app to open the url in; defaults to the server's DEFAULT_BROWSER, or the system default"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
         },
         "required": ["url"]
     });
@@ -96,7 +233,7 @@ pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
                         "type": "object",
                         "properties": {
                             "type": { "type": "string", "enum": ["KeyPress"] },
-                            "data": { "type": "string", "description": "Key code number or key name" }
+                            "data": { "type": "string", "description": "key name (Return, Tab, Escape, Delete, ForwardDelete, Home, End, PageUp, PageDown, arrows like Left, F1-F12) or single character, optionally with modifiers (cmd, shift, option, ctrl) as in \"cmd+shift+4\"; or a raw key code as \"code:49\"" }
                         },
                         "required": ["type", "data"]
                     },
@@ -130,90 +267,1878 @@ pub fn handle_initialize(id: Value) -> JsonResponse<Value> {
                             "data": { "type": "string" }
                         },
                         "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "type": "string", "enum": ["KeyDown", "KeyUp"] },
+                            "data": { "type": "integer", "description": "virtual key code (e.g. 49 = space, 123-126 = arrows); send KeyDown, then KeyUp later to hold a key" }
+                        },
+                        "required": ["type", "data"]
                     }
                 ]
-            }
+            },
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
         },
         "required": ["action"]
     });
     
-    // Define tool functions
-    let tool_functions = vec![
-        ToolFunctionDefinition {
-            name: "clickByIndex".to_string(),
-            description: "click on a ui element by its index and returns the updated element list. evaluate success by examining the updated elements to confirm ui responded as expected, not just whether the click executed.".to_string(),
-            parameters: click_by_index_schema,
+    let get_progress_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"}
         },
-        ToolFunctionDefinition {
-            name: "typeByIndex".to_string(),
-            description: "type text into a ui element by its index and returns the updated element list. evaluate success by examining if the text was accepted and ui updated appropriately.".to_string(),
-            parameters: type_by_index_schema,
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let screenshot_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"}
         },
-        ToolFunctionDefinition {
-            name: "pressKeyByIndex".to_string(),
-            description: "press key combination on a ui element by its index and returns the updated element list. evaluate success by examining if the key press triggered expected ui changes.".to_string(),
-            parameters: press_key_by_index_schema,
+        "required": ["element_index"]
+    });
+    
+    let click_by_text_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "query": {"type": "string", "description": "text of the element to click; exact matches win over prefix matches, which win over substring matches"},
+            "role": {"type": "string", "description": "only consider elements with this role, e.g. AXButton or button"},
+            "exact": {"type": "boolean", "description": "only accept elements whose text equals the query"},
+            "retry_if_unchanged": {"type": "boolean", "description": "click once more if the element listing is unchanged after the first click, which usually means it missed"},
+            "restore_focus": {"type": "boolean"}
         },
-        ToolFunctionDefinition {
-            name: "openApplication".to_string(),
-            description: "open an application and return the list of interactable elements in the app. evaluate success by checking if application window and controls are visible.".to_string(),
-            parameters: open_application_schema,
+        "required": ["query"],
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let get_click_point_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"}
         },
-        ToolFunctionDefinition {
-            name: "openUrl".to_string(),
-            description: "open a url in a browser and return the list of interactable elements in the browser. if browser is not specified, chrome will be used by default. evaluate success by confirming expected page content is visible.".to_string(),
-            parameters: open_url_schema,
+        "required": ["element_index"]
+    });
+    
+    let list_menu_bar_items_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string", "description": "only list status items owned by this app, e.g. Control Center"}
+        }
+    });
+    
+    let click_menu_bar_item_schema = json!({
+        "type": "object",
+        "properties": {
+            "item_index": {"type": "integer", "description": "index from listMenuBarItems"}
         },
-        ToolFunctionDefinition {
-            name: "inputControl".to_string(),
-            description: "perform direct input control actions with these formats: KeyPress(string keyCode/name), MouseMove({x:number, y:number}), MouseClick(string 'left'/'right'), WriteText(string text). returns updated element list. evaluate success by confirming ui responded to the input as expected.".to_string(),
-            parameters: input_control_schema,
+        "required": ["item_index"]
+    });
+    
+    let drag_file_to_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string", "description": "path of the file to drop; it is revealed in finder and its icon dragged from there"},
+            "element_index": {"type": "integer", "description": "index of the drop target from the last listing"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "restore_focus": {"type": "boolean"}
         },
-    ];
+        "required": ["path", "element_index"]
+    });
     
-    let capabilities = ServerCapabilities {
-        tools: Some(ToolServerCapabilities {
-            functions: tool_functions,
-        }),
-        resources: None, // Implement if needed
-    };
+    let describe_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"}
+        },
+        "required": ["element_index"]
+    });
     
-    JsonResponse(json!({
-        "jsonrpc": "2.0",
-        "id": id,
-        "result": {
-            "capabilities": capabilities
+    let list_all_elements_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_names": {"type": "array", "items": {"type": "string"}, "description": "only walk these apps (display names or bundle ids); every running app when omitted"},
+            "max_apps": {"type": "integer", "description": "apps to walk at most (default 8, at most 20)"},
+            "max_elements": {"type": "integer", "description": "elements to return at most (default 1000, at most 3000)"},
+            "format": {"type": "string", "enum": ["compact", "standard", "verbose"]},
+            "classification": {"type": "string", "enum": ["strict", "loose", "text_inclusive"], "description": "strict lists interactable elements only, loose (default) also lists anything with text, text_inclusive also keeps empty static text and headings"}
         }
-    }))
-}
-
-// Handler for executeToolFunction method
-pub async fn handle_execute_tool_function(
-    state: Arc<AppState>,
-    id: Value,
-    params: Value,
-) -> JsonResponse<Value> {
-    // Parse the params
-    let execute_params: ExecuteToolFunctionParams = match serde_json::from_value(params) {
-        Ok(p) => p,
-        Err(e) => {
-            error!("invalid params: {}", e);
-            return mcp_error_response(
-                id, 
-                -32602, 
-                format!("invalid params: {}", e), 
-                None
-            );
+    });
+    
+    let get_zoom_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"}
+        },
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let set_zoom_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "zoom": {"type": "number", "description": "target zoom factor, e.g. 0.75 for 75% or 1.5 for 150%"}
+        },
+        "required": ["zoom"],
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let press_until_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "key_combo": {"type": "string", "description": "key to press repeatedly, e.g. down or tab"},
+            "condition": {
+                "type": "object",
+                "properties": {
+                    "text": {"type": "string", "description": "case-insensitive substring of the element text"},
+                    "state": {"type": "string", "enum": ["focused", "selected"], "description": "whether the element must have keyboard focus or be selected (default selected)"},
+                    "exact": {"type": "boolean", "description": "require the whole text to match"}
+                },
+                "required": ["text"]
+            },
+            "max_presses": {"type": "integer", "description": "give up after this many presses (default 20, at most 200)"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause after each press before checking"}
+        },
+        "required": ["key_combo", "condition"],
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let get_cache_status_schema = json!({
+        "type": "object",
+        "properties": {}
+    });
+    
+    let launch_via_spotlight_schema = json!({
+        "type": "object",
+        "properties": {
+            "query": {"type": "string", "description": "text to type into spotlight, e.g. an app name"},
+            "confirm": {"type": "boolean", "description": "press return on the top result (default true)"},
+            "results_delay_ms": {"type": "integer", "description": "time to let results populate before pressing return (default 800)"},
+            "timeout_ms": {"type": "integer", "description": "how long to wait for the launched app to come to the front (default 5000)"}
+        },
+        "required": ["query"]
+    });
+    
+    let get_table_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer", "description": "index of the table, outline or list (or the scroll area around it)"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "max_rows": {"type": "integer", "description": "rows to read at most (default 200, at most 1000)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let step_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer", "description": "index of the stepper, slider or the number field next to a stepper"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "direction": {"type": "string", "enum": ["increment", "decrement"]},
+            "count": {"type": "integer", "description": "how many steps to take (default 1, at most 100)"}
+        },
+        "required": ["element_index", "direction"]
+    });
+    
+    let run_applescript_schema = json!({
+        "type": "object",
+        "properties": {
+            "script": {"type": "string", "description": "applescript source to run with osascript"},
+            "timeout_ms": {"type": "integer", "description": "kill the script after this long (default 30000, at most 120000)"}
+        },
+        "required": ["script"]
+    });
+    
+    let list_notifications_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string", "description": "only notifications from this app"}
         }
-    };
+    });
     
-    info!("executing tool function: {} with args: {}", 
-        execute_params.function, execute_params.arguments);
+    let tab_to_field_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "label": {"type": "string", "description": "label of the field to focus (case-insensitive substring)"},
+            "exact": {"type": "boolean", "description": "require the whole label to match"},
+            "max_tabs": {"type": "integer", "description": "give up after this many tabs (default 20, at most 200)"},
+            "reverse": {"type": "boolean", "description": "tab backwards with shift+tab"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause after each tab before checking the focus"}
+        },
+        "required": ["label"],
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
     
-    // Execute the appropriate function
-    match execute_params.function.as_str() {
-        "listInteractableElementsByIndex" => {
-            let request: ListInteractableElementsRequest = match serde_json::from_value(execute_params.arguments) {
+    let get_text_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "include_children": {"type": "integer", "description": "levels of children to include, 0 for the element's own text only (default 10, at most 25)"},
+            "expected_text": {"type": "string", "description": "text the element should contain, e.g. what was just typed; the result's contains_expected says whether it does"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let ocr_screen_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string", "description": "capture this app's front window; the whole main display when neither this nor region is given"},
+            "bundle_id": {"type": "string"},
+            "region": {
+                "type": "object",
+                "description": "capture exactly this screen area, in points",
+                "properties": {
+                    "x": {"type": "number"},
+                    "y": {"type": "number"},
+                    "width": {"type": "number"},
+                    "height": {"type": "number"}
+                },
+                "required": ["x", "y", "width", "height"]
+            },
+            "min_confidence": {"type": "number", "description": "drop text recognized with less confidence (0-1, default 0.3)"},
+            "include_image": {"type": "boolean", "description": "attach the captured image as base64 (default true)"},
+            "image_max_width": {"type": "integer", "description": "downscale the attached image to at most this width (default 1280)"}
+        }
+    });
+    
+    let dismiss_dialog_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"},
+            "mode": {"type": "string", "enum": ["default", "cancel"], "description": "default presses return (the highlighted button), cancel presses escape (default: default)"}
+        }
+    });
+    
+    let get_caret_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let get_app_metrics_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string"}
+        },
+        "anyOf": [
+            {"required": ["app_name"]},
+            {"required": ["bundle_id"]}
+        ]
+    });
+    
+    let set_field_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "text": {"type": "string", "description": "the value the field should hold afterwards"},
+            "use_clipboard": {"type": "boolean", "description": "paste instead of typing (default: only for long or non-ascii text)"},
+            "confirm": {"type": "boolean", "description": "commit with AXConfirm or return after verifying (default: detected from the field's role)"},
+            "max_attempts": {"type": "integer", "description": "how often to retry until the value reads back right (default 2, at most 5)"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between steps"}
+        },
+        "required": ["element_index", "text"]
+    });
+    
+    let reset_input_state_schema = json!({
+        "type": "object",
+        "properties": {
+            "home": {
+                "type": "object",
+                "properties": {"x": {"type": "number"}, "y": {"type": "number"}},
+                "required": ["x", "y"],
+                "description": "where to park the cursor (default: the server's INPUT_HOME_POSITION, else left alone)"
+            },
+            "clipboard": {"type": "string", "description": "text to put back on the clipboard"}
+        }
+    });
+    
+    let right_click_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let scroll_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer", "description": "a scroll area, list, table or anything inside one"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "direction": {"type": "string", "enum": ["up", "down", "left", "right"]},
+            "amount": {"type": "number", "description": "lines to scroll (default 3)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
+        },
+        "required": ["element_index", "direction"]
+    });
+    
+    let close_application_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string", "description": "bundle identifier, instead of the display name"},
+            "force": {"type": "boolean", "description": "kill the process instead of asking it to quit; unsaved changes are lost (default false)"}
+        }
+    });
+    
+    let capture_screen_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string", "description": "capture only this app's front window"},
+            "bundle_id": {"type": "string", "description": "bundle identifier, instead of app_name"},
+            "display": {"type": "integer", "description": "display to capture, 1 = main (default)"},
+            "max_width": {"type": "integer", "description": "downscale to at most this width in pixels"},
+            "quality": {"type": "integer", "description": "encode as jpeg with this quality (1-100) instead of png"},
+            "annotate": {"type": "boolean", "description": "draw each element of the last listing (of app_name, if given) as a box labelled with its index, so an index seen in the image can be passed to the by-index tools"},
+            "annotate_max_elements": {"type": "integer", "description": "label at most this many elements (default 200)"},
+            "annotate_interactable_only": {"type": "boolean", "description": "skip groups, static text and other non-interactable roles"}
+        }
+    });
+    
+    let wait_for_element_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string"},
+            "bundle_id": {"type": "string", "description": "bundle identifier, instead of app_name"},
+            "role": {"type": "string", "description": "role to wait for, e.g. \"button\" or \"AXTextField\""},
+            "text_contains": {"type": "string", "description": "case-insensitive substring of the element's text"},
+            "timeout_ms": {"type": "integer", "description": "give up after this long (default 5000, max 60000)"},
+            "poll_interval_ms": {"type": "integer", "description": "pause between listings (default 250)"}
+        }
+    });
+    
+    let drag_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer", "description": "index of the element to pick up"},
+            "app_name": {"type": "string", "description": "app whose listing the indices are from, when several apps are listed (default: the most recent listing)"},
+            "target_index": {"type": "integer", "description": "index of the element to drop onto"},
+            "x": {"type": "number", "description": "screen x to drop at, instead of target_index"},
+            "y": {"type": "number", "description": "screen y to drop at, instead of target_index"},
+            "restore_focus": {"type": "boolean"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let hover_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "dwell_ms": {"type": "integer", "description": "how long to rest the cursor on the element before listing again (default 500, max 10000)"},
+            "restore_focus": {"type": "boolean"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let clear_element_cache_schema = json!({
+        "type": "object",
+        "properties": {
+            "app_name": {"type": "string", "description": "only forget this app's listings (name or bundle id); every listing when omitted"}
+        }
+    });
+    
+    let click_by_id_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_id": {"type": "string", "description": "element_id of the element's entry in the listing"},
+            "app_name": {"type": "string", "description": "app whose listing the id is from, when several apps are listed (default: the most recent listing)"},
+            "click_count": {"type": "integer", "description": "2 to double-click (e.g. open an item), 3 to triple-click (e.g. select a line of text); default 1"},
+            "retry_if_unchanged": {"type": "boolean", "description": "click once more if the element listing is unchanged after the first click, which usually means it missed"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"}
+        },
+        "required": ["element_id"]
+    });
+    
+    let type_by_id_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_id": {"type": "string", "description": "element_id of the element's entry in the listing"},
+            "app_name": {"type": "string", "description": "app whose listing the id is from, when several apps are listed (default: the most recent listing)"},
+            "text": {"type": "string"},
+            "confirm_after": {"type": "boolean", "description": "commit the value with AXConfirm/Return after typing (e.g. address bars, search fields); detected from the role when omitted"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between focusing the element and sending input; raise for animation-heavy apps (defaults to the server's INTER_ACTION_DELAY_MS)"},
+            "strict_focus": {"type": "boolean", "description": "fail with FOCUS_MISMATCH instead of typing when focusing the element put the keyboard focus somewhere else; without it a mismatch is only reported as focus_warning"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "method": {"type": "string", "enum": ["auto", "keystroke", "paste", "accessibility"], "description": "how to enter the text (default auto: paste for non-ascii text, keystroke otherwise)"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"}
+        },
+        "required": ["element_id", "text"]
+    });
+    
+    let execute_batch_schema = json!({
+        "type": "object",
+        "properties": {
+            "actions": {
+                "type": "array",
+                "description": "steps to run in order; each is {type, data} like inputControl actions",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "enum": ["KeyPress", "MouseMove", "MouseClick", "WriteText", "KeyDown", "KeyUp", "ClickByIndex", "TypeByIndex", "PressKeyByIndex"]},
+                        "data": {"description": "the inputControl payload for input types; for ClickByIndex {element_index, click_count?}, TypeByIndex {element_index, text, confirm_after?, method?}, PressKeyByIndex {element_index, key_combo}"}
+                    },
+                    "required": ["type", "data"]
+                }
+            },
+            "app_name": {"type": "string", "description": "app whose listing the indices are from, when several apps are listed (default: the most recent listing)"},
+            "refresh_after_each": {"type": "boolean", "description": "also list the app after every step, not only after the last (slower)"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between steps (defaults to the server's INTER_ACTION_DELAY_MS)"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"},
+            "debug_capture": {"type": "boolean", "description": "attach a small screenshot and an element summary (count, top roles, focused element) to every step that ran, the failing one included; slow, for troubleshooting"}
+        },
+        "required": ["actions"]
+    });
+    
+    let get_element_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let focus_by_index_schema = json!({
+        "type": "object",
+        "properties": {
+            "element_index": {"type": "integer"},
+            "app_name": {"type": "string", "description": "app whose listing the index is from, when several apps are listed (default: the most recent listing)"},
+            "text": {"type": "string", "description": "text to enter once the element has focus"},
+            "method": {"type": "string", "enum": ["auto", "keystroke", "paste", "accessibility"], "description": "how to enter the text (default auto: paste for non-ascii text, keystroke otherwise)"},
+            "key_combo": {"type": "string", "description": "key combination to press once the element has focus, after the text if both are given"},
+            "inter_action_delay_ms": {"type": "integer", "description": "pause between focusing the element and sending input (defaults to the server's INTER_ACTION_DELAY_MS)"},
+            "restore_focus": {"type": "boolean", "description": "reactivate the previously frontmost app afterwards so automation stays in the background; adds some latency"},
+            "include_screenshot": {"type": "boolean", "description": "attach a base64 screenshot of the app window after the action"},
+            "screenshot_max_width": {"type": "integer", "description": "downscale the screenshot to at most this width in pixels"},
+            "screenshot_quality": {"type": "integer", "description": "encode the screenshot as jpeg with this quality (1-100) instead of png"},
+            "delay_ms": {"type": "integer", "description": "wait before listing the app after the action; lower for snappy native apps, raise for heavy ones (defaults to the server's REFRESH_DELAY_MS, 500)"}
+        },
+        "required": ["element_index"]
+    });
+    
+    let list_applications_schema = json!({
+        "type": "object",
+        "properties": {
+            "include_background": {"type": "boolean", "description": "also list agents and helpers without a Dock icon, e.g. menu bar apps (default false)"}
+        }
+    });
+    
+    // Define tool functions
+    let tool_functions = vec![
+        ToolFunctionDefinition {
+            name: "clickByIndex".to_string(),
+            description: "click on a ui element by its index and returns the updated element list. evaluate success by examining the updated elements to confirm ui responded as expected, not just whether the click executed; ui_changes counts the elements the click added, removed or changed.".to_string(),
+            parameters: click_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "typeByIndex".to_string(),
+            description: "type text into a ui element by its index and returns the updated element list. evaluate success by examining if the text was accepted and ui updated appropriately. method picks how the text is entered: keystroke types like a user (autocomplete and key handlers fire) but mangles many non-ascii characters and emoji; paste goes through the clipboard (restored afterwards) and is exact for any text, though some fields ignore pastes; accessibility sets the value directly, exact and focus-free, but apps listening only for key events may not notice. auto (default) pastes non-ascii text and types the rest.".to_string(),
+            parameters: type_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "pressKeyByIndex".to_string(),
+            description: "press key combination on a ui element by its index and returns the updated element list. evaluate success by examining if the key press triggered expected ui changes.".to_string(),
+            parameters: press_key_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "openApplication".to_string(),
+            description: "open an application and return the list of interactable elements in the app. evaluate success by checking if application window and controls are visible.".to_string(),
+            parameters: open_application_schema,
+        },
+        ToolFunctionDefinition {
+            name: "openAndFind".to_string(),
+            description: "open or activate an application, wait for it to be ready, and return the index of the first element matching the given role and/or text along with the element list. use this instead of openApplication followed by a search when you already know what to interact with first.".to_string(),
+            parameters: open_and_find_schema,
+        },
+        ToolFunctionDefinition {
+            name: "openUrl".to_string(),
+            description: "open a url in a browser and return the list of interactable elements in the browser. if browser is not specified, chrome will be used by default. evaluate success by confirming expected page content is visible.".to_string(),
+            parameters: open_url_schema,
+        },
+        ToolFunctionDefinition {
+            name: "inputControl".to_string(),
+            description: "perform direct input control actions with these formats: KeyPress(string key name, character or 'code:N'), MouseMove({x:number, y:number}), MouseClick(string 'left'/'right'), WriteText(string text). returns updated element list. evaluate success by confirming ui responded to the input as expected.".to_string(),
+            parameters: input_control_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getProgress".to_string(),
+            description: "read progress bars and busy spinners in an application. returns each indicator's value, range and whether it is indeterminate, plus an overall busy flag. poll this to find out whether a long operation is still running; an empty list means nothing is loading.".to_string(),
+            parameters: get_progress_schema,
+        },
+        ToolFunctionDefinition {
+            name: "screenshotByIndex".to_string(),
+            description: "capture a png screenshot of a single ui element by its index, returned as base64. use it to visually check an element's state when its text and role aren't enough.".to_string(),
+            parameters: screenshot_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "clickByText".to_string(),
+            description: "find the element whose text best matches the query in an application and click it, returning the updated element list. fails listing the candidates when several elements match equally well; narrow with role or exact, or fall back to clickByIndex.".to_string(),
+            parameters: click_by_text_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getClickPointByIndex".to_string(),
+            description: "get the screen coordinate clickByIndex would click for an element (center of its bounds, in points) without clicking; use it to diagnose clicks that miss".to_string(),
+            parameters: get_click_point_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "listMenuBarItems".to_string(),
+            description: "list the status items in the macOS menu bar (wi-fi, battery, third-party menu extras) with the app that owns each; indices are used by clickMenuBarItem".to_string(),
+            parameters: list_menu_bar_items_schema,
+        },
+        ToolFunctionDefinition {
+            name: "clickMenuBarItem".to_string(),
+            description: "open a menu bar status item by its index from listMenuBarItems and return the items of the menu it reveals; those can then be clicked with clickByIndex".to_string(),
+            parameters: click_menu_bar_item_schema,
+        },
+        ToolFunctionDefinition {
+            name: "dragFileToIndex".to_string(),
+            description: "drag a file from finder onto a ui element by its index, for uploads and imports that only accept drag-and-drop. the finder window opened for the drag must not cover the target. returns the updated element list; evaluate success by checking the target shows the file.".to_string(),
+            parameters: drag_file_to_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "describeByIndex".to_string(),
+            description: "describe a ui element by its index in one short sentence, e.g. \"enabled 'Send' button at top-right of the window, part of the 'Compose' toolbar\". cheaper to read than the raw attributes when deciding what an element is.".to_string(),
+            parameters: describe_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "listAllElements".to_string(),
+            description: "list interactable elements across the windows of all running apps (or the given ones) in one indexed list, each tagged with its app_name. the indices work with every by-index tool, which then act on the element's own app. slow and large - prefer listing a single app when you know which one owns the control.".to_string(),
+            parameters: list_all_elements_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getZoom".to_string(),
+            description: "read an app's current zoom level as a factor (1.0 = 100%) from its zoom control. browsers don't expose their level (zoom is null, but setZoom works); supported is false when the app has no zoom at all.".to_string(),
+            parameters: get_zoom_schema,
+        },
+        ToolFunctionDefinition {
+            name: "setZoom".to_string(),
+            description: "set an app's zoom level, through its zoom control where it has one or with cmd+/cmd- in browsers (rounded to the nearest browser zoom step). use it to fit content on screen before interacting. supported is false when the app has no zoom.".to_string(),
+            parameters: set_zoom_schema,
+        },
+        ToolFunctionDefinition {
+            name: "pressUntil".to_string(),
+            description: "press a key repeatedly until an element with the given text is selected (or focused), e.g. press down until the 'Downloads' row is selected. returns how many presses it took, the matched element and the updated element list; success is false when max_presses ran out first.".to_string(),
+            parameters: press_until_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getCacheStatus".to_string(),
+            description: "report which app(s) the element cache holds, how many elements, how old it is and whether it has expired, without re-listing anything. call this before a by-index action to decide whether to list elements again. also reports the menu bar item cache.".to_string(),
+            parameters: get_cache_status_schema,
+        },
+        ToolFunctionDefinition {
+            name: "launchViaSpotlight".to_string(),
+            description: "open spotlight with cmd+space, type a query and press return on the top result, then report which app ended up frontmost along with its elements. useful when an app can't be opened by name. requires spotlight to be enabled with its default cmd+space shortcut.".to_string(),
+            parameters: launch_via_spotlight_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getTableByIndex".to_string(),
+            description: "read a table, outline or list from the last listing as a 2d array of cell text, with the header row when there is one. outlines also return the nesting depth of each row. use this to read tabular data in one call instead of going through individual cells.".to_string(),
+            parameters: get_table_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "stepByIndex".to_string(),
+            description: "step a stepper (the up/down arrows next to a number field) or slider by index a number of times using the accessibility increment/decrement actions. returns the resulting value and the updated element list. fails if the element supports neither increment nor decrement.".to_string(),
+            parameters: step_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "runAppleScript".to_string(),
+            description: "run an arbitrary applescript and return its stdout, stderr and exit code. an escape hatch for app scripting dictionaries (music, mail, ...) the other tools don't cover; prefer the structured tools whenever they fit. only available when the server sets ENABLE_RAW_APPLESCRIPT, since it runs with the server's full automation permissions.".to_string(),
+            parameters: run_applescript_schema,
+        },
+        ToolFunctionDefinition {
+            name: "listNotifications".to_string(),
+            description: "list the notification banners and alerts currently on screen with their app, title, subtitle and body, e.g. to notice that a download finished or a message arrived. returns an empty list when none are showing; banners that already disappeared are not included.".to_string(),
+            parameters: list_notifications_schema,
+        },
+        ToolFunctionDefinition {
+            name: "tabToField".to_string(),
+            description: "press tab until the field with the given label has keyboard focus, then stop. returns how many tabs it took and the focused field. more robust than clicking for form filling since it doesn't depend on the layout; follow up with inputControl WriteText to type into the field.".to_string(),
+            parameters: tab_to_field_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getTextByIndex".to_string(),
+            description: "read all the text of an element and its children by index, e.g. a whole message bubble, card or article whose text is spread over many child elements. returns the distinct texts in tree order, one per line, plus the element's own current value. pass expected_text to check that a field now contains what was typed into it.".to_string(),
+            parameters: get_text_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "ocrScreen".to_string(),
+            description: "screenshot a window, region or the whole screen and recognize its text with on-device ocr. returns each line of text with its confidence and its bounds and center in screen points (click them with inputControl MouseMove + MouseClick), plus the image. a pure-vision fallback for apps whose ui accessibility can't see; needs screen recording permission.".to_string(),
+            parameters: ocr_screen_schema,
+        },
+        ToolFunctionDefinition {
+            name: "dismissDialog".to_string(),
+            description: "answer the sheet or dialog in front of an app by keyboard: mode 'default' presses return (the highlighted button), 'cancel' presses escape. brings the app forward first, then returns refreshed elements. a one-call alternative to listing the dialog and clicking its button by index.".to_string(),
+            parameters: dismiss_dialog_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getCaretByIndex".to_string(),
+            description: "read where the text caret is in a text field or text area by its index: the character offset of the insertion point, the length of any selected text, and the caret's screen bounds when the app reports them. caret is null for elements without a caret.".to_string(),
+            parameters: get_caret_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getAppMetrics".to_string(),
+            description: "measure an app's accessibility tree without listing it: total element count, maximum depth, window count and interactable count, from a quick counts-only pass. use it before listInteractableElementsByIndex on an unfamiliar app to decide whether to narrow the listing with region, locator or max_elements.".to_string(),
+            parameters: get_app_metrics_schema,
+        },
+        ToolFunctionDefinition {
+            name: "setFieldByIndex".to_string(),
+            description: "fill a form field by its index in one call: focus it, clear the existing content, type or paste the text, read the value back (retrying with a direct value set if it didn't take), and optionally confirm. returns the outcome of each step and the final value. prefer this over typeByIndex when the field may already hold text or must end up with exactly this value.".to_string(),
+            parameters: set_field_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "resetInputState".to_string(),
+            description: "reset keyboard and mouse to a known state: release every held modifier key and mouse button (e.g. after a failed drag or an unpaired key down), move the cursor to a home position, and optionally restore clipboard text. call between automations so one failure doesn't leak into the next.".to_string(),
+            parameters: reset_input_state_schema,
+        },
+        ToolFunctionDefinition {
+            name: "rightClickByIndex".to_string(),
+            description: "right-click an element by its index to open its context menu (falls back to the AXShowMenu action when a right click can't be sent). returns the refreshed element list, which includes the menu's items, so the next step can click one of them by index.".to_string(),
+            parameters: right_click_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "scrollByIndex".to_string(),
+            description: "scroll the content under an element by its index with the mouse wheel. returns the refreshed element list, so content scrolled into view can be used by index right away.".to_string(),
+            parameters: scroll_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "closeApplication".to_string(),
+            description: "quit an application, or kill it with force. a graceful quit may leave the app running behind a save dialog. also clears the app's cached element listings, so list again before using indices from it.".to_string(),
+            parameters: close_application_schema,
+        },
+        ToolFunctionDefinition {
+            name: "captureScreen".to_string(),
+            description: "take a screenshot of a display or of one app's front window and return it as a base64 image. use it to see rendered ui the accessibility tree doesn't describe; region gives the window's screen position for turning image coordinates into click points.".to_string(),
+            parameters: capture_screen_schema,
+        },
+        ToolFunctionDefinition {
+            name: "waitForElement".to_string(),
+            description: "wait until an element matching role and/or text appears in an app, re-listing it until then. returns the element's index and the listing it was found in, which becomes the cached listing for by-index tools. use after an action whose result loads slowly (a dialog, a search result, a new page) instead of listing and hoping the ui has settled.".to_string(),
+            parameters: wait_for_element_schema,
+        },
+        ToolFunctionDefinition {
+            name: "dragByIndex".to_string(),
+            description: "drag an element by its index and drop it on another element (target_index) or a screen point (x, y), pressing the mouse at the source's center, moving and releasing. use it to reorder list items, move files between folders or drag sliders. returns the updated element list; check it to confirm the item moved.".to_string(),
+            parameters: drag_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "hoverByIndex".to_string(),
+            description: "move the mouse onto a ui element by its index without clicking, wait dwell_ms, and return the updated element list. use it to reveal tooltips, hover menus, toolbars and disclosure controls that only appear on mouse-over, common in web apps.".to_string(),
+            parameters: hover_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "clearElementCache".to_string(),
+            description: "forget cached element listings so their indices can't be used anymore, for one app or all of them. call it when the ui changed in a way the server can't see (a long-running operation finished, the user touched the app) so the next by-index call fails loudly instead of clicking a stale index; list the app again afterwards. returns how many cached elements were dropped.".to_string(),
+            parameters: clear_element_cache_schema,
+        },
+        ToolFunctionDefinition {
+            name: "clickById".to_string(),
+            description: "click on a ui element by the element_id of its listing entry and return the updated element list. unlike an index, the id keeps naming the same element (same role, text and place in the window) when a refresh renumbers the listing; fails with 404 if that element is gone, so list again.".to_string(),
+            parameters: click_by_id_schema,
+        },
+        ToolFunctionDefinition {
+            name: "typeById".to_string(),
+            description: "type text into a ui element by the element_id of its listing entry and return the updated element list; typeByIndex, addressed by an id that survives refreshes. fails with 404 if the element is gone, so list again.".to_string(),
+            parameters: type_by_id_schema,
+        },
+        ToolFunctionDefinition {
+            name: "executeBatch".to_string(),
+            description: "run several actions (inputControl actions and ClickByIndex/TypeByIndex/PressKeyByIndex) in one request and list the app once at the end instead of after every step, e.g. click a field, type, press return. stops at the first failing step; failed_step and its error say which and why, and the returned elements show the ui after the steps that ran.".to_string(),
+            parameters: execute_batch_schema,
+        },
+        ToolFunctionDefinition {
+            name: "getElementByIndex".to_string(),
+            description: "return everything the accessibility api reports for one element from the last listing: role, value, label, description, every raw attribute (including the parent/children/window references listings leave out), bounds, enabled/focused/visible state and available actions. large; use it to debug why an action on an element isn't working, not for browsing.".to_string(),
+            parameters: get_element_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "focusByIndex".to_string(),
+            description: "give a ui element the keyboard focus through the accessibility api, without clicking it, then optionally type text and/or press a key combination, and return the updated element list. use it where a click would also act (buttons, links, checkboxes). fails without sending input if the element doesn't accept focus this way.".to_string(),
+            parameters: focus_by_index_schema,
+        },
+        ToolFunctionDefinition {
+            name: "listApplications".to_string(),
+            description: "list the running applications with their exact name, bundle id, pid and whether each is frontmost or hidden. use it to find the app_name or bundle_id to pass to openApplication or listInteractableElementsByIndex instead of guessing.".to_string(),
+            parameters: list_applications_schema,
+        },
+    ];
+    
+    tool_functions
+}
+
+// Handler for executeToolFunction method
+pub async fn handle_execute_tool_function(
+    state: Arc<AppState>,
+    id: Value,
+    params: Value,
+) -> JsonResponse<Value> {
+    // Parse the params
+    let execute_params: ExecuteToolFunctionParams = match serde_json::from_value(params) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("invalid params: {}", e);
+            return mcp_error_response(
+                id, 
+                -32602, 
+                format!("invalid params: {}", e), 
+                None
+            );
+        }
+    };
+    
+    info!("executing tool function: {} with args: {}", 
+        execute_params.function, execute_params.arguments);
+    
+    if !state.config.is_tool_enabled(&execute_params.function) {
+        error!("tool function is disabled: {}", execute_params.function);
+        return mcp_error_response(
+            id, 
+            METHOD_DISABLED_CODE, 
+            format!("METHOD_DISABLED: tool {} is disabled on this server", execute_params.function), 
+            Some(json!({"code": "METHOD_DISABLED"}))
+        );
+    }
+    
+    // Execute the appropriate function
+    match execute_params.function.as_str() {
+        "listInteractableElementsByIndex" => {
+            let request: ListInteractableElementsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match list_elements_and_attributes_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "elements": response.0.elements,
+                            "stats": response.0.stats,
+                            "cache_info": response.0.cache_info,
+                            "processing_time_seconds": response.0.processing_time_seconds,
+                            "index_stability": response.0.index_stability,
+                            "truncated": response.0.truncated,
+                            "total_available": response.0.total_available
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "clickByIndex" => {
+            let request: ClickByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match click_by_index_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "click": {
+                                "success": response.0.click.success,
+                                "message": response.0.click.message,
+                                "retried": response.0.click.retried
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "screenshot": response.0.screenshot,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "ui_changes": response.0.ui_changes,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "check if expected ui elements appeared",
+                                "verify if target element state changed",
+                                "look for new controls or content that should be available after this action"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "typeByIndex" => {
+            let request: TypeByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match type_by_index_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "type_action": {
+                                "success": response.0.type_action.success,
+                                "message": response.0.type_action.message,
+                                "confirmed": response.0.type_action.confirmed,
+                                "committed_value": response.0.type_action.committed_value,
+                                "value_matches": response.0.type_action.value_matches
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "screenshot": response.0.screenshot,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "check if text field contains the typed content",
+                                "verify if any new ui elements appeared in response to input",
+                                "look for validation messages or response content"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "pressKeyByIndex" => {
+            let request: PressKeyByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match press_key_by_index_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "press_key": {
+                                "success": response.0.press_key.success,
+                                "message": response.0.press_key.message
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "screenshot": response.0.screenshot,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "check if expected keyboard shortcut effects occurred",
+                                "verify if target element state changed as expected",
+                                "look for new interface elements that appeared after key press"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "openApplication" => {
+            let request: OpenApplicationRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match open_application_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "application": {
+                                "success": response.0.application.success,
+                                "message": response.0.application.message,
+                                "app_name": response.0.application.app_name,
+                                "bundle_id": response.0.application.bundle_id,
+                                "pid": response.0.application.pid
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "screenshot": response.0.screenshot,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "verify the application window is visible in elements",
+                                "check for typical controls and interface elements of this application",
+                                "confirm the application is in its expected initial state"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "openAndFind" => {
+            let request: OpenAndFindRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match open_and_find_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "application": {
+                                "success": response.0.application.success,
+                                "message": response.0.application.message,
+                                "app_name": response.0.application.app_name,
+                                "bundle_id": response.0.application.bundle_id,
+                                "pid": response.0.application.pid
+                            },
+                            "element_index": response.0.element_index,
+                            "element": response.0.element,
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "verify the returned element is the one you intended to interact with",
+                                "use element_index directly with clickByIndex or typeByIndex"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "openUrl" => {
+            let types_request: OpenUrlRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            // Convert from types::OpenUrlRequest to the handler's OpenUrlRequest
+            let request = crate::handlers::open_url::OpenUrlRequest {
+                url: types_request.url,
+                browser: types_request.browser,
+                options: types_request.options,
+            };
+            
+            match open_url_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "url": {
+                                "success": response.0.url.success,
+                                "message": response.0.url.message
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "screenshot": response.0.screenshot,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "check for browser controls and expected page content",
+                                "verify page title or key elements from the requested site",
+                                "look for signs the page has fully loaded"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "inputControl" => {
+            let request: InputControlRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match input_control_handler(State(state.clone()), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "input": {
+                                "success": response.0.input.success
+                            },
+                            "elements": response.0.elements,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
+                            "evaluation_hints": [
+                                "check if the ui responded appropriately to the input action",
+                                "verify cursor position changed for mouse moves",
+                                "look for content changes after text input or clicks"
+                            ]
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getProgress" => {
+            let request: GetProgressRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_progress_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "screenshotByIndex" => {
+            let request: ScreenshotByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match screenshot_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "clickByText" => {
+            let request: ClickByTextRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match click_by_text_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "element_index": response.0.element_index,
+                            "element": response.0.element,
+                            "click": {
+                                "success": response.0.click.success,
+                                "message": response.0.click.message,
+                                "retried": response.0.click.retried
+                            },
+                            "elements": response.0.elements,
+                            "focus_stolen": response.0.focus.focus_stolen,
+                            "previous_frontmost_app": response.0.focus.previous_frontmost_app,
+                            "new_frontmost_app": response.0.focus.new_frontmost_app,
+                            "ui_state_changed": response.0.ui_state_changed,
+                            "action_timestamp": chrono::Utc::now().to_rfc3339()
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getClickPointByIndex" => {
+            let request: GetClickPointByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_click_point_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "listMenuBarItems" => {
+            let request: ListMenuBarItemsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match list_menu_bar_items_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "clickMenuBarItem" => {
+            let request: ClickMenuBarItemRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match click_menu_bar_item_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "dragFileToIndex" => {
+            let request: DragFileToIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match drag_file_to_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "drag": response.0.drag,
+                            "elements": response.0.elements,
+                            "ui_state_changed": response.0.ui_state_changed
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "describeByIndex" => {
+            let request: DescribeByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match describe_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "element_index": response.0.element_index,
+                            "description": response.0.description
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "listAllElements" => {
+            let request: ListAllElementsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match list_all_elements_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "apps": response.0.apps,
+                            "elements": response.0.elements,
+                            "cache_info": response.0.cache_info,
+                            "truncated": response.0.truncated
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getZoom" => {
+            let request: GetZoomRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_zoom_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "setZoom" => {
+            let request: SetZoomRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match set_zoom_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "pressUntil" => {
+            let request: PressUntilRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match press_until_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "success": response.0.success,
+                            "presses": response.0.presses,
+                            "message": response.0.message,
+                            "matched": response.0.matched,
+                            "elements": response.0.elements
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getCacheStatus" => {
+            let response = cache_status_handler(State(state)).await;
+            JsonResponse(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": response.0
+            }))
+        },
+        "launchViaSpotlight" => {
+            let request: LaunchViaSpotlightRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match launch_via_spotlight_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "success": response.0.success,
+                            "message": response.0.message,
+                            "confirmed": response.0.confirmed,
+                            "frontmost_app": response.0.frontmost_app,
+                            "elements": response.0.elements
+                        }
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getTableByIndex" => {
+            let request: GetTableByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_table_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "stepByIndex" => {
+            let request: StepByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match step_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "runAppleScript" => {
+            let request: RunAppleScriptRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match run_applescript_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "listNotifications" => {
+            let request: ListNotificationsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match list_notifications_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "tabToField" => {
+            let request: TabToFieldRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match tab_to_field_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getTextByIndex" => {
+            let request: GetTextByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_text_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "ocrScreen" => {
+            let request: OcrScreenRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match ocr_screen_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "dismissDialog" => {
+            let request: DismissDialogRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match dismiss_dialog_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getCaretByIndex" => {
+            let request: GetCaretByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_caret_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getAppMetrics" => {
+            let request: GetAppMetricsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match get_app_metrics_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "setFieldByIndex" => {
+            let request: SetFieldByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match set_field_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "resetInputState" => {
+            let request: ResetInputStateRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
+            };
+            
+            match reset_input_state_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "rightClickByIndex" => {
+            let request: RightClickByIndexRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -226,31 +2151,48 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match list_elements_and_attributes_handler(State(state.clone()), Json(request)).await {
+            match right_click_by_index_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "elements": response.0.elements,
-                            "stats": response.0.stats,
-                            "cache_info": response.0.cache_info,
-                            "processing_time_seconds": response.0.processing_time_seconds
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "scrollByIndex" => {
+            let request: ScrollByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
+                }
+            };
+            
+            match scroll_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
-        "clickByIndex" => {
-            let request: ClickByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+        "closeApplication" => {
+            let request: CloseApplicationRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -263,39 +2205,48 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match click_by_index_handler(State(state.clone()), Json(request)).await {
+            match close_application_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "click": {
-                                "success": response.0.click.success,
-                                "message": response.0.click.message
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "check if expected ui elements appeared",
-                                "verify if target element state changed",
-                                "look for new controls or content that should be available after this action"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "captureScreen" => {
+            let request: CaptureScreenRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
+                }
+            };
+            
+            match capture_screen_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
-        "typeByIndex" => {
-            let request: TypeByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+        "waitForElement" => {
+            let request: WaitForElementRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -308,39 +2259,48 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match type_by_index_handler(State(state.clone()), Json(request)).await {
+            match wait_for_element_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "type_action": {
-                                "success": response.0.type_action.success,
-                                "message": response.0.type_action.message
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "check if text field contains the typed content",
-                                "verify if any new ui elements appeared in response to input",
-                                "look for validation messages or response content"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "dragByIndex" => {
+            let request: DragByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
+                }
+            };
+            
+            match drag_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
-        "pressKeyByIndex" => {
-            let request: PressKeyByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+        "hoverByIndex" => {
+            let request: HoverByIndexRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -353,39 +2313,42 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match press_key_by_index_handler(State(state.clone()), Json(request)).await {
+            match hover_by_index_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "press_key": {
-                                "success": response.0.press_key.success,
-                                "message": response.0.press_key.message
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "check if expected keyboard shortcut effects occurred",
-                                "verify if target element state changed as expected",
-                                "look for new interface elements that appeared after key press"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "clearElementCache" => {
+            let request: ClearElementCacheRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
                 }
-            }
+            };
+            
+            let response = clear_element_cache_handler(State(state), Json(request)).await;
+            JsonResponse(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": response.0
+            }))
         },
-        "openApplication" => {
-            let request: OpenApplicationRequest = match serde_json::from_value(execute_params.arguments) {
+        "clickById" => {
+            let request: ClickByIdRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -398,39 +2361,21 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match open_application_handler(State(state), Json(request)).await {
+            match click_by_id_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "application": {
-                                "success": response.0.application.success,
-                                "message": response.0.application.message
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "verify the application window is visible in elements",
-                                "check for typical controls and interface elements of this application",
-                                "confirm the application is in its expected initial state"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
-                        id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
-                        None
-                    )
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
-        "openUrl" => {
-            let types_request: OpenUrlRequest = match serde_json::from_value(execute_params.arguments) {
+        "typeById" => {
+            let request: TypeByIdRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -443,45 +2388,75 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            // Convert from types::OpenUrlRequest to the handler's OpenUrlRequest
-            let request = crate::handlers::open_url::OpenUrlRequest {
-                url: types_request.url,
-                browser: types_request.browser,
+            match type_by_id_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "executeBatch" => {
+            let request: ExecuteBatchRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
+                        id, 
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
+                        None
+                    );
+                }
             };
             
-            match open_url_handler(State(state.clone()), Json(request)).await {
+            match execute_batch_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "url": {
-                                "success": response.0.url.success,
-                                "message": response.0.url.message
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "check for browser controls and expected page content",
-                                "verify page title or key elements from the requested site",
-                                "look for signs the page has fully loaded"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "getElementByIndex" => {
+            let request: GetElementByIndexRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
+                }
+            };
+            
+            match get_element_by_index_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
-        "inputControl" => {
-            let request: InputControlRequest = match serde_json::from_value(execute_params.arguments) {
+        "focusByIndex" => {
+            let request: FocusByIndexRequest = match serde_json::from_value(execute_params.arguments) {
                 Ok(r) => r,
                 Err(e) => {
                     error!("invalid arguments: {}", e);
@@ -494,33 +2469,43 @@ pub async fn handle_execute_tool_function(
                 }
             };
             
-            match input_control_handler(State(state.clone()), Json(request)).await {
+            match focus_by_index_handler(State(state), Json(request)).await {
                 Ok(response) => {
                     JsonResponse(json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": {
-                            "input": {
-                                "success": response.0.input.success
-                            },
-                            "elements": response.0.elements,
-                            "ui_state_changed": true,
-                            "action_timestamp": chrono::Utc::now().to_rfc3339(),
-                            "evaluation_hints": [
-                                "check if the ui responded appropriately to the input action",
-                                "verify cursor position changed for mouse moves",
-                                "look for content changes after text input or clicks"
-                            ]
-                        }
+                        "result": response.0
                     }))
                 },
                 Err((status, error_json)) => {
-                    mcp_error_response(
+                    handler_error_response(id, status, error_json.0)
+                }
+            }
+        },
+        "listApplications" => {
+            let request: ListApplicationsRequest = match serde_json::from_value(execute_params.arguments) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("invalid arguments: {}", e);
+                    return mcp_error_response(
                         id, 
-                        status.as_u16() as i32, 
-                        error_json.0["error"].as_str().unwrap_or("unknown error").to_string(),
+                        -32602, 
+                        format!("invalid arguments: {}", e), 
                         None
-                    )
+                    );
+                }
+            };
+            
+            match list_applications_handler(State(state), Json(request)).await {
+                Ok(response) => {
+                    JsonResponse(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": response.0
+                    }))
+                },
+                Err((status, error_json)) => {
+                    handler_error_response(id, status, error_json.0)
                 }
             }
         },
@@ -533,6 +2518,37 @@ pub async fn handle_execute_tool_function(
     }
 }
 
+// JSON-RPC code and machine-readable kind for a handler's HTTP status. Codes stay in the
+// -32000..-32099 range the spec reserves for server errors; -32003 is METHOD_DISABLED_CODE
+fn handler_error_code(status: StatusCode) -> (i32, &'static str) {
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => (-32001, "bad_request"),
+        StatusCode::NOT_FOUND => (-32002, "not_found"),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => (-32004, "permission_denied"),
+        StatusCode::CONFLICT => (-32005, "conflict"),
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => (-32006, "timeout"),
+        StatusCode::SERVICE_UNAVAILABLE => (-32007, "unavailable"),
+        _ => (-32000, "internal"),
+    }
+}
+
+// Error response for a failed tool handler: its message, the code for its status, and in
+// data the original HTTP status, the kind, and any other fields of the handler's error body
+// (e.g. code, guidance)
+fn handler_error_response(id: Value, status: StatusCode, body: Value) -> JsonResponse<Value> {
+    let (code, kind) = handler_error_code(status);
+    let message = body["error"].as_str().unwrap_or("unknown error").to_string();
+
+    let mut data = serde_json::Map::new();
+    if let Value::Object(fields) = body {
+        data.extend(fields.into_iter().filter(|(key, _)| key != "error"));
+    }
+    data.insert("http_status".to_string(), json!(status.as_u16()));
+    data.insert("kind".to_string(), json!(kind));
+
+    mcp_error_response(id, code, message, Some(Value::Object(data)))
+}
+
 // Helper function for MCP error responses
 pub fn mcp_error_response(id: Value, code: i32, message: String, data: Option<Value>) -> JsonResponse<Value> {
     JsonResponse(json!({
@@ -545,3 +2561,29 @@ pub fn mcp_error_response(id: Value, code: i32, message: String, data: Option<Va
         }
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handler_errors_map_to_server_error_codes() {
+        let response = handler_error_response(
+            json!(7),
+            StatusCode::NOT_FOUND,
+            json!({"error": "no element at index 3", "guidance": "list elements again"}),
+        );
+        let error = &response.0["error"];
+        assert_eq!(error["code"], -32002);
+        assert_eq!(error["message"], "no element at index 3");
+        assert_eq!(error["data"]["http_status"], 404);
+        assert_eq!(error["data"]["kind"], "not_found");
+        assert_eq!(error["data"]["guidance"], "list elements again");
+        assert!(error["data"].get("error").is_none());
+
+        assert_eq!(handler_error_code(StatusCode::BAD_REQUEST), (-32001, "bad_request"));
+        assert_eq!(handler_error_code(StatusCode::FORBIDDEN), (-32004, "permission_denied"));
+        assert_eq!(handler_error_code(StatusCode::INTERNAL_SERVER_ERROR), (-32000, "internal"));
+        assert_eq!(handler_error_code(StatusCode::IM_A_TEAPOT), (-32000, "internal"));
+    }
+}
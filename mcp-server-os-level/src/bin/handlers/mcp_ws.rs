@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde_json::Value;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::types::{AppState, MCPRequest};
+use crate::handlers::mcp::{dispatch_mcp_request, mcp_error_response};
+
+// MCP over a WebSocket: the same JSON-RPC as POST /mcp, one message per text frame, answered
+// in order on the same socket, plus whatever is published on AppState::mcp_notifications
+// (notifications/ui_changed whenever an action changes an app's listing, see detect_ui_change)
+pub async fn mcp_ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    ws.on_upgrade(move |socket| serve_mcp_socket(socket, state))
+}
+
+async fn serve_mcp_socket(mut socket: WebSocket, state: Arc<AppState>) {
+    info!("mcp websocket client connected");
+    let mut notifications = state.mcp_notifications.subscribe();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    // Pings are answered by axum itself
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        warn!("mcp websocket receive failed: {}", e);
+                        break;
+                    }
+                };
+
+                if let Some(reply) = handle_frame(&state, &text).await {
+                    if socket.send(Message::Text(reply.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            notification = notifications.recv() => {
+                match notification {
+                    Ok(notification) => {
+                        if socket.send(Message::Text(notification.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("mcp websocket client fell behind, dropped {} notifications", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    info!("mcp websocket client disconnected");
+}
+
+// Answer one frame; client notifications (no id) get no reply, as JSON-RPC requires
+async fn handle_frame(state: &Arc<AppState>, text: &str) -> Option<Value> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("unparseable mcp websocket frame: {}", e);
+            return Some(mcp_error_response(Value::Null, -32700, format!("parse error: {}", e), None).0);
+        }
+    };
+
+    if value.get("id").is_none() {
+        debug!("ignoring mcp notification: {}", value);
+        return None;
+    }
+
+    match serde_json::from_value::<MCPRequest>(value.clone()) {
+        Ok(request) => {
+            info!("received mcp websocket request: {:?}", request);
+            Some(dispatch_mcp_request(state.clone(), request).await.0)
+        }
+        Err(e) => {
+            let id = value.get("id").cloned().unwrap_or(Value::Null);
+            Some(mcp_error_response(id, -32600, format!("invalid request: {}", e), None).0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::utils::test_state;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn answers_unparseable_frames_with_a_parse_error() {
+        let state = Arc::new(test_state());
+        let reply = handle_frame(&state, "{not json").await.unwrap();
+        assert_eq!(reply["error"]["code"], -32700);
+        assert_eq!(reply["id"], Value::Null);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn answers_malformed_requests_with_invalid_request() {
+        let state = Arc::new(test_state());
+        let reply = handle_frame(&state, r#"{"jsonrpc": "2.0", "id": 3}"#).await.unwrap();
+        assert_eq!(reply["error"]["code"], -32600);
+        assert_eq!(reply["id"], 3);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn does_not_answer_client_notifications() {
+        let state = Arc::new(test_state());
+        let reply = handle_frame(&state, r#"{"jsonrpc": "2.0", "method": "notifications/initialized"}"#).await;
+        assert!(reply.is_none());
+    }
+}
@@ -1,11 +1,54 @@
 // Export all handlers
 pub mod mcp;
+pub mod mcp_ws;
 pub mod click_by_index;
 pub mod type_by_index;
 pub mod press_key_by_index;
 pub mod open_application;
+pub mod open_and_find;
 pub mod open_url;
 pub mod input_control;
+pub mod get_progress;
+pub mod info;
+pub mod screenshot_by_index;
+pub mod click_by_text;
+pub mod get_click_point_by_index;
+pub mod list_menu_bar_items;
+pub mod click_menu_bar_item;
+pub mod drag_file_to_index;
+pub mod describe_by_index;
+pub mod list_all_elements;
+pub mod zoom;
+pub mod press_until;
+pub mod cache_status;
+pub mod launch_via_spotlight;
+pub mod get_table_by_index;
+pub mod step_by_index;
+pub mod run_applescript;
+pub mod list_notifications;
+pub mod tab_to_field;
+pub mod get_text_by_index;
+pub mod ocr_screen;
+pub mod dismiss_dialog;
+pub mod get_caret_by_index;
+pub mod get_app_metrics;
+pub mod set_field_by_index;
+pub mod reset_input_state;
+pub mod right_click_by_index;
+pub mod scroll_by_index;
+pub mod close_application;
+pub mod capture_screen;
+pub mod wait_for_element;
+pub mod drag_by_index;
+pub mod hover_by_index;
+pub mod clear_element_cache;
+pub mod click_by_id;
+pub mod type_by_id;
+pub mod health;
+pub mod execute_batch;
+pub mod get_element_by_index;
+pub mod focus_by_index;
+pub mod list_applications;
 pub mod utils;
 pub mod list_elements_and_attributes;
 
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{ActionScreenshot, AppState, ElementBounds, OcrScreenRequest, OcrScreenResponse, OcrText, ScreenPoint, ScreenshotOptions};
+use crate::handlers::utils::{front_window_bounds, run_jxa, screencapture, shrink_screenshot};
+
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.3;
+const DEFAULT_IMAGE_MAX_WIDTH: u32 = 1280;
+
+// Runs Vision's text recognizer on the image at argv[0]. Boxes come back normalized to
+// the image (0-1, origin bottom-left), along with the main display's size in points for
+// full-screen captures.
+const OCR_SCRIPT: &str = r#"
+ObjC.import('Vision');
+ObjC.import('AppKit');
+function run(argv) {
+    const url = $.NSURL.fileURLWithPath(argv[0]);
+    const handler = $.VNImageRequestHandler.alloc.initWithURLOptions(url, $({}));
+    const request = $.VNRecognizeTextRequest.alloc.init;
+    request.recognitionLevel = $.VNRequestTextRecognitionLevelAccurate;
+    request.usesLanguageCorrection = true;
+    if (!handler.performRequestsError($([request]), null)) {
+        throw new Error('text recognition failed');
+    }
+    const observations = request.results;
+    const texts = [];
+    for (let i = 0; i < observations.count; i++) {
+        const observation = observations.objectAtIndex(i);
+        const candidate = observation.topCandidates(1).objectAtIndex(0);
+        const box = observation.boundingBox;
+        texts.push({
+            text: candidate.string.js,
+            confidence: candidate.confidence,
+            x: box.origin.x, y: box.origin.y, width: box.size.width, height: box.size.height
+        });
+    }
+    const screen = $.NSScreen.mainScreen.frame.size;
+    return JSON.stringify({texts: texts, screen_width: screen.width, screen_height: screen.height});
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+struct OcrOutput {
+    texts: Vec<RecognizedText>,
+    screen_width: f64,
+    screen_height: f64,
+}
+
+// One observation, with its box normalized to the image, origin bottom-left
+#[derive(Debug, Deserialize)]
+struct RecognizedText {
+    text: String,
+    confidence: f32,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+// Screenshot plus text recognition, for apps whose UI accessibility can't see.
+// The returned boxes are in screen points, so they can be clicked directly.
+pub async fn ocr_screen_handler(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<OcrScreenRequest>,
+) -> Result<JsonResponse<OcrScreenResponse>, (StatusCode, JsonResponse<Value>)> {
+    let capture_region = match (&request.region, &request.app_name, &request.bundle_id) {
+        (Some(region), _, _) => Some(*region),
+        (None, None, None) => None,
+        (None, app_name, bundle_id) => Some(front_window_bounds(app_name.as_deref().unwrap_or_default(), bundle_id.as_deref())?),
+    };
+    info!("running ocr on {}", capture_region.map_or("the main display".to_string(), |r| format!("{:?}", r)));
+
+    let path = std::env::temp_dir().join(format!("ocr-{}.png", uuid::Uuid::new_v4()));
+    let path_str = path.to_string_lossy().to_string();
+    let result = capture_and_recognize(&path_str, capture_region).await;
+    let image = std::fs::read(&path).ok();
+    let _ = std::fs::remove_file(&path);
+    let output = result?;
+
+    let region = capture_region.unwrap_or(ElementBounds { x: 0.0, y: 0.0, width: output.screen_width, height: output.screen_height });
+    let min_confidence = request.min_confidence.unwrap_or(DEFAULT_MIN_CONFIDENCE);
+    let mut texts: Vec<OcrText> = output
+        .texts
+        .into_iter()
+        .filter(|recognized| recognized.confidence >= min_confidence && !recognized.text.trim().is_empty())
+        .map(|recognized| {
+            let bounds = to_screen_bounds(&recognized, &region);
+            OcrText {
+                text: recognized.text,
+                confidence: recognized.confidence,
+                center: ScreenPoint { x: bounds.x + bounds.width / 2.0, y: bounds.y + bounds.height / 2.0 },
+                bounds,
+            }
+        })
+        .collect();
+    texts.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+    info!("recognized {} lines of text", texts.len());
+
+    let image = match (request.include_image.unwrap_or(true), image) {
+        (true, Some(png)) => {
+            let options = ScreenshotOptions {
+                include_screenshot: true,
+                screenshot_max_width: Some(request.image_max_width.unwrap_or(DEFAULT_IMAGE_MAX_WIDTH)),
+                screenshot_quality: None,
+            };
+            match shrink_screenshot(png, &options).await {
+                Ok((image, format)) => Some(ActionScreenshot { format: format.to_string(), image_base64: STANDARD.encode(image) }),
+                Err(e) => {
+                    error!("failed to downscale ocr capture: {}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    Ok(JsonResponse(OcrScreenResponse { region, texts, image }))
+}
+
+async fn capture_and_recognize(path: &str, region: Option<ElementBounds>) -> Result<OcrOutput, (StatusCode, JsonResponse<Value>)> {
+    screencapture(path, region, None).await?;
+
+    let raw = match run_jxa(OCR_SCRIPT, &[path]).await {
+        Ok(raw) => raw,
+        Err(e) => {
+            error!("text recognition failed: {}", e);
+            return Err(e.to_response());
+        }
+    };
+    debug!("ocr output: {} bytes", raw.len());
+    serde_json::from_str(&raw).map_err(|e| {
+        error!("unexpected ocr output: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("unexpected ocr output: {}", e)})),
+        )
+    })
+}
+
+// Map a normalized, bottom-left-origin box onto the captured area in screen points
+fn to_screen_bounds(recognized: &RecognizedText, region: &ElementBounds) -> ElementBounds {
+    ElementBounds {
+        x: region.x + recognized.x * region.width,
+        y: region.y + (1.0 - recognized.y - recognized.height) * region.height,
+        width: recognized.width * region.width,
+        height: recognized.height * region.height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_normalized_boxes_to_screen_points() {
+        let region = ElementBounds { x: 100.0, y: 50.0, width: 800.0, height: 600.0 };
+        let recognized = RecognizedText { text: "Save".to_string(), confidence: 0.9, x: 0.25, y: 0.5, width: 0.1, height: 0.05 };
+        let bounds = to_screen_bounds(&recognized, &region);
+        assert_eq!(bounds.x, 300.0);
+        // The box's top edge sits 55% of the way down, since Vision measures from the bottom
+        assert_eq!(bounds.y, 50.0 + 0.45 * 600.0);
+        assert_eq!(bounds.width, 80.0);
+        assert_eq!(bounds.height, 30.0);
+    }
+}
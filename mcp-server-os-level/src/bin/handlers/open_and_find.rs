@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::types::{ActionOptions, AppState, OpenAndFindRequest, OpenAndFindResponse, OpenApplicationRequest};
+use crate::handlers::open_application::open_application_handler;
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{element_matches, FocusGuard};
+
+// Default time to wait for the element to show up after launch
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const POLL_INTERVAL_MS: u64 = 500;
+
+pub async fn open_and_find_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<OpenAndFindRequest>,
+) -> Result<JsonResponse<OpenAndFindResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.restore_focus).await;
+
+    if request.role.is_none() && request.text.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either role or text must be provided to find an element"})),
+        ));
+    }
+
+    let start = Instant::now();
+    let timeout = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+
+    // Open (or activate) the application - this already waits for the app and lists its elements
+    let opened = open_application_handler(
+        State(state.clone()),
+        Json(OpenApplicationRequest {
+            app_name: request.app_name.clone(),
+            bundle_id: request.bundle_id.clone(),
+            retries: None,
+            retry_delay_ms: None,
+            // Restoring is handled here, after polling, rather than inside the open
+            options: ActionOptions::default(),
+        }),
+    )
+    .await?
+    .0;
+
+    let application = opened.application;
+    // Relist under the bundle id when known, the display name can differ from what was asked for
+    let app_name = application
+        .bundle_id
+        .clone()
+        .or_else(|| application.app_name.clone())
+        .unwrap_or_else(|| request.app_name.clone());
+    let mut elements = opened.elements;
+    let ui_state_changed = opened.ui_state_changed;
+
+    // Keep re-listing until the element appears, the app may still be populating its window
+    loop {
+        let found = elements.as_ref().and_then(|list| {
+            list.elements
+                .iter()
+                .find(|e| element_matches(e, request.role.as_deref(), request.text.as_deref()))
+                .cloned()
+        });
+
+        if let (Some(element), Some(list)) = (found, elements.take()) {
+            let element_index = element["index"].as_u64().unwrap_or_default() as usize;
+            info!("found matching element at index {} in {}", element_index, app_name);
+            return Ok(JsonResponse(OpenAndFindResponse {
+                application,
+                element_index,
+                element,
+                elements: list,
+                ui_state_changed,
+                focus: focus_guard.report().await,
+            }));
+        }
+
+        if start.elapsed() >= timeout {
+            break;
+        }
+
+        info!("no matching element in {} yet, listing again", app_name);
+        elements = refresh_elements_and_attributes_after_action(state.clone(), app_name.clone(), POLL_INTERVAL_MS).await;
+    }
+
+    warn!("no element matching role {:?} / text {:?} found in {}", request.role, request.text, app_name);
+    Err((
+        StatusCode::NOT_FOUND,
+        JsonResponse(json!({
+            "error": format!(
+                "no element matching role {:?} and text {:?} found in {}",
+                request.role, request.text, app_name
+            )
+        })),
+    ))
+}
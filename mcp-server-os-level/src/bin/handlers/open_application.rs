@@ -6,22 +6,33 @@ use axum::{
 };
 use serde::Serialize;
 use serde_json::json;
-use computer_use_ai_sdk::Desktop;
+use computer_use_ai_sdk::{AutomationError, Desktop};
 
-use crate::types::{AppState, OpenApplicationRequest, OpenApplicationResponse, ListElementsAndAttributesResponse};
-use crate::refresh_elements_and_attributes_after_action;
+use crate::types::{AppState, OpenApplicationRequest, OpenApplicationResponse, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot};
+use crate::{refresh_after_action, refresh_elements_and_attributes_after_action};
+use crate::handlers::utils::{capture_action_screenshot, detect_ui_change, last_ui_fingerprint, requested_bundle_id, FocusGuard};
 
 // Response type that combines both results
 #[derive(Serialize)]
 pub struct OpenApplicationWithElementsResponse {
     pub application: OpenApplicationResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the action (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
 }
 
 pub async fn open_application_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<OpenApplicationRequest>,
 ) -> Result<JsonResponse<OpenApplicationWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
     // Create Desktop automation instance
     let desktop = match Desktop::new(false, true) {
         Ok(desktop) => desktop,
@@ -33,33 +44,90 @@ pub async fn open_application_handler(
         }
     };
 
-    // Open the application
-    match desktop.open_application(&request.app_name) {
-        Ok(_) => {
+    if request.bundle_id.is_none() && request.app_name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either app_name or bundle_id must be provided"})),
+        ));
+    }
+
+    // Open the application - bundle ids are unambiguous, so prefer them when given,
+    // including as app_name (e.g. "com.apple.Safari")
+    let target_bundle_id = requested_bundle_id(&request.app_name, request.bundle_id.as_deref()).map(str::to_string);
+    // Opening waits for the app to come up, so keep it off the async workers
+    let (app_name, bundle_id) = (request.app_name.clone(), target_bundle_id.clone());
+    let open_result = tokio::task::spawn_blocking(move || {
+        let desktop = Desktop::new(false, true)?;
+        match &bundle_id {
+            Some(bundle_id) => desktop.open_application_by_bundle_id(bundle_id),
+            None => desktop.open_application(&app_name),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| Err(AutomationError::Internal(e.to_string())));
+
+    match open_result {
+        Ok(app) => {
+            // Use the name the system reports so the refresh targets the right app
+            let resolved_name = app
+                .attributes()
+                .label
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| request.app_name.clone());
+            let pid = app.process_id().ok();
+            let bundle_id = target_bundle_id
+                .clone()
+                .or_else(|| pid.and_then(|pid| desktop.bundle_id(pid).ok().flatten()));
+            // Listings are cached under the bundle id when there is one
+            let app_key = bundle_id.clone().unwrap_or_else(|| resolved_name.clone());
+
+            // Compare against the last listing of the app, if it was listed before opening
+            let fingerprint_before = last_ui_fingerprint(&state, &app_key).await;
+
             // Application opened successfully
             let app_response = OpenApplicationResponse {
                 success: true,
-                message: format!("successfully opened application: {}", request.app_name),
+                message: match &target_bundle_id {
+                    Some(bundle_id) => format!("successfully opened application: {} ({})", resolved_name, bundle_id),
+                    None => format!("successfully opened application: {}", resolved_name),
+                },
+                app_name: Some(resolved_name.clone()),
+                pid,
+                bundle_id,
             };
             
-            // Get refreshed elements using the helper function - use a longer delay for app startup
-            let mut elements_response = refresh_elements_and_attributes_after_action(state.clone(), request.app_name.clone(), 1000).await;
+            // Get refreshed elements using the helper function; apps still starting up are retried below
+            let wants_elements = request.options.refresh_elements != Some(false);
+            let mut elements_response = refresh_after_action(&state, &app_key, &request.options).await;
             
-            // If elements retrieval failed, wait 500ms and retry once
-            if elements_response.is_none() {
-                log::info!("elements retrieval failed for {}, retrying after 500ms", request.app_name);
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                elements_response = refresh_elements_and_attributes_after_action(state, request.app_name.clone(), 500).await;
-                
-                if elements_response.is_none() {
-                    log::warn!("elements retrieval failed for {} even after retry", request.app_name);
+            // Slow apps populate their windows well after launch, so back off and retry
+            let retry_delays = if wants_elements {
+                state.config.open_app_retry_delays(request.retries, request.retry_delay_ms)
+            } else {
+                Vec::new()
+            };
+            let retries = retry_delays.len();
+            for (attempt, delay) in retry_delays.into_iter().enumerate() {
+                if elements_response.is_some() {
+                    break;
                 }
+                log::info!("elements retrieval failed for {}, retry {}/{} after {}ms",
+                           resolved_name, attempt + 1, retries, delay.as_millis());
+                elements_response = refresh_elements_and_attributes_after_action(state.clone(), app_key.clone(), delay.as_millis() as u64).await;
             }
+            if wants_elements && elements_response.is_none() {
+                log::warn!("elements retrieval failed for {} after {} retries", resolved_name, retries);
+            }
+            
+            let ui_state_changed = detect_ui_change(&state, &app_key, fingerprint_before, elements_response.is_some()).await;
             
             // Return combined response
             Ok(JsonResponse(OpenApplicationWithElementsResponse {
                 application: app_response,
                 elements: elements_response,
+                ui_state_changed,
+                focus: focus_guard.report().await,
+                screenshot: capture_action_screenshot(&app_key, &request.options.screenshot).await,
             }))
         },
         Err(err) => Err((
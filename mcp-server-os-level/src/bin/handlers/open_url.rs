@@ -6,17 +6,24 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use computer_use_ai_sdk::Desktop;
 
-use crate::types::AppState;
-use crate::handlers::utils::refresh_elements_and_attributes_after_action;
+use crate::types::{AppState, FocusChange, ActionOptions, ActionScreenshot};
+use crate::handlers::utils::{capture_action_screenshot, detect_ui_change, refresh_after_action, FocusGuard};
 use crate::types::ListElementsAndAttributesResponse;
 
+// Time for the browser to launch or come to the front before looking it up; the wait
+// before listing it is the separate, configurable refresh delay
+const BROWSER_START_WAIT: tokio::time::Duration = tokio::time::Duration::from_millis(800);
+
 #[derive(Deserialize, Clone)]
 pub struct OpenUrlRequest {
     pub url: String,
     pub browser: Option<String>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
 }
 
 #[derive(Serialize)]
@@ -30,12 +37,22 @@ pub struct OpenUrlResponse {
 pub struct OpenUrlWithElementsResponse {
     pub url: OpenUrlResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the browser's refreshed listing differs from its last one (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
 }
 
 pub async fn open_url_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<OpenUrlRequest>,
 ) -> Result<JsonResponse<OpenUrlWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
     info!("handling request to open url: {}", request.url);
     
     // Create Desktop automation instance
@@ -50,22 +67,33 @@ pub async fn open_url_handler(
         }
     };
 
-    // Open the URL
-    let browser_ref = request.browser.as_deref();
+    // The browser isn't known until after opening, so remember every app's last listing
+    let listings_before = state.ui_fingerprint.lock().await.clone();
+
+    // Open the URL, in the configured default browser unless the request names one
+    let mut browser = request.browser.clone().or_else(|| state.config.default_browser.clone());
     
-    if let Some(browser) = browser_ref {
+    if let Some(browser) = &browser {
         debug!("opening url {} in specified browser: {}", request.url, browser);
     } else {
         debug!("opening url {} in system default browser", request.url);
     }
     
-    match desktop.open_url(&request.url, browser_ref) {
+    let mut opened = desktop.open_url(&request.url, browser.as_deref());
+    if opened.is_err() && request.browser.is_none() {
+        if let Some(default_browser) = browser.take() {
+            warn!("could not open url in DEFAULT_BROWSER {} (is it installed?), using the system default", default_browser);
+            opened = desktop.open_url(&request.url, None);
+        }
+    }
+    
+    match opened {
         Ok(_) => {
             // Wait for browser to start/activate
-            tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
+            tokio::time::sleep(BROWSER_START_WAIT).await;
             
             // Determine which browser to use for refreshing elements
-            let browser_for_refresh: Option<String> = if let Some(browser) = &request.browser {
+            let browser_for_refresh: Option<String> = if let Some(browser) = &browser {
                 // If user specified a browser, use that
                 info!("using specified browser for refresh: {}", browser);
                 
@@ -122,17 +150,38 @@ pub async fn open_url_handler(
             };
             
             // Only attempt to refresh elements if we know which browser to target
-            let elements_response = if let Some(browser) = browser_for_refresh {
-                refresh_elements_and_attributes_after_action(state, browser, 2000).await
+            let elements_response = if let Some(browser) = browser_for_refresh.clone() {
+                refresh_after_action(&state, &browser, &request.options).await
             } else {
                 // If we don't know which browser was used, don't try to refresh elements
                 None
             };
             
+            let ui_state_changed = match &browser_for_refresh {
+                Some(browser) => {
+                    // Listings are keyed by bundle id, which the refreshed listing reports
+                    let browser_key = elements_response
+                        .as_ref()
+                        .and_then(|elements| elements.bundle_id.clone())
+                        .unwrap_or_else(|| browser.clone());
+                    let fingerprint_before = listings_before.get(&browser_key).copied();
+                    detect_ui_change(&state, &browser_key, fingerprint_before, elements_response.is_some()).await
+                }
+                None => None,
+            };
+            
+            let screenshot = match browser_for_refresh.as_deref() {
+                Some(browser) => capture_action_screenshot(browser, &request.options.screenshot).await,
+                None => None,
+            };
+
             // Return combined response
             Ok(JsonResponse(OpenUrlWithElementsResponse {
                 url: url_response,
                 elements: elements_response,
+                ui_state_changed,
+                focus: focus_guard.report().await,
+                screenshot,
             }))
         },
         Err(err) => {
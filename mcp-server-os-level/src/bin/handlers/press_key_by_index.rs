@@ -9,27 +9,38 @@ use serde_json::json;
 use tracing::{debug, error};
 use computer_use_ai_sdk::Desktop;
 
-use crate::types::{AppState, PressKeyByIndexRequest, PressKeyByIndexResponse, ListElementsAndAttributesResponse};
-use crate::refresh_elements_and_attributes_after_action;
+use crate::types::{AppState, PressKeyByIndexRequest, PressKeyByIndexResponse, ListElementsAndAttributesResponse, FocusChange, FocusMethod, ActionScreenshot};
+use crate::refresh_after_action;
+use crate::handlers::utils::{is_transient, retry_with_backoff, focus_element, capture_action_screenshot, detect_ui_change, element_owner, ensure_app_allowed, escape_applescript_string, last_ui_fingerprint, resolve_application, run_osascript, settle, FocusGuard, OsascriptError};
 
 // Response type that combines both results
 #[derive(Debug, Serialize)]
 pub struct PressKeyByIndexWithElementsResponse {
     pub press_key: PressKeyByIndexResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the action (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
 }
 
 pub async fn press_key_by_index_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<PressKeyByIndexRequest>,
 ) -> Result<JsonResponse<PressKeyByIndexWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
     debug!("pressing key combination by index: element_index={}, key_combo={}", 
         request.element_index, request.key_combo);
 
     // Get elements from cache
     let elements_opt = {
         let cache = state.element_cache.lock().await;
-        cache.clone()
+        cache.get(request.app_name.as_deref())
     };
 
     // Check if cache exists
@@ -43,7 +54,13 @@ pub async fn press_key_by_index_handler(
     }
 
     match elements_opt {
-        Some((elements, timestamp, app_name)) if timestamp.elapsed() < std::time::Duration::from_secs(30) => {
+        Some((elements, timestamp, app_name)) if timestamp.elapsed() < state.config.element_cache_ttl => {
+            // A cross-app listing acts on the element's own app
+            let app_name = element_owner(&state, request.element_index, &app_name).await;
+            ensure_app_allowed(&state.config, &app_name, None)?;
+            // Compare the listing before and after to tell whether the action did anything
+            let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
             // Activate the app first
             debug!("activating app: {}", app_name);
             let desktop = match Desktop::new(false, true) {
@@ -60,7 +77,7 @@ pub async fn press_key_by_index_handler(
             };
 
             // Get and activate the application
-            let _ = match desktop.application(&app_name) {
+            let _ = match retry_with_backoff("looking up the app", is_transient, || async { resolve_application(&desktop, &app_name, None) }).await {
                 Ok(app) => app,
                 Err(e) => {
                     error!("application not found: {}", e);
@@ -77,28 +94,32 @@ pub async fn press_key_by_index_handler(
             if request.element_index < elements.len() {
                 let element = &elements[request.element_index];
                 
-                // Step 1: Try to click the element first to focus it
-                if let Err(e) = element.click() {
-                    debug!("failed to click element before key press: {}", e);
+                // Step 1: Focus the element first, by clicking it unless asked not to
+                // Only accessibility focus is retried; another click-to-focus would click twice
+                if let Err(e) = retry_with_backoff("focusing the element", |e| request.focus_method == FocusMethod::Accessibility && is_transient(e), || async { focus_element(element, request.focus_method) }).await {
+                    debug!("failed to focus element ({:?}) before key press: {}", request.focus_method, e);
                     // Continue anyway
                 }
                 
-                // Small delay to ensure element is focused
-                std::thread::sleep(std::time::Duration::from_millis(100));
+                // Let the focus change (and any animation) settle before sending input
+                settle(state.config.inter_action_delay(request.inter_action_delay_ms)).await;
                 
                 // Step 2: Try inputControl first (AppleScript)
                 debug!("attempting to press key '{}' using inputControl (AppleScript)", request.key_combo);
                 
-                use std::process::Command;
-                
                 // Convert key combo to AppleScript format
                 let key_script = convert_key_combo_to_applescript(&request.key_combo);
                 
-                let input_control_success = match Command::new("osascript").arg("-e").arg(key_script).output() {
+                // Not retried: the key may have been posted before osascript failed
+                let input_control_success = match run_osascript(&key_script).await {
                     Ok(_) => {
                         debug!("successfully pressed key '{}' using inputControl", request.key_combo);
                         true
                     },
+                    Err(e @ OsascriptError::PermissionDenied(_)) => {
+                        error!("failed to press key using inputControl: {}", e);
+                        return Err(e.to_response());
+                    },
                     Err(e) => {
                         debug!("failed to press key using inputControl: {} - falling back to accessibility API", e);
                         false
@@ -137,12 +158,16 @@ pub async fn press_key_by_index_handler(
                 };
                 
                 // Get refreshed elements using the helper function
-                let elements_response = refresh_elements_and_attributes_after_action(state, app_name.clone(), 500).await;
+                let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+                let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
                 
                 // Return combined response
                 Ok(JsonResponse(PressKeyByIndexWithElementsResponse {
                     press_key: press_key_response,
                     elements: elements_response,
+                    ui_state_changed,
+                    focus: focus_guard.report().await,
+                    screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
                 }))
             } else {
                 error!(
@@ -181,7 +206,7 @@ pub async fn press_key_by_index_handler(
 }
 
 // Helper function to convert key combo to AppleScript format
-fn convert_key_combo_to_applescript(key_combo: &str) -> String {
+pub fn convert_key_combo_to_applescript(key_combo: &str) -> String {
     // Split the key combo by "+" to handle modifiers
     let parts: Vec<&str> = key_combo.split('+').collect();
     
@@ -194,76 +219,161 @@ fn convert_key_combo_to_applescript(key_combo: &str) -> String {
     let has_option = parts.iter().any(|p| p.trim().eq_ignore_ascii_case("option") || p.trim().eq_ignore_ascii_case("alt")); 
     let has_control = parts.iter().any(|p| p.trim().eq_ignore_ascii_case("control") || p.trim().eq_ignore_ascii_case("ctrl"));
     
-    // For special keys like Return, Tab, etc.
-    let special_key_mapping = match main_key.to_lowercase().as_str() {
-        "return" | "enter" => "return",
-        "tab" => "tab",
-        "escape" | "esc" => "escape",
-        "backspace" | "delete" => "delete",
-        "space" => "space",
-        "down" | "downarrow" => "down arrow",
-        "up" | "uparrow" => "up arrow",
-        "left" | "leftarrow" => "left arrow",
-        "right" | "rightarrow" => "right arrow",
-        _ => main_key,  // use as is for regular keys
-    };
-    
     // Build the AppleScript
     let mut script = String::from("tell application \"System Events\" to ");
+    let has_modifiers = has_command || has_shift || has_option || has_control;
     
-    // For simple one-character keys
-    if special_key_mapping.len() == 1 && !has_command && !has_shift && !has_option && !has_control {
-        script.push_str(&format!("keystroke \"{}\"", special_key_mapping));
+    if let Some(code) = special_key_code(main_key) {
+        // Named keys (Return, arrows, F-keys, ...) only exist as key codes
+        script.push_str(&format!("key code {}", code));
+    } else if main_key.chars().count() == 1 && !has_modifiers {
+        // For simple one-character keys
+        script.push_str(&format!("keystroke \"{}\"", escape_applescript_string(main_key)));
     } else {
-        // For key combinations or special keys
-        script.push_str("key code ");
-        
-        // Map the key to AppleScript key code or use the name for special keys
-        match special_key_mapping {
-            "return" => script.push_str("36"),
-            "tab" => script.push_str("48"),
-            "escape" => script.push_str("53"),
-            "delete" => script.push_str("51"),
-            "space" => script.push_str("49"),
-            "down arrow" => script.push_str("125"),
-            "up arrow" => script.push_str("126"),
-            "left arrow" => script.push_str("123"),
-            "right arrow" => script.push_str("124"),
-            _ => {
-                // For single character keys
-                if special_key_mapping.len() == 1 {
-                    // Get ASCII value
-                    let c = special_key_mapping.chars().next().unwrap();
-                    // This is a simplification - a proper implementation would map characters to key codes
-                    // For letters, lowercase ASCII - 'a' + 0 would work
-                    if c.is_ascii_lowercase() {
-                        script.push_str(&format!("{}", (c as u8 - b'a') + 0));
-                    } else if c.is_ascii_uppercase() {
-                        script.push_str(&format!("{}", (c as u8 - b'A') + 0));
-                    } else {
-                        // This is a placeholder - you'd need a full mapping for all characters
-                        script.push_str(&format!("\"{}\"", c));
-                    }
-                } else {
-                    // For anything else, default to keystroke
-                    script = format!("tell application \"System Events\" to keystroke \"{}\"", special_key_mapping);
-                }
-            }
-        }
-        
-        // Add modifiers
-        if has_command || has_shift || has_option || has_control {
-            script.push_str(" using {");
-            let mut modifiers = Vec::new();
-            if has_command { modifiers.push("command down"); }
-            if has_shift { modifiers.push("shift down"); }
-            if has_option { modifiers.push("option down"); }
-            if has_control { modifiers.push("control down"); }
-            script.push_str(&modifiers.join(", "));
-            script.push_str("}");
+        // Single characters go by their virtual key code so modifiers apply to the key
+        // itself; anything without one is typed as a keystroke instead
+        let mut chars = main_key.chars();
+        match (chars.next().and_then(key_code_for_char), chars.next()) {
+            (Some(code), None) => script.push_str(&format!("key code {}", code)),
+            _ => script.push_str(&format!("keystroke \"{}\"", escape_applescript_string(main_key))),
         }
     }
     
+    // Add modifiers
+    if has_modifiers {
+        script.push_str(" using {");
+        let mut modifiers = Vec::new();
+        if has_command { modifiers.push("command down"); }
+        if has_shift { modifiers.push("shift down"); }
+        if has_option { modifiers.push("option down"); }
+        if has_control { modifiers.push("control down"); }
+        script.push_str(&modifiers.join(", "));
+        script.push_str("}");
+    }
+    
     debug!("generated applescript: {}", script);
     script
 }
+
+// macOS virtual key codes of the named keys, shared by pressKeyByIndex and inputControl's
+// KeyPress. Names are matched ignoring case, spaces, '-' and '_', so "PageDown",
+// "page down" and "page_down" are the same key. "Delete" is the backspace key, as it is
+// labelled on Mac keyboards; the key that deletes forward is "ForwardDelete".
+pub fn special_key_code(name: &str) -> Option<u16> {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let code = match name.as_str() {
+        "return" | "enter" => 36,
+        "tab" => 48,
+        "space" => 49,
+        "delete" | "backspace" => 51,
+        "escape" | "esc" => 53,
+        "forwarddelete" | "fwddelete" => 117,
+        "home" => 115,
+        "end" => 119,
+        "pageup" => 116,
+        "pagedown" => 121,
+        "left" | "leftarrow" | "arrowleft" => 123,
+        "right" | "rightarrow" | "arrowright" => 124,
+        "down" | "downarrow" | "arrowdown" => 125,
+        "up" | "uparrow" | "arrowup" => 126,
+        "f1" => 122,
+        "f2" => 120,
+        "f3" => 99,
+        "f4" => 118,
+        "f5" => 96,
+        "f6" => 97,
+        "f7" => 98,
+        "f8" => 100,
+        "f9" => 101,
+        "f10" => 109,
+        "f11" => 103,
+        "f12" => 111,
+        _ => return None,
+    };
+    Some(code)
+}
+
+// macOS virtual key codes (ANSI layout) of the characters on the main keyboard block.
+// They follow the physical layout, not the alphabet: 'a' is 0, 's' is 1, 'b' is 11.
+fn key_code_for_char(c: char) -> Option<u16> {
+    let code = match c.to_ascii_lowercase() {
+        'a' => 0, 's' => 1, 'd' => 2, 'f' => 3, 'h' => 4, 'g' => 5, 'z' => 6, 'x' => 7,
+        'c' => 8, 'v' => 9, 'b' => 11, 'q' => 12, 'w' => 13, 'e' => 14, 'r' => 15,
+        'y' => 16, 't' => 17, '1' => 18, '2' => 19, '3' => 20, '4' => 21, '6' => 22,
+        '5' => 23, '=' => 24, '9' => 25, '7' => 26, '-' => 27, '8' => 28, '0' => 29,
+        ']' => 30, 'o' => 31, 'u' => 32, '[' => 33, 'i' => 34, 'p' => 35, 'l' => 37,
+        'j' => 38, '\'' => 39, 'k' => 40, ';' => 41, '\\' => 42, ',' => 43, '/' => 44,
+        'n' => 45, 'm' => 46, '.' => 47, '`' => 50,
+        _ => return None,
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letter_combos_use_real_key_codes() {
+        assert_eq!(
+            convert_key_combo_to_applescript("cmd+s"),
+            "tell application \"System Events\" to key code 1 using {command down}"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("cmd+a"),
+            "tell application \"System Events\" to key code 0 using {command down}"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("cmd+shift+z"),
+            "tell application \"System Events\" to key code 6 using {command down, shift down}"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("Command+B"),
+            "tell application \"System Events\" to key code 11 using {command down}"
+        );
+    }
+
+    #[test]
+    fn characters_without_a_key_code_fall_back_to_keystroke() {
+        assert_eq!(
+            convert_key_combo_to_applescript("option+é"),
+            "tell application \"System Events\" to keystroke \"é\" using {option down}"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("s"),
+            "tell application \"System Events\" to keystroke \"s\""
+        );
+    }
+
+    #[test]
+    fn named_keys_map_to_their_key_codes() {
+        assert_eq!(special_key_code("F5"), Some(96));
+        assert_eq!(special_key_code("f12"), Some(111));
+        assert_eq!(special_key_code("PageDown"), Some(121));
+        assert_eq!(special_key_code("page up"), Some(116));
+        assert_eq!(special_key_code("Home"), Some(115));
+        assert_eq!(special_key_code("forward_delete"), Some(117));
+        assert_eq!(special_key_code("ArrowLeft"), Some(123));
+        assert_eq!(special_key_code("F13"), None);
+    }
+
+    #[test]
+    fn named_keys_are_pressed_by_key_code() {
+        assert_eq!(
+            convert_key_combo_to_applescript("F5"),
+            "tell application \"System Events\" to key code 96"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("shift+PageDown"),
+            "tell application \"System Events\" to key code 121 using {shift down}"
+        );
+        assert_eq!(
+            convert_key_combo_to_applescript("cmd+Up"),
+            "tell application \"System Events\" to key code 126 using {command down}"
+        );
+    }
+}
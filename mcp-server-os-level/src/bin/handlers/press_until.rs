@@ -0,0 +1,152 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, ListFormat, PressUntilCondition, PressUntilRequest, PressUntilResponse, PressUntilState};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::list_elements_and_attributes::listing_entry;
+use crate::handlers::press_key_by_index::convert_key_combo_to_applescript;
+use crate::handlers::utils::{app_identity, ensure_app_allowed, resolve_application, run_osascript, settle};
+
+const DEFAULT_MAX_PRESSES: usize = 20;
+// Runaway loops on lists that never match are worse than a second call
+const MAX_PRESSES_LIMIT: usize = 200;
+
+pub async fn press_until_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<PressUntilRequest>,
+) -> Result<JsonResponse<PressUntilResponse>, (StatusCode, JsonResponse<Value>)> {
+    let max_presses = request.max_presses.unwrap_or(DEFAULT_MAX_PRESSES).min(MAX_PRESSES_LIMIT);
+    info!("pressing '{}' until '{}' is {:?} (at most {} presses)",
+          request.key_combo, request.condition.text, request.condition.state, max_presses);
+
+    ensure_app_allowed(&state.config, &request.app_name, request.bundle_id.as_deref())?;
+
+    // Key presses go to the frontmost app, so activate it
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    let app = match resolve_application(&desktop, &request.app_name, request.bundle_id.as_deref()) {
+        Ok(app) => app,
+        Err(e) => {
+            error!("application not found: {}", e);
+            return Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({
+                    "error": format!("application not found: {}", e)
+                })),
+            ));
+        }
+    };
+    let (app_name, bundle_id) = app_identity(&desktop, &app);
+    let app_key = bundle_id.or(app_name).unwrap_or_else(|| request.app_name.clone());
+
+    let key_script = convert_key_combo_to_applescript(&request.key_combo);
+    let delay = state.config.inter_action_delay(request.inter_action_delay_ms);
+
+    // The condition may already hold, in which case nothing is pressed
+    let mut presses = 0;
+    let mut matched = find_match(&desktop, &app, &request.condition);
+    while matched.is_none() && presses < max_presses {
+        if let Err(e) = run_osascript(&key_script).await {
+            error!("failed to press '{}': {}", request.key_combo, e);
+            return Err(e.to_response());
+        }
+        presses += 1;
+
+        // Let the selection move (and any scrolling finish) before checking
+        settle(delay).await;
+        matched = find_match(&desktop, &app, &request.condition);
+        debug!("press {}: condition {}", presses, if matched.is_some() { "met" } else { "not met" });
+    }
+
+    let success = matched.is_some();
+    let message = if success {
+        format!("'{}' is {:?} after {} presses of '{}'", request.condition.text, request.condition.state, presses, request.key_combo)
+    } else {
+        format!("'{}' was not {:?} after {} presses of '{}'", request.condition.text, request.condition.state, presses, request.key_combo)
+    }
+    .to_lowercase();
+    info!("{}", message);
+
+    let elements = refresh_elements_and_attributes_after_action(state.clone(), app_key, 0).await;
+
+    Ok(JsonResponse(PressUntilResponse {
+        success,
+        presses,
+        message,
+        matched,
+        elements,
+    }))
+}
+
+// The focused or selected element whose text satisfies the condition, if any
+fn find_match(desktop: &Desktop, app: &UIElement, condition: &PressUntilCondition) -> Option<Value> {
+    let candidates = match condition.state {
+        PressUntilState::Focused => desktop.focused_element().ok().into_iter().collect(),
+        PressUntilState::Selected => app
+            .locator("")
+            .and_then(|locator| locator.all())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|element| {
+                element
+                    .attributes()
+                    .properties
+                    .get("AXSelected")
+                    .and_then(|v| v.as_ref())
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<_>>(),
+    };
+
+    candidates.iter().find_map(|element| {
+        let entry = listing_entry(element, &element.attributes(), 0, ListFormat::Compact);
+        let text = entry["text"].as_str().unwrap_or_default();
+        text_satisfies(text, condition).then(|| json!({"role": entry["role"], "text": text}))
+    })
+}
+
+fn text_satisfies(text: &str, condition: &PressUntilCondition) -> bool {
+    let text = text.trim().to_lowercase();
+    let wanted = condition.text.trim().to_lowercase();
+    if condition.exact {
+        text == wanted
+    } else {
+        text.contains(&wanted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(text: &str, exact: bool) -> PressUntilCondition {
+        PressUntilCondition { text: text.to_string(), state: PressUntilState::Selected, exact }
+    }
+
+    #[test]
+    fn matches_text_case_insensitively() {
+        assert!(text_satisfies("Downloads", &condition("download", false)));
+        assert!(!text_satisfies("Downloads", &condition("download", true)));
+        assert!(text_satisfies(" Downloads ", &condition("downloads", true)));
+        assert!(!text_satisfies("Desktop", &condition("download", false)));
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::Desktop;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, ResetInputStateRequest, ResetInputStateResponse, ScreenPoint};
+use crate::handlers::utils::{escape_applescript_string, run_osascript};
+
+// Put input back in a known state between automations: no modifier keys or mouse buttons
+// held (a crashed drag or an unpaired key-down leaves them stuck), the cursor at a home
+// position, and optionally the clipboard a test saved before it started
+pub async fn reset_input_state_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ResetInputStateRequest>,
+) -> Result<JsonResponse<ResetInputStateResponse>, (StatusCode, JsonResponse<Value>)> {
+    let home = request
+        .home
+        .or_else(|| state.config.input_home_position.map(|(x, y)| ScreenPoint { x, y }));
+    info!("resetting input state (home: {:?}, clipboard: {})", home, request.clipboard.is_some());
+
+    let desktop = match Desktop::new(false, false) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+
+    if let Err(e) = desktop.release_held_input() {
+        error!("failed to release held keys and buttons: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to release held keys and buttons: {}", e)})),
+        ));
+    }
+
+    if let Some(home) = home {
+        if let Err(e) = desktop.move_mouse((home.x, home.y)) {
+            error!("failed to move the cursor home: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to move the cursor home: {}", e)})),
+            ));
+        }
+    }
+
+    if let Some(clipboard) = &request.clipboard {
+        if let Err(e) = run_osascript(&format!("set the clipboard to \"{}\"", escape_applescript_string(clipboard))).await {
+            error!("failed to restore the clipboard: {}", e);
+            return Err(e.to_response());
+        }
+    }
+
+    Ok(JsonResponse(ResetInputStateResponse {
+        success: true,
+        cursor: home,
+        clipboard_restored: request.clipboard.is_some(),
+        message: match home {
+            Some(home) => format!("released held keys and buttons, cursor at ({}, {})", home.x, home.y),
+            None => "released held keys and buttons".to_string(),
+        },
+    }))
+}
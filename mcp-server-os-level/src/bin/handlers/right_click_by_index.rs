@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+use computer_use_ai_sdk::{Desktop, UIElement};
+
+use crate::types::{AppState, ClickByIndexResponse, RightClickByIndexRequest};
+use crate::refresh_after_action;
+use crate::handlers::click_by_index::ClickByIndexWithElementsResponse;
+use crate::handlers::utils::{cached_element_by_index, capture_action_screenshot, click_point, detect_ui_change, detect_ui_diff, ensure_app_allowed, last_ui_fingerprint, last_ui_snapshot, resolve_application, run_jxa, FocusGuard, OsascriptError};
+
+// System Events can only left-click, so post the right button events through CoreGraphics
+const RIGHT_CLICK_SCRIPT: &str = r#"
+ObjC.import('CoreGraphics');
+function run(argv) {
+    const point = $.CGPointMake(Number(argv[0]), Number(argv[1]));
+    for (const type of [$.kCGEventRightMouseDown, $.kCGEventRightMouseUp]) {
+        $.CGEventPost($.kCGHIDEventTap, $.CGEventCreateMouseEvent(null, type, point, $.kCGMouseButtonRight));
+        delay(0.05);
+    }
+}
+"#;
+
+// Open an element's context menu: right-click its center, or ask for AXShowMenu when the
+// click can't be sent. The refreshed listing then includes the menu's items.
+pub async fn right_click_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RightClickByIndexRequest>,
+) -> Result<JsonResponse<ClickByIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    info!("right-clicking element {} in {}", request.element_index, app_name);
+
+    // Compare the listing before and after to tell whether a menu opened
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+    let snapshot_before = last_ui_snapshot(&state, &app_name).await;
+
+    let method_used = if right_click_element(&element, &app_name).await? { "AppleScript" } else { "AXShowMenu" };
+
+    let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+    let ui_changes = detect_ui_diff(&state, &app_name, snapshot_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(ClickByIndexWithElementsResponse {
+        click: ClickByIndexResponse {
+            success: true,
+            message: format!(
+                "successfully right-clicked element with role: {} (using {} method)",
+                element.role(), method_used
+            ),
+            elements: None,
+            retried: false,
+        },
+        elements: elements_response,
+        ui_state_changed,
+        ui_changes,
+        focus: focus_guard.report().await,
+        screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
+    }))
+}
+
+// Right-click the element's center, falling back to AXShowMenu.
+// Returns whether the coordinate click was used.
+async fn right_click_element(element: &UIElement, app_name: &str) -> Result<bool, (StatusCode, JsonResponse<Value>)> {
+    let clicked = match element.bounds() {
+        Ok(bounds) => {
+            let point = click_point(bounds);
+
+            // The menu belongs to whichever app is frontmost, so bring this one forward first
+            let desktop = match Desktop::new(false, true) {
+                Ok(d) => d,
+                Err(e) => {
+                    error!("failed to initialize desktop automation: {}", e);
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        JsonResponse(json!({
+                            "error": format!("failed to initialize desktop automation: {}", e)
+                        })),
+                    ));
+                }
+            };
+            if let Err(e) = resolve_application(&desktop, app_name, None) {
+                error!("application not found: {}", e);
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    JsonResponse(json!({"error": format!("application not found: {}", e)})),
+                ));
+            }
+
+            debug!("right-clicking at [{}, {}]", point.x, point.y);
+            match run_jxa(RIGHT_CLICK_SCRIPT, &[&point.x.to_string(), &point.y.to_string()]).await {
+                Ok(_) => true,
+                Err(e @ OsascriptError::PermissionDenied(_)) => {
+                    error!("failed to right-click: {}", e);
+                    return Err(e.to_response());
+                }
+                Err(e) => {
+                    debug!("failed to right-click: {} - falling back to AXShowMenu", e);
+                    false
+                }
+            }
+        }
+        Err(e) => {
+            debug!("could not get element bounds ({}) - falling back to AXShowMenu", e);
+            false
+        }
+    };
+
+    if !clicked {
+        if let Err(e) = element.perform_action("AXShowMenu") {
+            error!("failed to open context menu with AXShowMenu: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to open context menu with both a right click and AXShowMenu: {}", e)
+                })),
+            ));
+        }
+    }
+
+    Ok(clicked)
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use crate::config::RAW_APPLESCRIPT_TOOL;
+use crate::types::{AppState, RunAppleScriptRequest, RunAppleScriptResponse};
+use crate::handlers::utils::{app_not_allowed, run_osascript_output};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const MAX_TIMEOUT_MS: u64 = 120_000;
+
+const RAW_APPLESCRIPT_WARNING: &str = "runAppleScript is enabled on this server (ENABLE_RAW_APPLESCRIPT): \
+     any client that can reach it can run arbitrary scripts with the server's Automation and \
+     Accessibility permissions, including shell commands via `do shell script`. \
+     only enable it on localhost-bound servers with trusted clients.";
+
+// Escape hatch for app scripting dictionaries (Music, Mail, ...) the structured tools don't
+// cover. Only mounted when ENABLE_RAW_APPLESCRIPT is set, and refuses to run otherwise.
+pub async fn run_applescript_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RunAppleScriptRequest>,
+) -> Result<JsonResponse<RunAppleScriptResponse>, (StatusCode, JsonResponse<Value>)> {
+    if !state.config.is_tool_enabled(RAW_APPLESCRIPT_TOOL) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            JsonResponse(json!({
+                "error": "runAppleScript is disabled - set ENABLE_RAW_APPLESCRIPT=true on the server to allow it"
+            })),
+        ));
+    }
+
+    // A script can drive any app, so there's nothing safe mode could check it against
+    if state.config.safe_mode_allowed_apps.is_some() {
+        return Err(app_not_allowed(&state.config, "runAppleScript (it can target any app)"));
+    }
+
+    if request.script.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "script must not be empty"})),
+        ));
+    }
+
+    let timeout = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).min(MAX_TIMEOUT_MS));
+    // Log every script run so there is a record of what clients did with it
+    warn!("running raw applescript ({} chars, timeout {:?}): {}", request.script.len(), timeout, request.script);
+
+    let output = match run_osascript_output(&request.script, timeout).await {
+        Ok(output) => output,
+        Err(e) => {
+            error!("failed to run applescript: {}", e);
+            return Err(e.to_response());
+        }
+    };
+
+    let exit_code = output.status.code();
+    info!("raw applescript finished with exit code {:?}", exit_code);
+
+    Ok(JsonResponse(RunAppleScriptResponse {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).trim_end().to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim_end().to_string(),
+        exit_code,
+        warning: RAW_APPLESCRIPT_WARNING.to_string(),
+    }))
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::types::{AppState, ScreenshotByIndexRequest, ScreenshotByIndexResponse};
+use crate::handlers::utils::cached_element_by_index;
+
+pub async fn screenshot_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ScreenshotByIndexRequest>,
+) -> Result<JsonResponse<ScreenshotByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    info!("capturing element {} in {}", request.element_index, app_name);
+
+    match element.screenshot() {
+        Ok(png) => Ok(JsonResponse(ScreenshotByIndexResponse {
+            success: true,
+            format: "png".to_string(),
+            image_base64: STANDARD.encode(png),
+        })),
+        Err(e) => {
+            error!("failed to capture element {}: {}", request.element_index, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to capture element: {}", e)
+                })),
+            ))
+        }
+    }
+}
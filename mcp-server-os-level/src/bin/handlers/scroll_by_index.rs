@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{error, info};
+use computer_use_ai_sdk::Desktop;
+
+use crate::types::{AppState, ScrollByIndexRequest, ScrollByIndexResponse, ListElementsAndAttributesResponse, FocusChange, ActionScreenshot};
+use crate::refresh_after_action;
+use crate::handlers::utils::{cached_element_by_index, capture_action_screenshot, detect_ui_change, ensure_app_allowed, last_ui_fingerprint, resolve_application, FocusGuard};
+
+// Lines scrolled when the request doesn't say
+const DEFAULT_SCROLL_LINES: f64 = 3.0;
+
+// Response type that combines both results
+#[derive(Serialize)]
+pub struct ScrollByIndexWithElementsResponse {
+    pub scroll: ScrollByIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the scroll (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
+}
+
+// Scroll wheel over an element's center, then list again so newly revealed content
+// gets indices
+pub async fn scroll_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ScrollByIndexRequest>,
+) -> Result<JsonResponse<ScrollByIndexWithElementsResponse>, (StatusCode, JsonResponse<Value>)> {
+    let direction = request.direction.to_lowercase();
+    if !matches!(direction.as_str(), "up" | "down" | "left" | "right") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": format!("invalid scroll direction: {} (expected up, down, left or right)", request.direction)})),
+        ));
+    }
+    let amount = request.amount.unwrap_or(DEFAULT_SCROLL_LINES);
+
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    info!("scrolling element {} in {} {} by {}", request.element_index, app_name, direction, amount);
+
+    // Compare the listing before and after to tell whether anything moved
+    let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
+    // Only the frontmost app's windows get the wheel events reliably
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    if let Err(e) = resolve_application(&desktop, &app_name, None) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("application not found: {}", e)})),
+        ));
+    }
+
+    if let Err(e) = element.scroll(&direction, amount) {
+        error!("failed to scroll element: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to scroll element: {}", e)})),
+        ));
+    }
+
+    let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+    let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
+
+    Ok(JsonResponse(ScrollByIndexWithElementsResponse {
+        scroll: ScrollByIndexResponse {
+            success: true,
+            message: format!("scrolled {} by {} lines over element with role: {}", direction, amount, element.role()),
+        },
+        elements: elements_response,
+        ui_state_changed,
+        focus: focus_guard.report().await,
+        screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
+    }))
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, SetFieldByIndexRequest, SetFieldByIndexResponse, SetFieldStep};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::type_by_index::{confirm_value, focus_lands_on, needs_confirm};
+use crate::handlers::utils::{cached_element_by_index, ensure_app_allowed, escape_applescript_string, resolve_application, run_osascript, settle, OsascriptError};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 2;
+const MAX_ATTEMPTS: u32 = 5;
+
+// Text longer than this is pasted rather than typed key by key
+const LONG_TEXT_CHARS: usize = 100;
+
+// How long the app gets to read the clipboard before the previous contents go back
+const PASTE_SETTLE: Duration = Duration::from_millis(150);
+
+// Form filling in one call: focus the field, clear it, type (or paste) the text, read it back
+// and try again if it didn't take, then confirm. Retries set AXValue directly, which works
+// for fields that drop synthesized keystrokes (e.g. while an autocomplete popup has focus).
+pub async fn set_field_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetFieldByIndexRequest>,
+) -> Result<JsonResponse<SetFieldByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+
+    let delay = state.config.inter_action_delay(request.inter_action_delay_ms);
+    let max_attempts = request.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS).clamp(1, MAX_ATTEMPTS);
+    let use_clipboard = request.use_clipboard.unwrap_or_else(|| wants_clipboard(&request.text));
+    let secure = is_secure_field(&element);
+    info!("setting field {} in {} ({} chars, clipboard: {}, secure: {})",
+          request.element_index, app_name, request.text.chars().count(), use_clipboard, secure);
+
+    // Keystrokes go to the frontmost app, so activate it
+    let desktop = match Desktop::new(false, true) {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failed to initialize desktop automation: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to initialize desktop automation: {}", e)
+                })),
+            ));
+        }
+    };
+    if let Err(e) = resolve_application(&desktop, &app_name, None) {
+        error!("application not found: {}", e);
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({
+                "error": format!("application not found: {}", e)
+            })),
+        ));
+    }
+
+    let mut steps = Vec::new();
+    let mut attempts = 0;
+    let mut verified = false;
+
+    while attempts < max_attempts && !verified {
+        attempts += 1;
+
+        if let Err(e) = element.click() {
+            debug!("failed to click field before setting it: {}", e);
+        }
+        settle(delay).await;
+        let focused = focus_lands_on(&desktop, &element);
+        steps.push(step("focus", focused != Some(false), Some("click"),
+                        (focused == Some(false)).then(|| "keyboard focus landed on another element".to_string())));
+
+        let (cleared, clear_method) = clear_field(&element, delay).await.map_err(|e| e.to_response())?;
+        steps.push(step("clear", cleared, Some(clear_method), None));
+
+        // Keystrokes (or a paste) first; if that didn't read back right, set the value directly
+        let typed = if attempts == 1 {
+            let method = if use_clipboard { "clipboard" } else { "keystroke" };
+            match enter_text(&request.text, use_clipboard).await {
+                Ok(()) => step("type", true, Some(method), None),
+                Err(e @ OsascriptError::PermissionDenied(_)) => {
+                    error!("failed to type into field: {}", e);
+                    return Err(e.to_response());
+                }
+                Err(e) => step("type", false, Some(method), Some(e.to_string())),
+            }
+        } else {
+            match element.set_value(&request.text) {
+                Ok(()) => step("type", true, Some("AXValue"), None),
+                Err(e) => step("type", false, Some("AXValue"), Some(e.to_string())),
+            }
+        };
+        steps.push(typed);
+        settle(delay).await;
+
+        // Password fields don't give their value back, so there's nothing to compare
+        if secure {
+            steps.push(step("verify", true, None, Some("secure text field, value can't be read back".to_string())));
+            verified = true;
+            break;
+        }
+        let value = element.attributes().value;
+        verified = value.as_deref() == Some(request.text.as_str());
+        steps.push(step("verify", verified, None, Some(format!("read back {:?}", value))));
+        if !verified {
+            debug!("field {} reads {:?} after attempt {}", request.element_index, value, attempts);
+        }
+    }
+
+    let confirm = request.confirm.unwrap_or_else(|| needs_confirm(&element));
+    if confirm && verified {
+        let confirmed = confirm_value(&element).await;
+        steps.push(step("confirm", confirmed, Some("AXConfirm or Return"), None));
+    }
+
+    let value = if secure { None } else { element.attributes().value };
+    let success = verified && steps.last().map_or(false, |last| last.success);
+    info!("set field {} in {}: success {} after {} attempts", request.element_index, app_name, success, attempts);
+
+    let elements = refresh_elements_and_attributes_after_action(state.clone(), app_name, 300).await;
+
+    Ok(JsonResponse(SetFieldByIndexResponse {
+        success,
+        steps,
+        value,
+        attempts,
+        elements,
+    }))
+}
+
+fn step(step: &'static str, success: bool, method: Option<&'static str>, detail: Option<String>) -> SetFieldStep {
+    SetFieldStep { step, success, method, detail }
+}
+
+// Keystrokes mangle non-ASCII text on many keyboard layouts and are slow for long text
+fn wants_clipboard(text: &str) -> bool {
+    !text.is_ascii() || text.chars().count() > LONG_TEXT_CHARS
+}
+
+fn is_secure_field(element: &UIElement) -> bool {
+    element
+        .attributes()
+        .properties
+        .get("AXSubrole")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        == Some("AXSecureTextField")
+}
+
+// Empty the field through AXValue, or by selecting everything and deleting it for fields
+// that ignore that. Returns whether it reads back empty and how it was cleared.
+async fn clear_field(element: &UIElement, delay: Duration) -> Result<(bool, &'static str), OsascriptError> {
+    let is_empty = |element: &UIElement| element.attributes().value.map_or(true, |value| value.is_empty());
+    if element.set_value("").is_ok() && is_empty(element) {
+        return Ok((true, "AXValue"));
+    }
+
+    run_osascript("tell application \"System Events\" to keystroke \"a\" using command down").await?;
+    run_osascript("tell application \"System Events\" to key code 51").await?;
+    settle(delay).await;
+    Ok((is_empty(element), "select all + delete"))
+}
+
+pub async fn enter_text(text: &str, use_clipboard: bool) -> Result<(), OsascriptError> {
+    if !use_clipboard {
+        let script = format!("tell application \"System Events\" to keystroke \"{}\"", escape_applescript_string(text));
+        return run_osascript(&script).await.map(|_| ());
+    }
+
+    // Paste, then put back whatever text the user had on the clipboard
+    let previous = run_osascript("the clipboard as text").await.ok();
+    run_osascript(&format!("set the clipboard to \"{}\"", escape_applescript_string(text))).await?;
+    let pasted = run_osascript("tell application \"System Events\" to keystroke \"v\" using command down").await;
+    settle(PASTE_SETTLE).await;
+    if let Some(previous) = previous {
+        if let Err(e) = run_osascript(&format!("set the clipboard to \"{}\"", escape_applescript_string(&previous))).await {
+            debug!("failed to restore the clipboard: {}", e);
+        }
+    }
+    pasted.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pastes_long_or_non_ascii_text() {
+        assert!(!wants_clipboard("jane@example.com"));
+        assert!(wants_clipboard("Zoë"));
+        assert!(wants_clipboard("東京"));
+        assert!(wants_clipboard(&"a".repeat(LONG_TEXT_CHARS + 1)));
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::UIElement;
+use serde_json::{json, Value};
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, StepByIndexRequest, StepByIndexResponse};
+use crate::refresh_elements_and_attributes_after_action;
+use crate::handlers::utils::{cached_element_by_index, ensure_app_allowed};
+
+// Roles that take AXIncrement/AXDecrement
+const STEPPABLE_ROLES: [&str; 3] = ["AXIncrementor", "AXSlider", "AXValueIndicator"];
+const MAX_STEPS: u32 = 100;
+
+pub async fn step_by_index_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<StepByIndexRequest>,
+) -> Result<JsonResponse<StepByIndexResponse>, (StatusCode, JsonResponse<Value>)> {
+    let (element, app_name) = cached_element_by_index(&state, request.element_index, request.app_name.as_deref()).await?;
+    ensure_app_allowed(&state.config, &app_name, None)?;
+    let count = request.count.unwrap_or(1).min(MAX_STEPS);
+    let action = request.direction.ax_action();
+
+    // A number field is usually listed next to its stepper rather than as one
+    let Some(stepper) = find_stepper(&element) else {
+        error!("element {} ({}) supports neither increment nor decrement", request.element_index, element.role());
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("element {} is a {} and supports neither increment nor decrement, and has no stepper next to it",
+                                request.element_index, element.role())
+            })),
+        ));
+    };
+
+    info!("performing {} {} times on element {} in {}", action, count, request.element_index, app_name);
+    let mut steps = 0;
+    while steps < count {
+        if let Err(e) = stepper.perform_action(action) {
+            // The very first step failing means the control doesn't take the action at all
+            error!("failed to perform {} after {} steps: {}", action, steps, e);
+            if steps == 0 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    JsonResponse(json!({
+                        "error": format!("element {} does not support {}: {}", request.element_index, action, e)
+                    })),
+                ));
+            }
+            break;
+        }
+        steps += 1;
+    }
+
+    // Prefer the field's value, steppers themselves often have none
+    let value = element.attributes().value.or_else(|| stepper.attributes().value);
+    debug!("value after stepping: {:?}", value);
+
+    let elements = refresh_elements_and_attributes_after_action(state.clone(), app_name, 200).await;
+
+    Ok(JsonResponse(StepByIndexResponse {
+        success: steps == count,
+        steps,
+        message: format!("performed {} {} of {} times", action, steps, count),
+        value,
+        elements,
+    }))
+}
+
+// The element itself if it steps, otherwise a stepper among its children or siblings
+fn find_stepper(element: &UIElement) -> Option<UIElement> {
+    let is_steppable = |candidate: &UIElement| STEPPABLE_ROLES.contains(&candidate.role().as_str());
+    if is_steppable(element) {
+        return Some(element.clone());
+    }
+    let children = element.children().unwrap_or_default();
+    if let Some(stepper) = children.into_iter().find(|child| child.role() == "AXIncrementor") {
+        return Some(stepper);
+    }
+    element
+        .parent()
+        .ok()
+        .flatten()?
+        .children()
+        .ok()?
+        .into_iter()
+        .find(|sibling| sibling.role() == "AXIncrementor")
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::types::{AppState, PressUntilCondition, PressUntilRequest, PressUntilState, TabToFieldRequest, TabToFieldResponse};
+use crate::handlers::press_until::press_until_handler;
+
+// Keyboard-only form navigation: Tab through the fields until the one with the label
+// has focus. Works the same whatever the layout, unlike clicking at coordinates.
+pub async fn tab_to_field_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<TabToFieldRequest>,
+) -> Result<JsonResponse<TabToFieldResponse>, (StatusCode, JsonResponse<Value>)> {
+    if request.label.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "label must not be empty"})),
+        ));
+    }
+
+    info!("tabbing to field '{}' in {}", request.label, request.bundle_id.as_deref().unwrap_or(&request.app_name));
+
+    // This is pressUntil with the key and the condition fixed
+    let pressed = press_until_handler(
+        State(state),
+        Json(PressUntilRequest {
+            app_name: request.app_name,
+            bundle_id: request.bundle_id,
+            key_combo: if request.reverse { "shift+tab" } else { "tab" }.to_string(),
+            condition: PressUntilCondition {
+                text: request.label.clone(),
+                state: PressUntilState::Focused,
+                exact: request.exact,
+            },
+            max_presses: request.max_tabs,
+            inter_action_delay_ms: request.inter_action_delay_ms,
+        }),
+    )
+    .await?
+    .0;
+
+    let message = if pressed.success {
+        format!("field '{}' has focus after {} tabs", request.label, pressed.presses)
+    } else {
+        format!("no field labelled '{}' got focus in {} tabs", request.label, pressed.presses)
+    };
+
+    Ok(JsonResponse(TabToFieldResponse {
+        success: pressed.success,
+        tabs: pressed.presses,
+        message,
+        focused: pressed.matched,
+        elements: pressed.elements,
+    }))
+}
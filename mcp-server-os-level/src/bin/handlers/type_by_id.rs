@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use tracing::info;
+
+use crate::types::{AppState, TypeByIdRequest, TypeByIndexRequest};
+use crate::handlers::type_by_index::{type_by_index_handler, TypeByIndexWithElementsResponse};
+use crate::handlers::utils::cached_index_by_id;
+
+// Type into an element by the element_id of its listing entry: the id is resolved to the
+// element's current index in the cached listing, and the typing is typeByIndex's
+pub async fn type_by_id_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<TypeByIdRequest>,
+) -> Result<JsonResponse<TypeByIndexWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    let element_index = cached_index_by_id(&state, &request.element_id, request.app_name.as_deref()).await?;
+    info!("element id {} is at index {}", request.element_id, element_index);
+
+    type_by_index_handler(
+        State(state),
+        Json(TypeByIndexRequest {
+            element_index,
+            app_name: request.app_name,
+            text: request.text,
+            confirm_after: request.confirm_after,
+            inter_action_delay_ms: request.inter_action_delay_ms,
+            strict_focus: request.strict_focus,
+            method: request.method,
+            focus_method: Default::default(),
+            options: request.options,
+        }),
+    )
+    .await
+}
@@ -7,26 +7,38 @@ use axum::{
 use serde::Serialize;
 use serde_json::json;
 use tracing::{debug, error};
-use computer_use_ai_sdk::Desktop;
+use computer_use_ai_sdk::{Desktop, UIElement};
 
-use crate::types::{AppState, TypeByIndexRequest, TypeByIndexResponse, ListElementsAndAttributesResponse};
-use crate::refresh_elements_and_attributes_after_action;
+use crate::types::{AppState, TypeByIndexRequest, TypeByIndexResponse, TypingMethod, ListElementsAndAttributesResponse, FocusChange, FocusMethod, ActionScreenshot};
+use crate::refresh_after_action;
+use crate::handlers::set_field_by_index::enter_text;
+use crate::handlers::utils::{is_transient, retry_with_backoff, focus_element, capture_action_screenshot, detect_ui_change, element_owner, ensure_app_allowed, last_ui_fingerprint, resolve_application, run_osascript, settle, FocusGuard, OsascriptError};
 
 // Response type that combines both results
 #[derive(Serialize)]
 pub struct TypeByIndexWithElementsResponse {
     pub type_action: TypeByIndexResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the action (None if unknown)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
 }
 
 pub async fn type_by_index_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<TypeByIndexRequest>,
 ) -> Result<JsonResponse<TypeByIndexWithElementsResponse>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    // Put the user's app back in front once we're done, if asked
+    let focus_guard = FocusGuard::capture(request.options.restore_focus).await;
+
     // Get elements from cache
     let elements_opt = {
         let cache = state.element_cache.lock().await;
-        cache.clone()
+        cache.get(request.app_name.as_deref())
     };
 
     // Check if cache exists
@@ -40,13 +52,20 @@ pub async fn type_by_index_handler(
     }
 
     match elements_opt {
-        Some((elements, timestamp, app_name)) if timestamp.elapsed() < std::time::Duration::from_secs(30) => {
+        Some((elements, timestamp, app_name)) if timestamp.elapsed() < state.config.element_cache_ttl => {
+            // A cross-app listing acts on the element's own app
+            let app_name = element_owner(&state, request.element_index, &app_name).await;
+            ensure_app_allowed(&state.config, &app_name, None)?;
+            // Compare the listing before and after to tell whether the action did anything
+            let fingerprint_before = last_ui_fingerprint(&state, &app_name).await;
+
             // Use element_index directly
             if request.element_index < elements.len() {
                 let element = &elements[request.element_index];
                 
-                // Step 1: Try inputControl first
-                debug!("attempting to type text '{}' using inputControl (AppleScript)", request.text);
+                // Step 1: Type with the requested method, unless that's AXValue anyway
+                let method = request.method.resolve(&request.text);
+                debug!("attempting to type text '{}' using {:?}", request.text, method);
 
                 // Activate the app first
                 debug!("activating app: {}", app_name);
@@ -64,7 +83,7 @@ pub async fn type_by_index_handler(
                 };
 
                 // Get and activate the application
-                let _ = match desktop.application(&app_name) {
+                let _ = match retry_with_backoff("looking up the app", is_transient, || async { resolve_application(&desktop, &app_name, None) }).await {
                     Ok(app) => app,
                     Err(e) => {
                         error!("application not found: {}", e);
@@ -77,34 +96,61 @@ pub async fn type_by_index_handler(
                     }
                 };
 
-                // Click the element first to ensure it has focus
-                if let Err(e) = element.click() {
-                    debug!("failed to click element before typing: {}", e);
+                // Focus the element first, by clicking it unless asked not to
+                // Only accessibility focus is retried; another click-to-focus would click twice
+                if let Err(e) = retry_with_backoff("focusing the element", |e| request.focus_method == FocusMethod::Accessibility && is_transient(e), || async { focus_element(element, request.focus_method) }).await {
+                    debug!("failed to focus element ({:?}) before typing: {}", request.focus_method, e);
                     // Continue anyway
                 }
 
-                // Small delay to ensure element is focused
-                std::thread::sleep(std::time::Duration::from_millis(100));
-
-                // Use inputControl for text input using System Events
-                use std::process::Command;
+                // Let the focus change (and any animation) settle before sending input
+                settle(state.config.inter_action_delay(request.inter_action_delay_ms)).await;
 
-                // Escape any quotes in the text to avoid breaking the AppleScript
-                let escaped_text = request.text.replace("\"", "\\\"");
-                let script = format!("tell application \"System Events\" to keystroke \"{}\"", escaped_text);
+                // Keystrokes go wherever the focus is, so check the click focused the target
+                let focus_warning = match focus_lands_on(&desktop, element) {
+                    Some(false) => {
+                        let focused = desktop.focused_element().map(|f| f.role()).unwrap_or_default();
+                        let warning = format!(
+                            "FOCUS_MISMATCH: focus is on a {} instead of element {} ({})",
+                            focused, request.element_index, element.role()
+                        );
+                        if request.strict_focus {
+                            error!("{} - not typing", warning);
+                            return Err((
+                                StatusCode::CONFLICT,
+                                JsonResponse(json!({
+                                    "error": format!("{} - nothing was typed", warning),
+                                    "code": "FOCUS_MISMATCH"
+                                })),
+                            ));
+                        }
+                        debug!("{} - typing anyway", warning);
+                        Some(warning)
+                    }
+                    _ => None,
+                };
 
-                let input_control_success = match Command::new("osascript").arg("-e").arg(script).output() {
-                    Ok(_) => {
-                        debug!("successfully typed text '{}' using inputControl", request.text);
-                        true
+                // Keystrokes and pastes go through System Events; enter_text escapes the text and
+                // puts the user's clipboard back after pasting
+                let input_control_success = match method {
+                    TypingMethod::Accessibility => false,
+                    _ => match enter_text(&request.text, method == TypingMethod::Paste).await {
+                        Ok(_) => {
+                            debug!("successfully typed text '{}' using {:?}", request.text, method);
+                            true
+                        },
+                        Err(e @ OsascriptError::PermissionDenied(_)) => {
+                            error!("failed to type text using {:?}: {}", method, e);
+                            return Err(e.to_response());
+                        },
+                        Err(e) => {
+                            debug!("failed to type text using {:?}: {} - falling back to AXValue", method, e);
+                            false
+                        }
                     },
-                    Err(e) => {
-                        debug!("failed to type text using inputControl: {} - falling back to AXValue", e);
-                        false
-                    }
                 };
 
-                // Step 2: If inputControl failed, try AXValue as fallback
+                // Step 2: Set AXValue when asked to, or when the input above failed
                 if !input_control_success {
                     debug!("falling back to AXValue for typing");
                     match element.type_text(&request.text) {
@@ -112,8 +158,8 @@ pub async fn type_by_index_handler(
                             debug!("successfully typed text '{}' into element with role: {} using AXValue", 
                                   request.text, element.role());
                             
-                            // Add a small delay to ensure UI updates
-                            std::thread::sleep(std::time::Duration::from_millis(100));
+                            // Let the UI catch up before reading the value back
+                            settle(state.config.inter_action_delay(request.inter_action_delay_ms)).await;
                             
                             // Verify text was actually set by reading it back
                             let verification = match element.text(1) {
@@ -139,7 +185,7 @@ pub async fn type_by_index_handler(
                                 return Err((
                                     StatusCode::INTERNAL_SERVER_ERROR,
                                     JsonResponse(json!({
-                                        "error": "failed to type text using both inputControl and AXValue methods"
+                                        "error": "failed to type text, AXValue did not take the text either"
                                     })),
                                 ));
                             }
@@ -149,30 +195,50 @@ pub async fn type_by_index_handler(
                             return Err((
                                 StatusCode::INTERNAL_SERVER_ERROR,
                                 JsonResponse(json!({
-                                    "error": format!("failed to type text using both inputControl and AXValue methods: {}", e)
+                                    "error": format!("failed to type text, setting AXValue failed too: {}", e)
                                 })),
                             ));
                         }
                     }
                 }
 
+                // Step 3: Commit the value for fields that ignore it until confirmed
+                let confirm = request.confirm_after.unwrap_or_else(|| needs_confirm(element));
+                let confirmed = confirm && confirm_value(element).await;
+
+                // Read the value back so the caller can check what the field actually holds
+                let committed_value = element.attributes().value;
+                let value_matches = committed_value.as_ref().map(|value| value.contains(&request.text));
+
                 // Create the success response based on which method worked
-                let method_used = if input_control_success { "AppleScript" } else { "AXValue" };
+                let method_used = match (input_control_success, method) {
+                    (true, TypingMethod::Paste) => "paste",
+                    (true, _) => "keystroke",
+                    (false, _) => "AXValue",
+                };
                 let type_response = TypeByIndexResponse {
                     success: true,
                     message: format!(
-                        "successfully typed text into element with role: {} (using {} method)",
-                        element.role(), method_used
+                        "successfully typed text into element with role: {} (using {} method{})",
+                        element.role(), method_used, if confirmed { ", confirmed" } else { "" }
                     ),
+                    confirmed,
+                    committed_value,
+                    value_matches,
+                    focus_warning,
                 };
                 
                 // Get refreshed elements using the helper function
-                let elements_response = refresh_elements_and_attributes_after_action(state, app_name.clone(), 500).await;
+                let elements_response = refresh_after_action(&state, &app_name, &request.options).await;
+                let ui_state_changed = detect_ui_change(&state, &app_name, fingerprint_before, elements_response.is_some()).await;
                 
                 // Return combined response
                 Ok(JsonResponse(TypeByIndexWithElementsResponse {
                     type_action: type_response,
                     elements: elements_response,
+                    ui_state_changed,
+                    focus: focus_guard.report().await,
+                    screenshot: capture_action_screenshot(&app_name, &request.options.screenshot).await,
                 }))
             } else {
                 error!(
@@ -209,3 +275,59 @@ pub async fn type_by_index_handler(
         }
     }
 }
+
+// Roles and subroles that accept an AXValue but only act on it once confirmed
+const CONFIRM_ROLES: [&str; 3] = ["AXComboBox", "AXSearchField", "AXURLField"];
+
+pub fn needs_confirm(element: &UIElement) -> bool {
+    let attrs = element.attributes();
+    let subrole = attrs
+        .properties
+        .get("AXSubrole")
+        .and_then(|v| v.as_ref())
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    CONFIRM_ROLES.contains(&attrs.role.as_str()) || CONFIRM_ROLES.contains(&subrole.as_str())
+}
+
+// Commit the typed value with AXConfirm, falling back to pressing Return
+pub async fn confirm_value(element: &UIElement) -> bool {
+    match element.perform_action("AXConfirm") {
+        Ok(_) => {
+            debug!("confirmed value with AXConfirm");
+            true
+        }
+        Err(e) => {
+            debug!("AXConfirm failed: {} - falling back to Return", e);
+            match run_osascript("tell application \"System Events\" to key code 36").await {
+                Ok(_) => {
+                    debug!("confirmed value with Return");
+                    true
+                }
+                Err(e) => {
+                    error!("failed to press Return: {}", e);
+                    false
+                }
+            }
+        }
+    }
+}
+
+// How far up from the focused element to look for the target; text views usually take
+// focus in a child of the listed element (a field editor or the text area in a scroll view)
+const MAX_FOCUS_DEPTH: usize = 4;
+
+// Whether the keyboard focus is on the element or inside it (None if focus can't be read)
+pub fn focus_lands_on(desktop: &Desktop, element: &UIElement) -> Option<bool> {
+    let mut current = Some(desktop.focused_element().ok()?);
+    for _ in 0..=MAX_FOCUS_DEPTH {
+        let Some(candidate) = current else { break };
+        if candidate == *element {
+            return Some(true);
+        }
+        current = candidate.parent().ok().flatten();
+    }
+    Some(false)
+}
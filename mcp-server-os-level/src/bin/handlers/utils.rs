@@ -1,12 +1,21 @@
-use std::sync::Arc;
-use axum::extract::{Json, State};
+use std::{collections::HashMap, fmt, process::Output, sync::Arc};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use computer_use_ai_sdk::{AutomationError, Desktop, MouseButton, Selector, UIElement};
 use tokio::time::Duration;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 use crate::types::*;
 use crate::AppState;
+use crate::config::ServerConfig;
 
 use super::list_elements_and_attributes::list_elements_and_attributes_handler;
+use super::press_key_by_index::convert_key_combo_to_applescript;
 
 
 pub async fn refresh_elements_and_attributes_after_action(
@@ -18,13 +27,18 @@ pub async fn refresh_elements_and_attributes_after_action(
     info!("waiting for UI to update after action before listing elements and attributes");
     tokio::time::sleep(Duration::from_millis(delay_ms)).await;
     
-    // Create request for list elements and attributes
-    let list_request = ListInteractableElementsRequest {
+    // List the app the way it was last listed, since a listing with other filters or order
+    // would never match the fingerprint taken before the action
+    let previous_request = state.listing_requests.lock().await.get(&app_name).cloned();
+    let list_request = previous_request.unwrap_or_else(|| ListInteractableElementsRequest {
         app_name,
         max_elements: None,
         use_background_apps: Some(false),
         activate_app: Some(true),
-    };
+        ..Default::default()
+    });
+    // Handlers search and return this listing, which a summary would leave empty
+    let list_request = ListInteractableElementsRequest { summarize_above: Some(0), ..list_request };
     
     // Call the handler to get fresh elements
     match list_elements_and_attributes_handler(State(state), Json(list_request)).await {
@@ -35,3 +49,1037 @@ pub async fn refresh_elements_and_attributes_after_action(
         }
     }
 }
+
+// The listing after an action, waiting and skipping it as the request's ActionOptions say
+pub async fn refresh_after_action(state: &Arc<AppState>, app_name: &str, options: &ActionOptions) -> Option<ListElementsAndAttributesResponse> {
+    if options.refresh_elements == Some(false) {
+        return None;
+    }
+    refresh_elements_and_attributes_after_action(state.clone(), app_name.to_string(), state.config.refresh_delay_ms(options.delay_ms)).await
+}
+
+// Find a running app by bundle id when one is given (or app_name is one), otherwise by name.
+// Listings are cached under the bundle id, so a name that matches no app is also tried as a bundle id.
+pub fn resolve_application(desktop: &Desktop, app_name: &str, bundle_id: Option<&str>) -> Result<UIElement, AutomationError> {
+    if let Some(bundle_id) = requested_bundle_id(app_name, bundle_id) {
+        return desktop.application_by_bundle_id(bundle_id);
+    }
+    desktop
+        .application(app_name)
+        .or_else(|e| desktop.application_by_bundle_id(app_name).map_err(|_| e))
+}
+
+// The bundle id to target: the explicit one, or app_name itself when it's written as one
+pub fn requested_bundle_id<'a>(app_name: &'a str, bundle_id: Option<&'a str>) -> Option<&'a str> {
+    bundle_id
+        .filter(|bundle_id| !bundle_id.is_empty())
+        .or_else(|| Some(app_name).filter(|name| looks_like_bundle_id(name)))
+}
+
+// Reverse-DNS like com.apple.Safari. At least three parts, so app names with a dot in them
+// (draw.io, Node.js) still go by name
+fn looks_like_bundle_id(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() >= 3
+        && parts.iter().all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        && parts[0].starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+// Safe mode: refuse input to an app that isn't in SAFE_MODE_ALLOWED_APPS. The allow list may
+// name the app either way, so when the given name or bundle id isn't on it, look up the other.
+pub fn ensure_app_allowed(config: &ServerConfig, app_name: &str, bundle_id: Option<&str>) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    let mut identities: Vec<String> = [Some(app_name), bundle_id].into_iter().flatten().map(str::to_string).collect();
+    if config.is_app_allowed(&identities) {
+        return Ok(());
+    }
+
+    let resolved = Desktop::new(false, false)
+        .and_then(|desktop| resolve_application(&desktop, app_name, bundle_id).map(|app| app_identity(&desktop, &app)));
+    if let Ok((name, bundle_id)) = resolved {
+        identities.extend(name.into_iter().chain(bundle_id));
+        if config.is_app_allowed(&identities) {
+            return Ok(());
+        }
+    }
+
+    let target = bundle_id.filter(|id| !id.is_empty()).unwrap_or(app_name);
+    Err(app_not_allowed(config, if target.is_empty() { "an unknown app" } else { target }))
+}
+
+// The APP_NOT_ALLOWED error for input refused by safe mode
+pub fn app_not_allowed(config: &ServerConfig, target: &str) -> (StatusCode, JsonResponse<Value>) {
+    let mut allowed: Vec<&String> = config.safe_mode_allowed_apps.iter().flatten().collect();
+    allowed.sort();
+    error!("safe mode: refusing input to {}", target);
+    (
+        StatusCode::FORBIDDEN,
+        JsonResponse(json!({
+            "error": format!("APP_NOT_ALLOWED: {} is not in SAFE_MODE_ALLOWED_APPS - nothing was sent", target),
+            "code": "APP_NOT_ALLOWED",
+            "allowed_apps": allowed
+        })),
+    )
+}
+
+// Chromium (Chrome, Edge, Brave, Arc) and Electron apps (VS Code, Slack, Discord, Notion,
+// Obsidian, ...) only build their web content's accessibility tree once an assistive app
+// asks for it; until then a walk finds little more than the window frame. Chromium listens
+// for AXEnhancedUserInterface, Electron for AXManualAccessibility, so set both on the app.
+// Returns whether either was accepted. Enhanced mode is known to slow window animations
+// and confuse some window managers, so this is opt-in per request.
+pub async fn enable_enhanced_accessibility(app: &UIElement) -> bool {
+    let mut enabled = false;
+    for attribute in ["AXEnhancedUserInterface", "AXManualAccessibility"] {
+        match app.set_bool_attribute(attribute, true) {
+            Ok(()) => {
+                debug!("set {} on the app", attribute);
+                enabled = true;
+            }
+            Err(e) => debug!("app did not accept {}: {}", attribute, e),
+        }
+    }
+    if enabled {
+        // The tree is built asynchronously after the flag flips
+        settle(Duration::from_millis(500)).await;
+    }
+    enabled
+}
+
+// Display name and bundle id of an application element
+pub fn app_identity(desktop: &Desktop, app: &UIElement) -> (Option<String>, Option<String>) {
+    let name = app.attributes().label.filter(|name| !name.is_empty());
+    let bundle_id = app.process_id().ok().and_then(|pid| desktop.bundle_id(pid).ok().flatten());
+    (name, bundle_id)
+}
+
+// Wait between steps of an action without tying up the runtime worker, so other
+// requests keep being served while this one waits on the UI
+pub async fn settle(delay: Duration) {
+    debug!("waiting {:?} for the UI to settle", delay);
+    tokio::time::sleep(delay).await;
+}
+
+// Tries of a step that can fail transiently, e.g. while the app is mid-animation, and the
+// wait before the first retry (doubling for each further one)
+const RETRY_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// Run a fallible step up to RETRY_ATTEMPTS times with exponential backoff, returning the
+// last error when every try fails, or right away when `should_retry` says another try can't
+// help. Only for steps that are safe to repeat (lookups, setting focus), never for sending
+// a click or keystroke that may already have been delivered.
+pub async fn retry_with_backoff<T, E, F, Fut>(what: &str, should_retry: impl Fn(&E) -> bool, mut attempt: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: fmt::Debug,
+{
+    let mut delay = RETRY_DELAY;
+    for tried in 1..RETRY_ATTEMPTS {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if !should_retry(&e) => return Err(e),
+            Err(e) => {
+                debug!("{} failed (try {}/{}): {:?} - retrying in {:?}", what, tried, RETRY_ATTEMPTS, e, delay);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+    attempt().await
+}
+
+// Whether a failed accessibility step might succeed on another try; a missing permission or
+// a bad argument won't
+pub fn is_transient(e: &AutomationError) -> bool {
+    !matches!(
+        e,
+        AutomationError::PermissionDenied(_)
+            | AutomationError::InvalidArgument(_)
+            | AutomationError::UnsupportedOperation(_)
+            | AutomationError::UnsupportedPlatform(_)
+    )
+}
+
+// Fingerprint of the last listing of this app
+pub async fn last_ui_fingerprint(state: &AppState, app_name: &str) -> Option<u64> {
+    state.ui_fingerprint.lock().await.get(app_name).copied()
+}
+
+// Whether the listing refreshed after an action differs from the one before it, telling
+// /mcp/ws clients when it does. None when there was no earlier listing of the app or the
+// refresh failed.
+pub async fn detect_ui_change(state: &AppState, app_name: &str, before: Option<u64>, refreshed: bool) -> Option<bool> {
+    if !refreshed {
+        return None;
+    }
+    let after = last_ui_fingerprint(state, app_name).await?;
+    let changed = before.map(|before| before != after);
+    if changed == Some(true) {
+        // Fails only when no client is connected, which is fine
+        let _ = state.mcp_notifications.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/ui_changed",
+            "params": {"app": app_name}
+        }));
+    }
+    changed
+}
+
+// Per-element hashes of the last listing of `app_name`
+pub async fn last_ui_snapshot(state: &AppState, app_name: &str) -> Option<HashMap<String, u64>> {
+    state.ui_snapshot.lock().await.get(app_name).cloned()
+}
+
+// What changed between the listing before an action and the one refreshed after it.
+// None under the same conditions as detect_ui_change
+pub async fn detect_ui_diff(
+    state: &AppState,
+    app_name: &str,
+    before: Option<HashMap<String, u64>>,
+    refreshed: bool,
+) -> Option<UiDiff> {
+    if !refreshed {
+        return None;
+    }
+    let after = last_ui_snapshot(state, app_name).await?;
+    before.map(|before| diff_ui_snapshots(&before, &after))
+}
+
+fn diff_ui_snapshots(before: &HashMap<String, u64>, after: &HashMap<String, u64>) -> UiDiff {
+    let removed = before.keys().filter(|id| !after.contains_key(*id)).count();
+    let mut added = 0;
+    let mut changed = 0;
+    for (id, hash) in after {
+        match before.get(id) {
+            None => added += 1,
+            Some(previous) if previous != hash => changed += 1,
+            Some(_) => {}
+        }
+    }
+
+    let summary = if added + removed + changed == 0 {
+        "no elements added, removed or changed".to_string()
+    } else {
+        format!("{} elements added, {} removed, {} changed", added, removed, changed)
+    };
+    UiDiff { added, removed, changed, summary }
+}
+
+// Periodically drop cached element handles nobody has refreshed within the idle timeout
+pub async fn sweep_idle_caches(state: Arc<AppState>, idle_timeout: Duration) {
+    // Check often enough that entries don't outlive the timeout by much
+    let period = (idle_timeout / 4).clamp(Duration::from_secs(5), Duration::from_secs(60));
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        let reclaimed = reclaim_idle_handles(&state, idle_timeout).await;
+        if reclaimed > 0 {
+            info!("reclaimed {} idle element handles", reclaimed);
+        }
+    }
+}
+
+// Drop element listings and the menu bar cache older than `idle_timeout`, along with what went
+// with the dropped listings, returning how many element handles were released
+async fn reclaim_idle_handles(state: &AppState, idle_timeout: Duration) -> usize {
+    let mut reclaimed = 0;
+    let mut dropped = Vec::new();
+    {
+        let mut cache = state.element_cache.lock().await;
+        cache.listings.retain(|key, listing| {
+            let idle = listing.timestamp.elapsed() >= idle_timeout;
+            if idle {
+                reclaimed += listing.elements.len();
+                dropped.push(key.clone());
+            }
+            !idle
+        });
+    }
+    forget_listings(state, &dropped).await;
+    {
+        let mut menu_bar_cache = state.menu_bar_cache.lock().await;
+        if let Some((items, timestamp)) = &*menu_bar_cache {
+            if timestamp.elapsed() >= idle_timeout {
+                reclaimed += items.len();
+                *menu_bar_cache = None;
+            }
+        }
+    }
+    reclaimed
+}
+
+// Forget what went with listings removed from the element cache: their stable ids (so the
+// next listing indexes from scratch), the UI fingerprint and a cross-app listing's owners
+pub async fn forget_listings(state: &AppState, keys: &[String]) {
+    {
+        let mut element_ids = state.element_ids.lock().await;
+        let mut fingerprint = state.ui_fingerprint.lock().await;
+        let mut snapshot = state.ui_snapshot.lock().await;
+        let mut listing_requests = state.listing_requests.lock().await;
+        for key in keys {
+            element_ids.remove(key);
+            fingerprint.remove(key);
+            snapshot.remove(key);
+            listing_requests.remove(key);
+        }
+    }
+    if keys.iter().any(|key| key == ALL_APPS_KEY) {
+        state.element_owners.lock().await.clear();
+    }
+}
+
+// Cache key of a listing that spans several apps (see listAllElements)
+pub const ALL_APPS_KEY: &str = "*";
+
+// App an element of a listing belongs to: the cache key, or for a cross-app
+// listing the element's own app
+pub async fn element_owner(state: &AppState, element_index: usize, cache_key: &str) -> String {
+    if cache_key != ALL_APPS_KEY {
+        return cache_key.to_string();
+    }
+    let owners = state.element_owners.lock().await;
+    owners.get(element_index).cloned().unwrap_or_else(|| cache_key.to_string())
+}
+
+// Look up an element from the listing of `app_name` (default: the last listing), returning
+// it with the app it belongs to
+pub async fn cached_element_by_index(
+    state: &AppState,
+    element_index: usize,
+    app_name: Option<&str>,
+) -> Result<(UIElement, String), (StatusCode, JsonResponse<Value>)> {
+    let listing = state.element_cache.lock().await.get(app_name);
+    match listing {
+        Some((elements, timestamp, cache_key)) if timestamp.elapsed() < state.config.element_cache_ttl => {
+            match elements.get(element_index) {
+                Some(element) => Ok((element.clone(), element_owner(state, element_index, &cache_key).await)),
+                None => {
+                    error!("element index out of bounds: {} (max: {})", element_index, elements.len().saturating_sub(1));
+                    Err((
+                        StatusCode::BAD_REQUEST,
+                        JsonResponse(json!({
+                            "error": format!("element index out of bounds: {} (max: {})",
+                                            element_index, elements.len().saturating_sub(1))
+                        })),
+                    ))
+                }
+            }
+        }
+        Some(_) => Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": "cache entry expired, please list elements again"
+            })),
+        )),
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": match app_name {
+                    Some(app_name) => format!("no element cache found for {} - list its elements with listInteractableElementsByIndex first", app_name),
+                    None => "no element cache found - you must call listInteractableElementsByIndex first to index the elements before using by-index operations".to_string(),
+                }
+            })),
+        )),
+    }
+}
+
+// Index of the element a listing gave this element_id, for the by-id tools; ids are kept
+// per listing, so the lookup is against the same listing by-index calls would use
+pub async fn cached_index_by_id(
+    state: &AppState,
+    element_id: &str,
+    app_name: Option<&str>,
+) -> Result<usize, (StatusCode, JsonResponse<Value>)> {
+    let Some((_, _, cache_key)) = state.element_cache.lock().await.get(app_name) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": "no element cache found - you must call listInteractableElementsByIndex first to get element ids"
+            })),
+        ));
+    };
+    let index = state
+        .element_ids
+        .lock()
+        .await
+        .get(&cache_key)
+        .and_then(|ids| ids.iter().position(|id| id == element_id));
+    index.ok_or_else(|| {
+        error!("element id {} not found in the listing of {}", element_id, cache_key);
+        (
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({
+                "error": format!("element id {} not found - the element is gone or changed, list the elements again", element_id)
+            })),
+        )
+    })
+}
+
+// Check an element from the list response against the role/text predicate
+pub fn element_matches(element: &Value, role: Option<&str>, text: Option<&str>) -> bool {
+    if let Some(role) = role {
+        if !role_matches(element, role) {
+            return false;
+        }
+    }
+
+    if let Some(text) = text {
+        let element_text = element["text"].as_str().unwrap_or_default();
+        if !element_text.to_lowercase().contains(&text.to_lowercase()) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// Listed roles look like "AXButton" or "AXButton (button)"; accept either "AXButton" or "button"
+pub fn role_matches(element: &Value, role: &str) -> bool {
+    let element_role = element["role"].as_str().unwrap_or_default();
+    let raw_role = element_role.split(" (").next().unwrap_or_default();
+    raw_role.trim_start_matches("AX").eq_ignore_ascii_case(role.trim_start_matches("AX"))
+}
+
+// Give an element the keyboard focus the requested way. Accessibility focus is checked
+// afterwards, since some elements accept the attribute write without taking focus.
+pub fn focus_element(element: &UIElement, method: FocusMethod) -> Result<(), AutomationError> {
+    match method {
+        FocusMethod::Click => element.click().map(|_| ()),
+        FocusMethod::Accessibility => {
+            if element.set_bool_attribute("AXFocused", true).is_err() {
+                element.focus()?;
+            }
+            match element.is_focused() {
+                Ok(false) => Err(AutomationError::PlatformError(format!(
+                    "{} did not take accessibility focus",
+                    element.role()
+                ))),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+// The point coordinate clicks land on: the center of the element's bounds, rounded to whole points
+pub fn click_point(bounds: (f64, f64, f64, f64)) -> ScreenPoint {
+    let (x, y, width, height) = bounds;
+    ScreenPoint {
+        x: (x + width / 2.0).round(),
+        y: (y + height / 2.0).round(),
+    }
+}
+
+// Capture the app's front window for an action response when the request asked for it.
+// Failures are logged and leave the screenshot out rather than failing the action.
+pub async fn capture_action_screenshot(app_name: &str, options: &ScreenshotOptions) -> Option<ActionScreenshot> {
+    if !options.include_screenshot {
+        return None;
+    }
+
+    let window = Desktop::new(false, false)
+        .and_then(|desktop| resolve_application(&desktop, app_name, None))
+        .and_then(|app| Selector::query("role=AXWindow").and_then(|selector| app.locator(selector)))
+        .and_then(|locator| locator.first());
+    let window = match window {
+        Ok(Some(window)) => window,
+        Ok(None) => {
+            error!("no window found to screenshot for {}", app_name);
+            return None;
+        }
+        Err(e) => {
+            error!("failed to find window to screenshot for {}: {}", app_name, e);
+            return None;
+        }
+    };
+
+    let png = match window.screenshot() {
+        Ok(png) => png,
+        Err(e) => {
+            error!("failed to capture screenshot of {}: {}", app_name, e);
+            return None;
+        }
+    };
+
+    match shrink_screenshot(png, options).await {
+        Ok((image, format)) => Some(ActionScreenshot {
+            format: format.to_string(),
+            image_base64: STANDARD.encode(image),
+        }),
+        Err(e) => {
+            error!("failed to downscale screenshot of {}: {}", app_name, e);
+            None
+        }
+    }
+}
+
+// Bounds of an app's front window in screen points
+pub fn front_window_bounds(app_name: &str, bundle_id: Option<&str>) -> Result<ElementBounds, (StatusCode, JsonResponse<Value>)> {
+    let window = Desktop::new(false, false)
+        .and_then(|desktop| resolve_application(&desktop, app_name, bundle_id))
+        .and_then(|app| Selector::query("role=AXWindow").and_then(|selector| app.locator(selector)))
+        .and_then(|locator| locator.first());
+    match window.map(|window| window.map(|w| w.bounds())) {
+        Ok(Some(Ok((x, y, width, height)))) => Ok(ElementBounds { x, y, width, height }),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("no window found for {}", bundle_id.unwrap_or(app_name))})),
+        )),
+        Err(e) => {
+            error!("failed to find window to capture: {}", e);
+            Err((
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": format!("failed to find window: {}", e)})),
+            ))
+        }
+    }
+}
+
+// Capture the screen to a PNG at `path`: just `region` if given, else the whole of `display`
+// (numbered like screencapture -D, 1 = main), else the main display
+pub async fn screencapture(path: &str, region: Option<ElementBounds>, display: Option<u32>) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    let mut command = tokio::process::Command::new("screencapture");
+    command.arg("-x");
+    match region {
+        Some(r) => {
+            command.arg(format!("-R{},{},{},{}", r.x.round(), r.y.round(), r.width.round(), r.height.round()));
+        }
+        None => match display {
+            Some(display) => {
+                command.arg(format!("-D{}", display));
+            }
+            None => {
+                command.arg("-m");
+            }
+        },
+    }
+    let captured = command.arg(path).output().await;
+    match captured {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            error!("screencapture failed: {}", stderr);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({
+                    "error": format!("failed to capture the screen: {} - grant this server Screen Recording permission (System Settings > Privacy & Security > Screen Recording)", stderr)
+                })),
+            ));
+        }
+        Err(e) => {
+            error!("failed to run screencapture: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to run screencapture: {}", e)})),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Downscale and/or re-encode a PNG with sips to bound the response size
+pub async fn shrink_screenshot(png: Vec<u8>, options: &ScreenshotOptions) -> Result<(Vec<u8>, &'static str), String> {
+    let resample_width = options
+        .screenshot_max_width
+        .filter(|max| png_width(&png).map_or(true, |width| width > *max));
+    let quality = options.screenshot_quality.map(|q| q.clamp(1, 100));
+    if resample_width.is_none() && quality.is_none() {
+        return Ok((png, "png"));
+    }
+
+    let id = uuid::Uuid::new_v4();
+    let input = std::env::temp_dir().join(format!("action-{}.png", id));
+    let output = std::env::temp_dir().join(format!("action-{}-out", id));
+    tokio::fs::write(&input, &png).await.map_err(|e| e.to_string())?;
+
+    let mut command = tokio::process::Command::new("sips");
+    if let Some(width) = resample_width {
+        command.arg("--resampleWidth").arg(width.to_string());
+    }
+    if let Some(quality) = quality {
+        command.args(["-s", "format", "jpeg", "-s", "formatOptions"]).arg(quality.to_string());
+    }
+    let result = match command.arg(&input).arg("--out").arg(&output).output().await {
+        Ok(out) if out.status.success() => tokio::fs::read(&output).await.map_err(|e| e.to_string()),
+        Ok(out) => Err(String::from_utf8_lossy(&out.stderr).trim().to_string()),
+        Err(e) => Err(format!("failed to run sips: {}", e)),
+    };
+
+    let _ = tokio::fs::remove_file(&input).await;
+    let _ = tokio::fs::remove_file(&output).await;
+    result.map(|image| (image, if quality.is_some() { "jpeg" } else { "png" }))
+}
+
+// Width from the PNG IHDR chunk, which always directly follows the signature
+fn png_width(png: &[u8]) -> Option<u32> {
+    png.get(16..20).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// Remembers the frontmost app when a handler starts so the response can report
+// whether focus moved, and with `restore_focus` reactivates it when the handler
+// returns (on success or error). Restoring costs the app switch itself plus an
+// osascript round trip, so it's opt-in.
+pub struct FocusGuard {
+    previous_app: Option<String>,
+    restore: bool,
+}
+
+impl FocusGuard {
+    pub async fn capture(restore_focus: bool) -> Self {
+        let previous_app = frontmost_app_name().await;
+        if restore_focus {
+            if let Some(app) = &previous_app {
+                debug!("will restore focus to {} after action", app);
+            }
+        }
+        Self { previous_app, restore: restore_focus }
+    }
+
+    // Compare the frontmost app now against the one seen when the guard was created
+    pub async fn report(&self) -> FocusChange {
+        let new_app = frontmost_app_name().await;
+        FocusChange {
+            focus_stolen: matches!((&self.previous_app, &new_app), (Some(previous), Some(new)) if previous != new),
+            previous_frontmost_app: self.previous_app.clone(),
+            new_frontmost_app: new_app,
+        }
+    }
+}
+
+impl Drop for FocusGuard {
+    fn drop(&mut self) {
+        if !self.restore {
+            return;
+        }
+        if let Some(app) = self.previous_app.take() {
+            info!("restoring focus to {}", app);
+            let script = format!("tell application \"{}\" to activate", escape_applescript_string(&app));
+            // Drop can't await, so hand the activation to the runtime
+            tokio::spawn(async move {
+                if let Err(e) = run_osascript(&script).await {
+                    error!("failed to restore focus to {}: {}", app, e);
+                }
+            });
+        }
+    }
+}
+
+// Name of the application currently in front
+pub async fn frontmost_app_name() -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        // The app owning the focused element, by process id
+        return tokio::task::spawn_blocking(|| {
+            let desktop = Desktop::new(false, false).ok()?;
+            let pid = desktop.focused_element().ok()?.process_id().ok()?;
+            let app = desktop.applications().ok()?.into_iter().find(|app| app.process_id().ok() == Some(pid))?;
+            app.attributes().label
+        })
+        .await
+        .ok()
+        .flatten()
+        .filter(|name| !name.is_empty());
+    }
+    run_osascript("tell application \"System Events\" to get name of first application process whose frontmost is true")
+        .await
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+// Keyboard and mouse input for whatever is in front. On macOS keys and text go through
+// System Events, which follows the user's keyboard layout; elsewhere the SDK posts them
+// (SendInput on Windows). The mouse always goes through the SDK.
+pub async fn send_key_combo(key_combo: &str) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    if cfg!(target_os = "macos") {
+        return run_osascript(&convert_key_combo_to_applescript(key_combo)).await.map(|_| ()).map_err(|e| {
+            error!("failed to press {}: {}", key_combo, e);
+            e.to_response()
+        });
+    }
+    let key_combo = key_combo.to_string();
+    sdk_input(&format!("pressing {}", key_combo), move |desktop| desktop.press_key_combo(&key_combo)).await
+}
+
+pub async fn send_text(text: &str) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    if cfg!(target_os = "macos") {
+        let script = format!("tell application \"System Events\" to keystroke \"{}\"", escape_applescript_string(text));
+        return run_osascript(&script).await.map(|_| ()).map_err(|e| {
+            error!("failed to type text: {}", e);
+            e.to_response()
+        });
+    }
+    let text = text.to_string();
+    sdk_input("typing text", move |desktop| desktop.type_text(&text)).await
+}
+
+pub async fn send_mouse_click(at: Option<(f64, f64)>, button: MouseButton, click_count: u32) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    sdk_input("clicking the mouse", move |desktop| desktop.click_mouse(at, button, click_count)).await
+}
+
+pub async fn send_mouse_move(to: (f64, f64)) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    sdk_input("moving the mouse", move |desktop| desktop.move_mouse(to)).await
+}
+
+// Post input through the SDK off the async workers
+async fn sdk_input<F>(what: &str, input: F) -> Result<(), (StatusCode, JsonResponse<Value>)>
+where
+    F: FnOnce(&Desktop) -> Result<(), AutomationError> + Send + 'static,
+{
+    let result = tokio::task::spawn_blocking(move || Desktop::new(false, false).and_then(|desktop| input(&desktop)))
+        .await
+        .unwrap_or_else(|e| Err(AutomationError::Internal(e.to_string())));
+    result.map_err(|e| {
+        error!("failed {}: {}", what, e);
+        let status = match e {
+            AutomationError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            AutomationError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, JsonResponse(json!({"error": format!("failed {}: {}", what, e)})))
+    })
+}
+
+const AUTOMATION_PERMISSION_GUIDANCE: &str = "grant this server Automation permission for System Events \
+     (System Settings > Privacy & Security > Automation) and Accessibility permission, then retry";
+
+// Why an AppleScript run through osascript failed
+#[derive(Debug)]
+pub enum OsascriptError {
+    // Missing Automation/Accessibility permission to drive System Events
+    PermissionDenied(String),
+    Failed(String),
+}
+
+impl fmt::Display for OsascriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OsascriptError::PermissionDenied(msg) => write!(f, "PERMISSION_DENIED: {} - {}", msg, AUTOMATION_PERMISSION_GUIDANCE),
+            OsascriptError::Failed(msg) => write!(f, "applescript failed: {}", msg),
+        }
+    }
+}
+
+impl OsascriptError {
+    // Error response for handlers to return as-is
+    pub fn to_response(&self) -> (StatusCode, JsonResponse<Value>) {
+        match self {
+            OsascriptError::PermissionDenied(_) => (
+                StatusCode::FORBIDDEN,
+                JsonResponse(json!({
+                    "error": self.to_string(),
+                    "code": "PERMISSION_DENIED",
+                    "guidance": AUTOMATION_PERMISSION_GUIDANCE
+                })),
+            ),
+            OsascriptError::Failed(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": self.to_string()})),
+            ),
+        }
+    }
+}
+
+// Run an AppleScript and return its trimmed stdout, treating a non-zero exit as failure
+pub async fn run_osascript(script: &str) -> Result<String, OsascriptError> {
+    run_osascript_args(&["-e", script]).await
+}
+
+// Make text safe to put between double quotes in an AppleScript string literal: backslashes
+// and quotes are escaped, newlines/returns/tabs become their escapes, and other control
+// characters (which keystroke can't type anyway) are dropped
+pub fn escape_applescript_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Run a JavaScript for Automation script, whose `run(argv)` receives `args`. JXA reaches
+// Cocoa frameworks (Vision, AppKit) through its ObjC bridge, which AppleScript can't.
+pub async fn run_jxa(script: &str, args: &[&str]) -> Result<String, OsascriptError> {
+    let mut osascript_args = vec!["-l", "JavaScript", "-e", script];
+    osascript_args.extend_from_slice(args);
+    run_osascript_args(&osascript_args).await
+}
+
+async fn run_osascript_args(args: &[&str]) -> Result<String, OsascriptError> {
+    // Elsewhere the handlers' AppleScript paths fail over to their accessibility fallbacks,
+    // which the SDK implements per platform
+    if !cfg!(target_os = "macos") {
+        return Err(OsascriptError::Failed("osascript is only available on macOS".to_string()));
+    }
+    let output = command_output("osascript", args)
+        .await
+        .map_err(|e| OsascriptError::Failed(format!("failed to run osascript: {}", e)))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        debug!("osascript failed: {}", stderr.trim());
+        Err(classify_osascript_error(&stderr))
+    }
+}
+
+// Run an AppleScript and return its raw output whatever the exit status, killing
+// osascript if it runs longer than `timeout`
+pub async fn run_osascript_output(script: &str, timeout: Duration) -> Result<Output, OsascriptError> {
+    if !cfg!(target_os = "macos") {
+        return Err(OsascriptError::Failed("osascript is only available on macOS".to_string()));
+    }
+    let child = tokio::process::Command::new("osascript")
+        .args(["-e", script])
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(timeout, child).await {
+        Ok(output) => output.map_err(|e| OsascriptError::Failed(format!("failed to run osascript: {}", e))),
+        Err(_) => Err(OsascriptError::Failed(format!("script timed out after {}ms", timeout.as_millis()))),
+    }
+}
+
+// Run a process without blocking the runtime worker while it executes
+pub async fn command_output(program: &str, args: &[&str]) -> std::io::Result<Output> {
+    tokio::process::Command::new(program).args(args).output().await
+}
+
+// Tell a missing permission apart from any other script error
+fn classify_osascript_error(stderr: &str) -> OsascriptError {
+    let message = stderr.trim().to_string();
+    let lowercase = message.to_lowercase();
+    // -1719 on its own is just an invalid index, e.g. asking for a window that isn't there;
+    // it only means a missing permission alongside the assistive access message
+    let permission_denied = message.contains("-1743")
+        || lowercase.contains("not allowed to send keystrokes")
+        || lowercase.contains("not authorized to send apple events")
+        || lowercase.contains("assistive access");
+
+    if permission_denied {
+        OsascriptError::PermissionDenied(message)
+    } else {
+        OsascriptError::Failed(message)
+    }
+}
+
+// Empty state for handler tests
+#[cfg(test)]
+pub fn test_state() -> AppState {
+    AppState {
+        element_cache: Arc::new(tokio::sync::Mutex::new(crate::types::ElementCache::default())),
+        element_ids: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        ui_fingerprint: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        ui_snapshot: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        listing_requests: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        menu_bar_cache: Arc::new(tokio::sync::Mutex::new(None)),
+        element_owners: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+        mcp_notifications: tokio::sync::broadcast::channel(8).0,
+        config: crate::config::ServerConfig::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_applescript_strings() {
+        assert_eq!(escape_applescript_string(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_applescript_string(r"C:\temp\"), r"C:\\temp\\");
+        assert_eq!(escape_applescript_string("a\" & quit & \"b"), r#"a\" & quit & \"b"#);
+        assert_eq!(escape_applescript_string("line one\nline two\t!"), r"line one\nline two\t!");
+        assert_eq!(escape_applescript_string("bell\u{7}\u{1b}[0m"), "bell[0m");
+        assert_eq!(escape_applescript_string("ship it 🚀 👍🏽"), "ship it 🚀 👍🏽");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn concurrent_settles_do_not_serialize() {
+        // On a single-threaded runtime a blocking sleep would run these back to back
+        let delay = Duration::from_millis(200);
+        let start = std::time::Instant::now();
+        tokio::join!(settle(delay), settle(delay), settle(delay));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= delay);
+        assert!(elapsed < delay * 2, "settles ran serially: {:?}", elapsed);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn retries_until_a_step_succeeds_or_tries_run_out() {
+        let mut tries = 0;
+        let result: Result<u32, &str> = retry_with_backoff("flaky step", |_| true, || {
+            tries += 1;
+            let outcome = if tries < 3 { Err("not yet") } else { Ok(tries) };
+            async move { outcome }
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        let mut tries = 0;
+        let result: Result<(), &str> = retry_with_backoff("failing step", |_| true, || {
+            tries += 1;
+            async { Err("never") }
+        })
+        .await;
+        assert_eq!(result, Err("never"));
+        assert_eq!(tries, RETRY_ATTEMPTS);
+
+        // Errors another try can't fix are returned after the first
+        let mut tries = 0;
+        let result: Result<(), AutomationError> = retry_with_backoff("denied step", is_transient, || {
+            tries += 1;
+            async { Err(AutomationError::PermissionDenied("no accessibility access".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(AutomationError::PermissionDenied(_))));
+        assert_eq!(tries, 1);
+    }
+
+    #[test]
+    fn ui_diff_counts_elements_by_id() {
+        let before = HashMap::from([("send".to_string(), 1), ("draft".to_string(), 2), ("subject".to_string(), 3)]);
+        let after = HashMap::from([("send".to_string(), 1), ("subject".to_string(), 4), ("sent".to_string(), 5), ("undo".to_string(), 6)]);
+        let diff = diff_ui_snapshots(&before, &after);
+        assert_eq!((diff.added, diff.removed, diff.changed), (2, 1, 1));
+        assert_eq!(diff.summary, "2 elements added, 1 removed, 1 changed");
+
+        let unchanged = diff_ui_snapshots(&before, &before);
+        assert_eq!((unchanged.added, unchanged.removed, unchanged.changed), (0, 0, 0));
+        assert_eq!(unchanged.summary, "no elements added, removed or changed");
+    }
+
+    #[test]
+    fn reverse_dns_app_names_are_bundle_ids() {
+        assert_eq!(requested_bundle_id("com.apple.Safari", None), Some("com.apple.Safari"));
+        assert_eq!(requested_bundle_id("com.microsoft.VSCode", Some("")), Some("com.microsoft.VSCode"));
+        assert_eq!(requested_bundle_id("Safari", Some("com.apple.Safari")), Some("com.apple.Safari"));
+        assert_eq!(requested_bundle_id("Visual Studio Code", None), None);
+        assert_eq!(requested_bundle_id("draw.io", None), None);
+        assert_eq!(requested_bundle_id("Node.js", None), None);
+        assert_eq!(requested_bundle_id("1.2.3", None), None);
+        assert_eq!(requested_bundle_id("My App.v2.beta", None), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reclaims_only_idle_caches() {
+        let state = test_state();
+        let idle_timeout = Duration::from_secs(60);
+
+        let fresh = std::time::Instant::now();
+        state.element_cache.lock().await.insert("com.apple.finder".to_string(), "Finder".to_string(), Vec::new());
+        assert_eq!(reclaim_idle_handles(&state, idle_timeout).await, 0);
+        assert!(state.element_cache.lock().await.get(Some("Finder")).is_some());
+
+        let stale = fresh - Duration::from_secs(120);
+        state.element_cache.lock().await.insert(ALL_APPS_KEY.to_string(), ALL_APPS_KEY.to_string(), Vec::new());
+        state.element_cache.lock().await.listings.get_mut(ALL_APPS_KEY).unwrap().timestamp = stale;
+        *state.element_owners.lock().await = vec!["com.apple.finder".to_string()];
+        state.element_ids.lock().await.insert(ALL_APPS_KEY.to_string(), vec!["a".to_string()]);
+        state.element_ids.lock().await.insert("com.apple.finder".to_string(), vec!["b".to_string()]);
+        state.ui_fingerprint.lock().await.insert(ALL_APPS_KEY.to_string(), 1);
+        *state.menu_bar_cache.lock().await = Some((Vec::new(), stale));
+        reclaim_idle_handles(&state, idle_timeout).await;
+        assert!(state.element_cache.lock().await.get(None).is_none());
+        assert!(state.element_cache.lock().await.get(Some("com.apple.finder")).is_some());
+        assert!(state.menu_bar_cache.lock().await.is_none());
+        assert!(state.element_owners.lock().await.is_empty());
+        assert!(state.element_ids.lock().await.get(ALL_APPS_KEY).is_none());
+        assert!(state.element_ids.lock().await.get("com.apple.finder").is_some());
+        assert_eq!(last_ui_fingerprint(&state, ALL_APPS_KEY).await, None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn forgets_state_of_cleared_listings_only() {
+        let state = test_state();
+        {
+            let mut cache = state.element_cache.lock().await;
+            cache.insert("com.apple.finder".to_string(), "Finder".to_string(), Vec::new());
+            cache.insert("com.apple.Safari".to_string(), "Safari".to_string(), Vec::new());
+        }
+        state.element_ids.lock().await.insert("com.apple.finder".to_string(), vec!["a".to_string()]);
+        state.element_ids.lock().await.insert("com.apple.Safari".to_string(), vec!["b".to_string()]);
+        state.ui_fingerprint.lock().await.insert("com.apple.finder".to_string(), 1);
+        state.ui_fingerprint.lock().await.insert("com.apple.Safari".to_string(), 2);
+        state.ui_snapshot.lock().await.insert("com.apple.finder".to_string(), HashMap::new());
+
+        let cleared = state.element_cache.lock().await.remove_app(&["finder"]);
+        assert_eq!(cleared, vec!["com.apple.finder".to_string()]);
+        forget_listings(&state, &cleared).await;
+        assert!(state.element_ids.lock().await.get("com.apple.finder").is_none());
+        assert!(state.element_ids.lock().await.get("com.apple.Safari").is_some());
+        assert_eq!(last_ui_fingerprint(&state, "com.apple.finder").await, None);
+        assert_eq!(last_ui_fingerprint(&state, "com.apple.Safari").await, Some(2));
+        assert!(state.ui_snapshot.lock().await.is_empty());
+
+        let cleared = state.element_cache.lock().await.clear();
+        forget_listings(&state, &cleared).await;
+        assert!(state.element_cache.lock().await.get(None).is_none());
+        assert!(state.element_ids.lock().await.is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn notifies_clients_of_ui_changes() {
+        let state = test_state();
+        let mut notifications = state.mcp_notifications.subscribe();
+        state.ui_fingerprint.lock().await.insert("com.apple.finder".to_string(), 2);
+
+        assert_eq!(detect_ui_change(&state, "com.apple.finder", Some(2), true).await, Some(false));
+        assert!(notifications.try_recv().is_err());
+
+        assert_eq!(detect_ui_change(&state, "com.apple.finder", Some(1), true).await, Some(true));
+        let notification = notifications.try_recv().unwrap();
+        assert_eq!(notification["method"], "notifications/ui_changed");
+        assert_eq!(notification["params"]["app"], "com.apple.finder");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn concurrent_commands_do_not_serialize() {
+        // A blocking spawn would run these one after another on a single worker
+        let start = std::time::Instant::now();
+        let (a, b, c) = tokio::join!(
+            command_output("sleep", &["0.3"]),
+            command_output("sleep", &["0.3"]),
+            command_output("sleep", &["0.3"]),
+        );
+        let elapsed = start.elapsed();
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        assert!(elapsed < Duration::from_millis(600), "commands ran serially: {:?}", elapsed);
+    }
+
+    #[test]
+    fn detects_not_authorized_apple_events() {
+        let stderr = "36:120: execution error: Not authorized to send Apple events to System Events. (-1743)\n";
+        assert!(matches!(classify_osascript_error(stderr), OsascriptError::PermissionDenied(_)));
+    }
+
+    #[test]
+    fn detects_keystroke_permission_error() {
+        let stderr = "0:52: execution error: System Events got an error: osascript is not allowed to send keystrokes. (1002)";
+        let err = classify_osascript_error(stderr);
+        assert!(matches!(err, OsascriptError::PermissionDenied(_)));
+        assert!(err.to_string().starts_with("PERMISSION_DENIED"));
+        assert_eq!(err.to_response().0, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn other_errors_are_not_permission_errors() {
+        let stderr = "0:30: syntax error: Expected end of line but found identifier. (-2741)";
+        assert!(matches!(classify_osascript_error(stderr), OsascriptError::Failed(_)));
+    }
+
+    #[test]
+    fn invalid_index_is_only_a_permission_error_with_assistive_access() {
+        let stderr = "0:60: execution error: System Events got an error: Can’t get window 1 of process \"Finder\". Invalid index. (-1719)";
+        assert!(matches!(classify_osascript_error(stderr), OsascriptError::Failed(_)));
+
+        let stderr = "0:60: execution error: System Events got an error: osascript is not allowed assistive access. (-1719)";
+        assert!(matches!(classify_osascript_error(stderr), OsascriptError::PermissionDenied(_)));
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use serde_json::{json, Value};
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::types::{AppState, ListInteractableElementsRequest, WaitForElementRequest, WaitForElementResponse};
+use crate::handlers::list_elements_and_attributes::list_elements_and_attributes_handler;
+use crate::handlers::utils::{element_matches, settle};
+
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const MAX_TIMEOUT_MS: u64 = 60_000;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 250;
+const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+// Re-list the app until an element matching the role/text appears, instead of guessing
+// how long the UI needs to settle. The matching listing becomes the cached one, so the
+// returned index works with the by-index tools right away.
+pub async fn wait_for_element_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WaitForElementRequest>,
+) -> Result<JsonResponse<WaitForElementResponse>, (StatusCode, JsonResponse<Value>)> {
+    if request.role.is_none() && request.text_contains.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either role or text_contains must be provided to wait for an element"})),
+        ));
+    }
+    let app_name = request.bundle_id.clone().unwrap_or_else(|| request.app_name.clone());
+    if app_name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "either app_name or bundle_id must be provided"})),
+        ));
+    }
+
+    let timeout = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).min(MAX_TIMEOUT_MS));
+    let interval = request.poll_interval_ms.unwrap_or(DEFAULT_POLL_INTERVAL_MS).max(MIN_POLL_INTERVAL_MS);
+    info!("waiting up to {:?} for role {:?} / text {:?} in {}", timeout, request.role, request.text_contains, app_name);
+
+    let start = Instant::now();
+    let mut attempts = 0;
+    loop {
+        if attempts > 0 {
+            settle(Duration::from_millis(interval)).await;
+        }
+        attempts += 1;
+        // List every element so the match isn't hidden by the default cap or a summary
+        let listed = list_elements_and_attributes_handler(
+            State(state.clone()),
+            Json(ListInteractableElementsRequest {
+                app_name: request.app_name.clone(),
+                bundle_id: request.bundle_id.clone(),
+                use_background_apps: Some(false),
+                activate_app: Some(true),
+                unbounded: Some(true),
+                ..Default::default()
+            }),
+        )
+        .await;
+
+        if let Ok(JsonResponse(list)) = listed {
+            let found = list
+                .elements
+                .iter()
+                .find(|e| element_matches(e, request.role.as_deref(), request.text_contains.as_deref()))
+                .cloned();
+            if let Some(element) = found {
+                let element_index = element["index"].as_u64().unwrap_or_default() as usize;
+                info!("found matching element at index {} in {} after {} listings", element_index, app_name, attempts);
+                return Ok(JsonResponse(WaitForElementResponse {
+                    element_index,
+                    element,
+                    elements: list,
+                    waited_ms: start.elapsed().as_millis() as u64,
+                    attempts,
+                }));
+            }
+        }
+
+        if start.elapsed() + Duration::from_millis(interval) > timeout {
+            break;
+        }
+    }
+
+    warn!("no element matching role {:?} / text {:?} appeared in {} within {:?}", request.role, request.text_contains, app_name, timeout);
+    Err((
+        StatusCode::NOT_FOUND,
+        JsonResponse(json!({
+            "error": format!(
+                "no element matching role {:?} and text {:?} appeared in {} within {}ms ({} listings)",
+                request.role, request.text_contains, app_name, timeout.as_millis(), attempts
+            )
+        })),
+    ))
+}
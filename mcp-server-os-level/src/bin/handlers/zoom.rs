@@ -0,0 +1,286 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+};
+use computer_use_ai_sdk::{Desktop, UIElement};
+use serde_json::{json, Value};
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::types::{AppState, GetZoomRequest, SetZoomRequest, ZoomResponse};
+use crate::handlers::utils::{app_identity, ensure_app_allowed, resolve_application, send_key_combo, settle};
+
+// Browsers zoom through Cmd+= / Cmd+- / Cmd+0 but don't expose the level to accessibility
+const BROWSER_BUNDLE_IDS: [&str; 7] = [
+    "com.apple.Safari",
+    "com.google.Chrome",
+    "org.mozilla.firefox",
+    "com.microsoft.edgemac",
+    "company.thebrowser.Browser",
+    "com.brave.Browser",
+    "com.operasoftware.Opera",
+];
+
+// Chrome's zoom ladder, which Edge, Brave, Arc and Opera share; Safari and Firefox
+// use similar steps, so levels reached through shortcuts are approximate
+const BROWSER_ZOOM_STEPS: [f64; 17] = [
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 4.0, 5.0,
+];
+
+// Controls a zoom level can live on
+const ZOOM_CONTROL_ROLES: [&str; 4] = ["AXSlider", "AXIncrementor", "AXPopUpButton", "AXComboBox"];
+
+// How a zoom control expresses its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ZoomUnits {
+    // 1.0 = 100%
+    Factor,
+    // 100 = 100%, as a number
+    Percent,
+    // "100%", as text
+    PercentText,
+}
+
+pub async fn get_zoom_handler(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<GetZoomRequest>,
+) -> Result<JsonResponse<ZoomResponse>, (StatusCode, JsonResponse<Value>)> {
+    // Reading the level shouldn't bring the app to the front
+    let desktop = desktop(false)?;
+    let app = application(&desktop, &request.app_name, request.bundle_id.as_deref())?;
+
+    if let Some((control, zoom, _)) = find_zoom_control(&app) {
+        info!("zoom control {} reads {}", control.role(), zoom);
+        return Ok(JsonResponse(ZoomResponse {
+            supported: true,
+            zoom: Some(zoom),
+            method: Some("control".to_string()),
+            message: format!("current zoom is {:.0}%", zoom * 100.0),
+        }));
+    }
+
+    let (_, bundle_id) = app_identity(&desktop, &app);
+    if is_browser(bundle_id.as_deref()) {
+        return Ok(JsonResponse(ZoomResponse {
+            supported: true,
+            zoom: None,
+            method: Some("shortcuts".to_string()),
+            message: "this browser doesn't expose its zoom level; setZoom can still set one".to_string(),
+        }));
+    }
+
+    Ok(JsonResponse(ZoomResponse {
+        supported: false,
+        zoom: None,
+        method: None,
+        message: "the app has no zoom control".to_string(),
+    }))
+}
+
+pub async fn set_zoom_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetZoomRequest>,
+) -> Result<JsonResponse<ZoomResponse>, (StatusCode, JsonResponse<Value>)> {
+    if !request.zoom.is_finite() || request.zoom <= 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "zoom must be a positive factor, e.g. 1.25 for 125%"})),
+        ));
+    }
+
+    ensure_app_allowed(&state.config, &request.app_name, request.bundle_id.as_deref())?;
+
+    // Shortcuts go to the frontmost app, so activate it
+    let desktop = desktop(true)?;
+    let app = application(&desktop, &request.app_name, request.bundle_id.as_deref())?;
+
+    if let Some((control, current, units)) = find_zoom_control(&app) {
+        let value = format_zoom_value(request.zoom, units);
+        debug!("setting zoom control {} from {} to {}", control.role(), current, value);
+        match control.set_value(&value) {
+            Ok(_) => {
+                settle(Duration::from_millis(200)).await;
+                let zoom = find_zoom_control(&app).map(|(_, zoom, _)| zoom);
+                return Ok(JsonResponse(ZoomResponse {
+                    supported: true,
+                    zoom,
+                    method: Some("control".to_string()),
+                    message: format!("set zoom to {:.0}%", request.zoom * 100.0),
+                }));
+            }
+            // Some zoom popups are read-only; shortcuts may still work below
+            Err(e) => debug!("zoom control rejected value {}: {}", value, e),
+        }
+    }
+
+    let (_, bundle_id) = app_identity(&desktop, &app);
+    if !is_browser(bundle_id.as_deref()) {
+        return Ok(JsonResponse(ZoomResponse {
+            supported: false,
+            zoom: None,
+            method: None,
+            message: "the app has no zoom control that accepts a value".to_string(),
+        }));
+    }
+
+    // Reset to 100%, then step towards the nearest level on the ladder
+    let steps = zoom_steps(request.zoom);
+    // "cmd" is Ctrl on Windows, where browsers use the same shortcuts
+    let key = if steps < 0 { "cmd+-" } else { "cmd+=" };
+    if let Err(e) = send_key_combo("cmd+0").await {
+        error!("failed to send zoom shortcuts");
+        return Err(e);
+    }
+    for _ in 0..steps.unsigned_abs() {
+        settle(Duration::from_millis(100)).await;
+        if let Err(e) = send_key_combo(key).await {
+            error!("failed to send zoom shortcuts");
+            return Err(e);
+        }
+    }
+
+    let zoom = step_zoom(steps);
+    info!("zoomed browser to ~{:.0}% with {} shortcut steps", zoom * 100.0, steps);
+    Ok(JsonResponse(ZoomResponse {
+        supported: true,
+        zoom: Some(zoom),
+        method: Some("shortcuts".to_string()),
+        message: format!("zoomed to about {:.0}% (nearest browser zoom step)", zoom * 100.0),
+    }))
+}
+
+fn desktop(activate_app: bool) -> Result<Desktop, (StatusCode, JsonResponse<Value>)> {
+    Desktop::new(false, activate_app).map_err(|e| {
+        error!("failed to initialize desktop automation: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({
+                "error": format!("failed to initialize desktop automation: {}", e)
+            })),
+        )
+    })
+}
+
+fn application(desktop: &Desktop, app_name: &str, bundle_id: Option<&str>) -> Result<UIElement, (StatusCode, JsonResponse<Value>)> {
+    resolve_application(desktop, app_name, bundle_id).map_err(|e| {
+        error!("application not found: {}", e);
+        (
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({
+                "error": format!("application not found: {}", e)
+            })),
+        )
+    })
+}
+
+fn is_browser(bundle_id: Option<&str>) -> bool {
+    bundle_id.map_or(false, |bundle_id| BROWSER_BUNDLE_IDS.contains(&bundle_id))
+}
+
+// The first zoom-labelled control with a readable level, as (control, factor, units)
+fn find_zoom_control(app: &UIElement) -> Option<(UIElement, f64, ZoomUnits)> {
+    let elements = app.locator("").ok()?.all().ok()?;
+    elements.into_iter().find_map(|element| {
+        let attrs = element.attributes();
+        let property = |name: &str| attrs.properties.get(name).and_then(|v| v.as_ref());
+        let role = property("AXRole").and_then(|v| v.as_str()).unwrap_or(&attrs.role);
+        if !ZOOM_CONTROL_ROLES.contains(&role) {
+            return None;
+        }
+
+        let help = property("AXHelp").and_then(|v| v.as_str());
+        let labelled_zoom = [attrs.label.as_deref(), attrs.description.as_deref(), help]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains("zoom"));
+        if !labelled_zoom {
+            return None;
+        }
+
+        let value = property("AXValue")
+            .cloned()
+            .or_else(|| attrs.value.clone().map(Value::String))?;
+        let (zoom, units) = parse_zoom_value(&value)?;
+        Some((element, zoom, units))
+    })
+}
+
+fn parse_zoom_value(value: &Value) -> Option<(f64, ZoomUnits)> {
+    let parsed = match value {
+        // Sliders use either factors (0.5-4) or percentages (25-400)
+        Value::Number(number) => {
+            let number = number.as_f64()?;
+            if number > 10.0 {
+                Some((number / 100.0, ZoomUnits::Percent))
+            } else {
+                Some((number, ZoomUnits::Factor))
+            }
+        }
+        Value::String(text) => {
+            let percent: f64 = text.trim().trim_end_matches('%').trim().parse().ok()?;
+            Some((percent / 100.0, ZoomUnits::PercentText))
+        }
+        _ => None,
+    };
+    parsed.filter(|(zoom, _)| *zoom > 0.0)
+}
+
+fn format_zoom_value(zoom: f64, units: ZoomUnits) -> String {
+    match units {
+        ZoomUnits::Factor => format!("{}", zoom),
+        ZoomUnits::Percent => format!("{}", (zoom * 100.0).round()),
+        ZoomUnits::PercentText => format!("{}%", (zoom * 100.0).round()),
+    }
+}
+
+// Cmd+= presses (negative for Cmd+-) from 100% to the ladder step nearest `zoom`
+fn zoom_steps(zoom: f64) -> i32 {
+    let default_index = BROWSER_ZOOM_STEPS.iter().position(|step| *step == 1.0).unwrap_or_default();
+    let nearest_index = BROWSER_ZOOM_STEPS
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - zoom).abs().total_cmp(&(*b - zoom).abs()))
+        .map(|(i, _)| i)
+        .unwrap_or(default_index);
+    nearest_index as i32 - default_index as i32
+}
+
+fn step_zoom(steps: i32) -> f64 {
+    let default_index = BROWSER_ZOOM_STEPS.iter().position(|step| *step == 1.0).unwrap_or_default() as i32;
+    let index = (default_index + steps).clamp(0, BROWSER_ZOOM_STEPS.len() as i32 - 1);
+    BROWSER_ZOOM_STEPS[index as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zoom_values_in_any_units() {
+        assert_eq!(parse_zoom_value(&json!(1.5)), Some((1.5, ZoomUnits::Factor)));
+        assert_eq!(parse_zoom_value(&json!(125)), Some((1.25, ZoomUnits::Percent)));
+        assert_eq!(parse_zoom_value(&json!("75%")), Some((0.75, ZoomUnits::PercentText)));
+        assert_eq!(parse_zoom_value(&json!("Fit Width")), None);
+        assert_eq!(parse_zoom_value(&json!(0)), None);
+    }
+
+    #[test]
+    fn formats_zoom_values_back_in_the_controls_units() {
+        assert_eq!(format_zoom_value(1.25, ZoomUnits::Factor), "1.25");
+        assert_eq!(format_zoom_value(1.25, ZoomUnits::Percent), "125");
+        assert_eq!(format_zoom_value(1.25, ZoomUnits::PercentText), "125%");
+    }
+
+    #[test]
+    fn steps_to_the_nearest_browser_zoom_level() {
+        assert_eq!(zoom_steps(1.0), 0);
+        assert_eq!(zoom_steps(1.25), 2);
+        assert_eq!(zoom_steps(1.3), 2);
+        assert_eq!(zoom_steps(0.5), -5);
+        assert_eq!(zoom_steps(10.0), 9);
+        assert_eq!(step_zoom(zoom_steps(1.3)), 1.25);
+        assert_eq!(step_zoom(zoom_steps(10.0)), 5.0);
+    }
+}
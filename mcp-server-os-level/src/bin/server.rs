@@ -1,26 +1,72 @@
-use std::{net::SocketAddr, sync::Arc, io::{self, BufRead, BufReader, Write}};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, io::{self, BufRead, BufReader, Write}};
 
 use axum::{
-    routing::post,
+    routing::{get, post, MethodRouter},
     Router,
 };
-use tokio::sync::Mutex;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tokio::sync::{broadcast, Mutex};
+use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
 use tracing::{error, info, level_filters::LevelFilter};
 use serde_json::{json, Value};
 mod types;
 use types::*;
+mod config;
+use config::{bind_address, ServerConfig};
 mod handlers;
 
 // Import only the handlers actually used
 use handlers::mcp::mcp_handler;
+use handlers::mcp_ws::mcp_ws_handler;
 use handlers::click_by_index::click_by_index_handler;
 use handlers::type_by_index::type_by_index_handler;
 use handlers::press_key_by_index::press_key_by_index_handler;
 use handlers::open_application::open_application_handler;
+use handlers::open_and_find::open_and_find_handler;
 use handlers::open_url::open_url_handler;
 use handlers::input_control::input_control_handler;
-use handlers::list_elements_and_attributes::list_elements_and_attributes_handler;
+use handlers::list_elements_and_attributes::list_elements_and_attributes_route;
+use handlers::get_progress::get_progress_handler;
+use handlers::info::info_handler;
+use handlers::health::{health_handler, ready_handler};
+use handlers::screenshot_by_index::screenshot_by_index_handler;
+use handlers::click_by_text::click_by_text_handler;
+use handlers::get_click_point_by_index::get_click_point_by_index_handler;
+use handlers::list_menu_bar_items::list_menu_bar_items_handler;
+use handlers::click_menu_bar_item::click_menu_bar_item_handler;
+use handlers::drag_file_to_index::drag_file_to_index_handler;
+use handlers::describe_by_index::describe_by_index_handler;
+use handlers::list_all_elements::list_all_elements_handler;
+use handlers::zoom::get_zoom_handler;
+use handlers::zoom::set_zoom_handler;
+use handlers::press_until::press_until_handler;
+use handlers::cache_status::cache_status_handler;
+use handlers::launch_via_spotlight::launch_via_spotlight_handler;
+use handlers::get_table_by_index::get_table_by_index_handler;
+use handlers::step_by_index::step_by_index_handler;
+use handlers::run_applescript::run_applescript_handler;
+use handlers::list_notifications::list_notifications_handler;
+use handlers::tab_to_field::tab_to_field_handler;
+use handlers::get_text_by_index::get_text_by_index_handler;
+use handlers::ocr_screen::ocr_screen_handler;
+use handlers::dismiss_dialog::dismiss_dialog_handler;
+use handlers::get_caret_by_index::get_caret_by_index_handler;
+use handlers::get_app_metrics::get_app_metrics_handler;
+use handlers::set_field_by_index::set_field_by_index_handler;
+use handlers::reset_input_state::reset_input_state_handler;
+use handlers::right_click_by_index::right_click_by_index_handler;
+use handlers::scroll_by_index::scroll_by_index_handler;
+use handlers::close_application::close_application_handler;
+use handlers::capture_screen::capture_screen_handler;
+use handlers::wait_for_element::wait_for_element_handler;
+use handlers::drag_by_index::drag_by_index_handler;
+use handlers::hover_by_index::hover_by_index_handler;
+use handlers::clear_element_cache::clear_element_cache_handler;
+use handlers::click_by_id::click_by_id_handler;
+use handlers::type_by_id::type_by_id_handler;
+use handlers::execute_batch::execute_batch_handler;
+use handlers::get_element_by_index::get_element_by_index_handler;
+use handlers::focus_by_index::focus_by_index_handler;
+use handlers::list_applications::list_applications_handler;
 use handlers::utils::*;
 
 // Import mcp_handler helpers but we'll call them directly
@@ -31,7 +77,8 @@ use handlers::mcp::{handle_initialize, handle_execute_tool_function, mcp_error_r
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Check if we should use STDIO mode
-    let use_stdio = std::env::args().any(|arg| arg == "--stdio");
+    let args: Vec<String> = std::env::args().collect();
+    let use_stdio = args.iter().any(|arg| arg == "--stdio");
     
     // initialize tracing with different settings based on mode
     if use_stdio {
@@ -55,44 +102,126 @@ async fn main() -> anyhow::Result<()> {
     
     // Create app state
     let app_state = Arc::new(AppState {
-        element_cache: Arc::new(Mutex::new(None)),
+        element_cache: Arc::new(Mutex::new(ElementCache::default())),
+        element_ids: Arc::new(Mutex::new(HashMap::new())),
+        ui_fingerprint: Arc::new(Mutex::new(HashMap::new())),
+        ui_snapshot: Arc::new(Mutex::new(HashMap::new())),
+        listing_requests: Arc::new(Mutex::new(HashMap::new())),
+        menu_bar_cache: Arc::new(Mutex::new(None)),
+        element_owners: Arc::new(Mutex::new(Vec::new())),
+        mcp_notifications: broadcast::channel(64).0,
+        config: ServerConfig::from_env(),
     });
 
+    // Release element handles of listings nobody has touched in a while
+    if let Some(idle_timeout) = app_state.config.cache_idle_timeout {
+        tokio::spawn(sweep_idle_caches(app_state.clone(), idle_timeout));
+    }
+
     if use_stdio {
         info!("running in STDIO mode for MCP");
         // run_stdio_mode(app_state).await?;
     } else {
-        info!("running in HTTP mode on port 8080");
-        run_http_server(app_state).await?;
+        let addr = bind_address(&args)?;
+        info!("running in HTTP mode on {}", addr);
+        run_http_server(app_state, addr).await?;
     }
     
     Ok(())
 }
 
-async fn run_http_server(app_state: Arc<AppState>) -> anyhow::Result<()> {
+async fn run_http_server(app_state: Arc<AppState>, addr: SocketAddr) -> anyhow::Result<()> {
     // Create CORS layer
     let cors = CorsLayer::very_permissive();
     
-    // Create router with both existing and MCP endpoints plus new endpoints
-    let app = Router::new()
+    // Tool routes, keyed by the MCP tool name so the config can turn them off
+    let tool_routes: Vec<(&str, &str, MethodRouter<Arc<AppState>>)> = vec![
+        ("clickByIndex", "/api/click-by-index", post(click_by_index_handler)),
+        ("typeByIndex", "/api/type-by-index", post(type_by_index_handler)),
+        ("pressKeyByIndex", "/api/press-key-by-index", post(press_key_by_index_handler)),
+        ("openApplication", "/api/open-application", post(open_application_handler)),
+        ("openAndFind", "/api/open-and-find", post(open_and_find_handler)),
+        ("openUrl", "/api/open-url", post(open_url_handler)),
+        ("inputControl", "/api/input-control", post(input_control_handler)),
+        ("listInteractableElementsByIndex", "/api/list-elements-and-attributes", post(list_elements_and_attributes_route)),
+        ("getProgress", "/api/get-progress", post(get_progress_handler)),
+        ("screenshotByIndex", "/api/screenshot-by-index", post(screenshot_by_index_handler)),
+        ("clickByText", "/api/click-by-text", post(click_by_text_handler)),
+        ("getClickPointByIndex", "/api/get-click-point-by-index", post(get_click_point_by_index_handler)),
+        ("listMenuBarItems", "/api/list-menu-bar-items", post(list_menu_bar_items_handler)),
+        ("clickMenuBarItem", "/api/click-menu-bar-item", post(click_menu_bar_item_handler)),
+        ("dragFileToIndex", "/api/drag-file-to-index", post(drag_file_to_index_handler)),
+        ("describeByIndex", "/api/describe-by-index", post(describe_by_index_handler)),
+        ("listAllElements", "/api/list-all-elements", post(list_all_elements_handler)),
+        ("getZoom", "/api/get-zoom", post(get_zoom_handler)),
+        ("setZoom", "/api/set-zoom", post(set_zoom_handler)),
+        ("pressUntil", "/api/press-until", post(press_until_handler)),
+        ("getCacheStatus", "/api/cache-status", get(cache_status_handler)),
+        ("launchViaSpotlight", "/api/launch-via-spotlight", post(launch_via_spotlight_handler)),
+        ("getTableByIndex", "/api/get-table-by-index", post(get_table_by_index_handler)),
+        ("stepByIndex", "/api/step-by-index", post(step_by_index_handler)),
+        ("runAppleScript", "/api/run-applescript", post(run_applescript_handler)),
+        ("listNotifications", "/api/list-notifications", post(list_notifications_handler)),
+        ("tabToField", "/api/tab-to-field", post(tab_to_field_handler)),
+        ("getTextByIndex", "/api/get-text-by-index", post(get_text_by_index_handler)),
+        ("ocrScreen", "/api/ocr-screen", post(ocr_screen_handler)),
+        ("dismissDialog", "/api/dismiss-dialog", post(dismiss_dialog_handler)),
+        ("getCaretByIndex", "/api/get-caret-by-index", post(get_caret_by_index_handler)),
+        ("getAppMetrics", "/api/app-metrics", post(get_app_metrics_handler)),
+        ("setFieldByIndex", "/api/set-field-by-index", post(set_field_by_index_handler)),
+        ("resetInputState", "/api/reset-input-state", post(reset_input_state_handler)),
+        ("rightClickByIndex", "/api/right-click-by-index", post(right_click_by_index_handler)),
+        ("scrollByIndex", "/api/scroll-by-index", post(scroll_by_index_handler)),
+        ("closeApplication", "/api/close-application", post(close_application_handler)),
+        ("captureScreen", "/api/screenshot", post(capture_screen_handler)),
+        ("waitForElement", "/api/wait-for-element", post(wait_for_element_handler)),
+        ("dragByIndex", "/api/drag-by-index", post(drag_by_index_handler)),
+        ("hoverByIndex", "/api/hover-by-index", post(hover_by_index_handler)),
+        ("clearElementCache", "/api/clear-cache", post(clear_element_cache_handler)),
+        ("clickById", "/api/click-by-id", post(click_by_id_handler)),
+        ("typeById", "/api/type-by-id", post(type_by_id_handler)),
+        ("executeBatch", "/api/batch", post(execute_batch_handler)),
+        ("getElementByIndex", "/api/get-element-by-index", post(get_element_by_index_handler)),
+        ("focusByIndex", "/api/focus-by-index", post(focus_by_index_handler)),
+        ("listApplications", "/api/list-applications", post(list_applications_handler)),
+    ];
+    
+    // Create router with the MCP endpoint plus every enabled tool endpoint
+    let mut app = Router::new()
         .route("/mcp", post(mcp_handler))
-        .route("/api/click-by-index", post(click_by_index_handler))
-        .route("/api/type-by-index", post(type_by_index_handler))
-        .route("/api/press-key-by-index", post(press_key_by_index_handler))
-        .route("/api/open-application", post(open_application_handler))
-        .route("/api/open-url", post(open_url_handler))
-        .route("/api/input-control", post(input_control_handler))
-        .route("/api/list-elements-and-attributes", post(list_elements_and_attributes_handler))
+        .route("/mcp/ws", get(mcp_ws_handler))
+        .route("/api/info", get(info_handler))
+        // Probes for supervisors, deliberately not MCP tools
+        .route("/health", get(health_handler))
+        .route("/ready", get(ready_handler));
+    
+    for (tool, path, route) in tool_routes {
+        if app_state.config.is_tool_enabled(tool) {
+            app = app.route(path, route);
+        } else {
+            info!("tool {} is disabled, not mounting {}", tool, path);
+        }
+    }
+    
+    let compress = app_state.config.response_compression;
+    let mut app = app
         .with_state(app_state)
         .layer(cors)
         .layer(TraceLayer::new_for_http());
     
-    // Get the address to bind to
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
+    // Only applies to HTTP; the stdio transport never goes through the router
+    if compress {
+        app = app.layer(CompressionLayer::new());
+    }
+    
+    // try_bind instead of bind, which panics when the port is taken
+    let server = axum::Server::try_bind(&addr).map_err(|e| {
+        anyhow::anyhow!("could not bind to {}: {} - is another instance running? pick another address with --host/--port or HOST/PORT", addr, e)
+    })?;
     info!("listening on {}", addr);
     
     // Start the server
-    axum::Server::bind(&addr)
+    server
         .serve(app.into_make_service())
         .await?;
     
@@ -1,9 +1,11 @@
 use std::{collections::HashMap, sync::Arc, time::Instant};
 use computer_use_ai_sdk::UIElement;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 use serde_json::Value;
 
+use crate::config::ServerConfig;
+
 // ================ Types ================
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -94,9 +96,97 @@ pub struct GetTextResponse {
     pub text: String,
 }
 
+// One app's listing in the element cache
+#[derive(Clone)]
+pub struct CachedListing {
+    pub elements: Vec<UIElement>,
+    pub timestamp: Instant,
+    // Name the app was listed by, so requests can pick the listing by name as well as by key
+    pub app_name: String,
+}
+
+// Listings by app key (bundle id when known, else the app name, ALL_APPS_KEY for listAllElements)
+#[derive(Default)]
+pub struct ElementCache {
+    pub listings: HashMap<String, CachedListing>,
+    // Key of the most recent listing, which by-index requests use unless they name an app
+    pub latest: Option<String>,
+}
+
+impl ElementCache {
+    // Store a listing for `key`, replacing that app's previous one, and make it the latest
+    pub fn insert(&mut self, key: String, app_name: String, elements: Vec<UIElement>) {
+        self.listings.insert(key.clone(), CachedListing { elements, timestamp: Instant::now(), app_name });
+        self.latest = Some(key);
+    }
+
+    // The listing for `app_name` (its key or the name it was listed by, case-insensitively),
+    // or the latest listing when no app is named, as (elements, listed at, key)
+    pub fn get(&self, app_name: Option<&str>) -> Option<(Vec<UIElement>, Instant, String)> {
+        let key = match app_name {
+            Some(app_name) => self
+                .listings
+                .keys()
+                .find(|key| key.eq_ignore_ascii_case(app_name))
+                .or_else(|| {
+                    self.listings
+                        .iter()
+                        .find(|(_, listing)| listing.app_name.eq_ignore_ascii_case(app_name))
+                        .map(|(key, _)| key)
+                })?,
+            None => self.latest.as_ref()?,
+        };
+        let listing = self.listings.get(key)?;
+        Some((listing.elements.clone(), listing.timestamp, key.clone()))
+    }
+
+    // Key of the most recent listing
+    pub fn latest_key(&self) -> Option<&str> {
+        self.latest.as_deref().filter(|key| self.listings.contains_key(*key))
+    }
+
+    // Drop the listings of an app known by any of `identities` (keys or listed names),
+    // returning the keys removed
+    pub fn remove_app<S: AsRef<str>>(&mut self, identities: &[S]) -> Vec<String> {
+        let matches = |name: &str| identities.iter().any(|identity| identity.as_ref().eq_ignore_ascii_case(name));
+        let keys: Vec<String> = self
+            .listings
+            .iter()
+            .filter(|(key, listing)| matches(key) || matches(&listing.app_name))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.listings.remove(key);
+        }
+        keys
+    }
+
+    // Drop every listing, returning the keys removed
+    pub fn clear(&mut self) -> Vec<String> {
+        self.latest = None;
+        self.listings.drain().map(|(key, _)| key).collect()
+    }
+}
+
 // App state
 pub struct AppState {
-    pub element_cache: Arc<Mutex<Option<(Vec<UIElement>, Instant, String)>>>,
+    pub element_cache: Arc<Mutex<ElementCache>>,
+    // Stable ids of each app's cached elements by index, used to keep indices steady across refreshes
+    pub element_ids: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    // Hash of each app's last listing's roles and texts, to tell whether an action changed the UI
+    pub ui_fingerprint: Arc<Mutex<HashMap<String, u64>>>,
+    // Hash of each entry of each app's last listing by element id, to say what an action added, removed or changed
+    pub ui_snapshot: Arc<Mutex<HashMap<String, HashMap<String, u64>>>>,
+    // The request behind each app's last listing, so the refresh after an action lists it the
+    // same way and its fingerprint compares like with like
+    pub listing_requests: Arc<Mutex<HashMap<String, ListInteractableElementsRequest>>>,
+    // Menu bar status items from the last listMenuBarItems, by index
+    pub menu_bar_cache: Arc<Mutex<Option<(Vec<UIElement>, Instant)>>>,
+    // Owning app of each cached element when the cache holds a listAllElements listing
+    pub element_owners: Arc<Mutex<Vec<String>>>,
+    // JSON-RPC notifications pushed to every connected /mcp/ws client
+    pub mcp_notifications: broadcast::Sender<Value>,
+    pub config: ServerConfig,
 }
 
 // MCP-specific types
@@ -191,16 +281,230 @@ pub struct ScrollElementRequest {
     pub amount: f64,
 }
 
+// Request for scrollByIndex: scroll the content under an indexed element
+#[derive(Debug, Deserialize)]
+pub struct ScrollByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // "up", "down", "left" or "right"
+    pub direction: String,
+    // Lines to scroll (default 3)
+    pub amount: Option<f64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScrollByIndexResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+// Request for dragByIndex: press on an indexed element, drag, and release over another
+// element or a screen point
+#[derive(Debug, Deserialize)]
+pub struct DragByIndexRequest {
+    // Element the drag starts on
+    pub element_index: usize,
+    // Which app's listing both indices are from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // Drop on the center of this element...
+    pub target_index: Option<usize>,
+    // ...or on this screen point
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DragByIndexResponse {
+    pub success: bool,
+    pub message: String,
+    // Where the drag was picked up and dropped, in screen points
+    pub from: ScreenPoint,
+    pub to: ScreenPoint,
+}
+
+// Request for hoverByIndex: rest the cursor on an indexed element without clicking
+#[derive(Debug, Deserialize)]
+pub struct HoverByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // How long to keep the cursor there before listing again (default 500, at most 10000)
+    pub dwell_ms: Option<u64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoverByIndexResponse {
+    pub success: bool,
+    pub message: String,
+    // Where the cursor was left, in screen points
+    pub point: ScreenPoint,
+}
+
+// Request for focusByIndex: give an indexed element the keyboard focus without clicking it,
+// then optionally type into it or press a key
+#[derive(Debug, Deserialize)]
+pub struct FocusByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // Text to enter once focused, and how (see TypingMethod)
+    pub text: Option<String>,
+    #[serde(default)]
+    pub method: TypingMethod,
+    // Key combination to press once focused (after the text, if both are given)
+    pub key_combo: Option<String>,
+    // Pause between focusing and the input, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FocusByIndexResponse {
+    pub success: bool,
+    pub message: String,
+    pub typed: bool,
+    pub key_pressed: bool,
+}
+
+// Optional screenshot attached to action responses
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ScreenshotOptions {
+    #[serde(default)]
+    pub include_screenshot: bool,
+    // Downscale to at most this many pixels wide
+    pub screenshot_max_width: Option<u32>,
+    // Encode as JPEG with this quality (1-100) instead of PNG
+    pub screenshot_quality: Option<u8>,
+}
+
+// What a request can ask for around the action itself, flattened into each action request:
+// - restore_focus:    hand focus back to the previously frontmost app afterwards (slower, see FocusGuard)
+// - include_screenshot, screenshot_max_width, screenshot_quality: attach a screenshot of the
+//                     app's window to the response, see ScreenshotOptions
+// - refresh_elements: list the app again after the action and return it (default true); turn
+//                     off in the middle of a sequence whose intermediate listings would go unused
+// - delay_ms:         wait before that listing, overriding REFRESH_DELAY_MS
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ActionOptions {
+    #[serde(default)]
+    pub restore_focus: bool,
+    #[serde(flatten)]
+    pub screenshot: ScreenshotOptions,
+    pub refresh_elements: Option<bool>,
+    pub delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActionScreenshot {
+    pub format: String, // "png" or "jpeg"
+    pub image_base64: String,
+}
+
 // Types for opening applications
 #[derive(Deserialize, Serialize)]
 pub struct OpenApplicationRequest {
+    // Display name, or a bundle identifier written as one (launched as if given in bundle_id)
+    #[serde(default)]
     pub app_name: String,
+    // When set, launch by bundle identifier instead of display name
+    pub bundle_id: Option<String>,
+    // Override the server's retries for apps that come up without elements
+    pub retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
 }
 
 #[derive(Serialize)]
 pub struct OpenApplicationResponse {
     pub success: bool,
     pub message: String,
+    // Resolved display name and process id of the opened application
+    pub app_name: Option<String>,
+    pub pid: Option<i32>,
+    pub bundle_id: Option<String>,
+}
+
+// Types for quitting an application
+#[derive(Debug, Deserialize)]
+pub struct CloseApplicationRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    // Kill the process instead of asking it to quit (unsaved work is lost, no save dialogs)
+    #[serde(default)]
+    pub force: bool,
+}
+
+// Request for clearElementCache: forget one app's listings, or all of them
+#[derive(Debug, Deserialize, Default)]
+pub struct ClearElementCacheRequest {
+    // App whose listings to drop (name or bundle id, as listed); every listing when omitted
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClearElementCacheResponse {
+    pub success: bool,
+    pub message: String,
+    // Keys of the listings that were dropped
+    pub cleared_caches: Vec<String>,
+    // Element handles released across those listings
+    pub dropped_elements: usize,
+}
+
+// Same shape as OpenApplicationResponse, describing the app that was closed
+#[derive(Serialize)]
+pub struct CloseApplicationResponse {
+    pub success: bool,
+    pub message: String,
+    pub app_name: Option<String>,
+    pub pid: Option<i32>,
+    pub bundle_id: Option<String>,
+    // Cached listings of the app that were dropped, so their indices can't be used anymore
+    pub cleared_caches: Vec<String>,
+}
+
+// Types for opening an application and locating an element in one call
+#[derive(Deserialize, Serialize)]
+pub struct OpenAndFindRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    // Predicate for the element to find; at least one must be given
+    pub role: Option<String>, // e.g. "AXButton" or "button"
+    pub text: Option<String>, // case-insensitive substring of the element text
+    // How long to keep re-listing while the app finishes loading
+    pub timeout_ms: Option<u64>,
+    // Hand focus back to the previously frontmost app afterwards (slower, see FocusGuard)
+    #[serde(default)]
+    pub restore_focus: bool,
+}
+
+#[derive(Serialize)]
+pub struct OpenAndFindResponse {
+    pub application: OpenApplicationResponse,
+    pub element_index: usize,
+    pub element: Value,
+    pub elements: ListElementsAndAttributesResponse,
+    // Whether opening the app changed its listing (None if it wasn't listed before)
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
 }
 
 // Types for opening URLs
@@ -208,6 +512,9 @@ pub struct OpenApplicationResponse {
 pub struct OpenUrlRequest {
     pub url: String,
     pub browser: Option<String>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
 }
 
 #[derive(Serialize)]
@@ -217,12 +524,88 @@ pub struct OpenUrlResponse {
 }
 
 // Types for interactable elements
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct ListInteractableElementsRequest {
+    #[serde(default)]
     pub app_name: String,
+    // Stable alternative to app_name, e.g. "com.apple.Safari"; wins when both are given
+    pub bundle_id: Option<String>,
     pub max_elements: Option<usize>,
     pub use_background_apps: Option<bool>,
     pub activate_app: Option<bool>,
+    // Return every element, ignoring the server's default cap
+    pub unbounded: Option<bool>,
+    // Only list elements matching a query such as `role=AXButton title~=save`
+    pub locator: Option<String>,
+    // Which fields each element carries, see ListFormat
+    #[serde(default)]
+    pub format: ListFormat,
+    // Which elements count as worth listing, see ClassificationProfile
+    #[serde(default)]
+    pub classification: ClassificationProfile,
+    // Ask Chromium/Electron apps to build their full accessibility tree before walking,
+    // see enable_enhanced_accessibility
+    pub enable_enhanced_accessibility: Option<bool>,
+    // Only list elements whose bounds overlap this screen area (elements without bounds are
+    // dropped); indices are assigned within the remaining elements
+    pub region: Option<ElementBounds>,
+    // Above this many listed elements return a summary instead of the list (0 = never),
+    // overriding the server's SUMMARIZE_ABOVE_ELEMENTS; unbounded listings are never summarized
+    pub summarize_above: Option<usize>,
+    // Only list elements whose interactability score (see interactability_score) is at least this
+    pub min_score: Option<f64>,
+    // Order the listing by score, highest first, instead of by position; indices are unchanged
+    pub sort_by_score: Option<bool>,
+    // Only list elements with one of these roles, e.g. ["button", "AXTextField"]
+    pub role_filter: Option<Vec<String>>,
+    // Only list elements whose combined text contains this (case-insensitive)
+    pub text_contains: Option<String>,
+    // Skip elements listed only for their text
+    pub interactable_only: Option<bool>,
+    // Only list elements in windows whose title contains this (case-insensitive); like region,
+    // applied before indexing
+    pub window_name: Option<String>,
+}
+
+// Shape of each listed element:
+// - compact:  index, role, text, score (0-1, how likely it responds to a click, see interactability_score)
+// - standard: compact plus text_parts (mixed-direction text only), checked/has_submenu/is_separator
+//             (menu items only), enabled, focused and bounds {x, y, width, height}
+// - verbose:  standard plus the raw label, value, description and every AX property under `properties`
+// - path_map: no `elements`; instead `path_map` keyed by the element's place in the hierarchy,
+//             e.g. "Window[Inbox]/Toolbar/Button[Send]": {index, bounds, enabled}
+//             (single-app listings only, elsewhere it reads as standard)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    Compact,
+    #[default]
+    Standard,
+    Verbose,
+    #[serde(rename = "path_map")]
+    PathMap,
+}
+
+// Which elements a listing keeps; the stats' excluded and text counts follow the same rules:
+// - strict:         interactable roles only, text-only elements like labels are excluded
+// - loose:          interactable roles, plus anything else that carries text
+// - text_inclusive: loose, plus text roles (static text, headings) even while they're empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassificationProfile {
+    Strict,
+    #[default]
+    Loose,
+    TextInclusive,
+}
+
+// How likely an element with a given role is to respond to input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interactability {
+    Definite,
+    Sometimes,
+    None,
 }
 
 #[derive(Debug, Serialize)]
@@ -256,20 +639,388 @@ pub struct ElementStatistics {
     pub without_text_count: usize,
     pub top_roles: HashMap<String, u32>,
     pub properties: HashMap<String, u32>,
+    pub truncated: bool,
+    pub total_available: usize,
+    // Profile the counts above were classified with
+    pub classification: ClassificationProfile,
+    // Elements dropped by the request's region before anything else (not in excluded_count)
+    pub excluded_by_region: usize,
+    // Elements dropped by the request's window_name, likewise
+    pub excluded_by_window: usize,
+    // Elements kept by the classification but scored below the request's min_score
+    pub excluded_by_score: usize,
+    // Elements kept by the classification but dropped by role_filter, text_contains or interactable_only
+    pub excluded_by_filter: usize,
+}
+
+// How indices relate to the previous list of the same app
+#[derive(serde::Serialize, Debug)]
+pub struct IndexStabilityInfo {
+    pub preserved_count: usize,
+    pub changed_indices: Vec<usize>, // indices that now point to a different element or past the end
+    pub removed_indices: Vec<usize>, // previous indices whose element is gone
+    pub new_count: usize,
+    pub renumbered: bool, // true when previous indices could not all be kept, e.g. a removal shifted later elements
 }
 
 #[derive(serde::Serialize, Debug)]
 pub struct ListElementsAndAttributesResponse {
+    // The listed app's display name and bundle id; prefer the bundle id for later requests
+    pub app_name: Option<String>,
+    pub bundle_id: Option<String>,
     pub elements: Vec<serde_json::Value>,
     pub cache_info: ElementCacheInfo,
     pub stats: ElementStatistics,
     pub processing_time_seconds: String,
+    // Where processing_time_seconds went
+    pub timing: ListTiming,
+    pub index_stability: Option<IndexStabilityInfo>,
+    // Set when more elements matched than were returned
+    pub truncated: bool,
+    pub total_available: usize,
+    // Only for format "path_map", which leaves `elements` empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_map: Option<serde_json::Map<String, Value>>,
+    // Whether the app accepted enable_enhanced_accessibility, when it was asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enhanced_accessibility: Option<bool>,
+    // Hash of the listed content, also sent as the ETag header (see list_elements_and_attributes_route)
+    pub etag: String,
+    // Set when the app looks like it isn't exposing its UI to accessibility (see accessibility_warning)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessibility_warning: Option<String>,
+    // Set instead of `elements` (which is then empty) when the listing was too big to be useful
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ListingSummary>,
+}
+
+// An overview of a listing above the summary ceiling; its indices are live like a full listing's
+#[derive(Debug, Serialize)]
+pub struct ListingSummary {
+    // Listed elements the summary stands for
+    pub element_count: usize,
+    pub ceiling: usize,
+    // Listed elements per role, most common first
+    pub roles: Vec<RoleCount>,
+    pub windows: Vec<WindowSummary>,
+    // The largest interactable elements, as regular listing entries
+    pub prominent: Vec<Value>,
+    // How to get a listing small enough to return in full
+    pub hint: String,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct RoleCount {
+    pub role: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowSummary {
+    pub index: usize,
+    pub title: Option<String>,
+    // Pass as `region` to list only this window
+    pub bounds: ElementBounds,
+    // Listed elements centered inside this window
+    pub element_count: usize,
+}
+
+// Time spent in each phase of a listing
+#[derive(Debug, Serialize)]
+pub struct ListTiming {
+    // Walking the accessibility tree and sorting the result
+    pub walk_ms: u64,
+    // Reading each element's attributes and building its JSON
+    pub attributes_ms: u64,
+    // Index preservation, the interactability filter and truncation
+    pub filter_ms: u64,
+    // Generating the stats block
+    pub stats_ms: u64,
+}
+
+// Types for reading progress and busy indicators
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetProgressRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressIndicatorInfo {
+    pub role: String,
+    pub label: Option<String>,
+    pub value: Option<f64>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub fraction: Option<f64>, // value normalized to 0.0-1.0 when the range is known
+    pub indeterminate: bool,   // spinner or progress bar without a value
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetProgressResponse {
+    pub indicators: Vec<ProgressIndicatorInfo>,
+    // True while a spinner is shown or a progress bar hasn't reached its maximum
+    pub busy: bool,
+}
+
+// Types for describing the running server
+#[derive(Debug, Serialize)]
+pub struct ServerInfoResponse {
+    pub name: String,
+    pub version: String,
+    pub enabled_tools: Vec<String>,
+    pub disabled_tools: Vec<String>,
+    // Browser openUrl uses when the request names none (None = system default)
+    pub default_browser: Option<String>,
+    // Apps input is limited to in safe mode (lowercase; None = safe mode off)
+    pub safe_mode_allowed_apps: Option<Vec<String>>,
+}
+
+// Types for the /health and /ready probes
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadyResponse {
+    pub ready: bool,
+    pub accessibility_granted: bool,
+    // Why the permissions check itself failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Types for inspecting the element caches without re-walking anything
+#[derive(Debug, Serialize)]
+pub struct CacheStatusResponse {
+    // The last listing, which by-index tools use unless they name an app (None if nothing is cached)
+    pub element_cache: Option<CacheEntryStatus>,
+    // Every cached listing, including the last one, newest first
+    pub element_caches: Vec<CacheEntryStatus>,
+    // The last listMenuBarItems result used by clickMenuBarItem
+    pub menu_bar_cache: Option<CacheEntryStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheEntryStatus {
+    // Apps the cached elements belong to, with how many elements each (empty for menu bar items)
+    pub apps: Vec<CachedAppStatus>,
+    pub element_count: usize,
+    pub age_seconds: f64,
+    pub ttl_seconds: u64,
+    // Whether by-index calls would fail with "cache entry expired"
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedAppStatus {
+    pub app_name: String,
+    pub element_count: usize,
 }
 
 // Types for index-based operations
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScreenshotByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScreenshotByIndexResponse {
+    pub success: bool,
+    pub format: String,       // always "png"
+    pub image_base64: String,
+}
+
+// Types for menu bar status items (menu extras)
+#[derive(Debug, Default, Deserialize)]
+pub struct ListMenuBarItemsRequest {
+    // Only list items owned by this app, e.g. "Control Center" or "Dropbox"
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MenuBarItemInfo {
+    pub index: usize,
+    pub owner_app: Option<String>,
+    pub pid: Option<i32>,
+    pub title: Option<String>,
+    pub bounds: Option<ElementBounds>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListMenuBarItemsResponse {
+    pub items: Vec<MenuBarItemInfo>,
+}
+
+// Running apps, to find the name or bundle id other tools expect
+#[derive(Debug, Default, Deserialize)]
+pub struct ListApplicationsRequest {
+    // Also list agents and daemons without a Dock icon (default false)
+    pub include_background: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RunningApplication {
+    // Localized name, what app_name matches
+    pub name: String,
+    pub bundle_id: Option<String>,
+    pub pid: i32,
+    pub frontmost: bool,
+    pub hidden: bool,
+    // No Dock icon or menu bar (a menu extra, helper or daemon)
+    pub background: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListApplicationsResponse {
+    pub applications: Vec<RunningApplication>,
+    pub frontmost_app: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClickMenuBarItemRequest {
+    pub item_index: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClickMenuBarItemResponse {
+    pub success: bool,
+    pub message: String,
+    pub owner_app: Option<String>,
+    // Items of the menu the click revealed; their indices work with the by-index tools
+    pub menu_items: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetClickPointByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+}
+
+// Screen position in the global coordinate space AppleScript clicks in (points, origin top-left of the main display)
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ScreenPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+// A screen rectangle in the same coordinate space as ScreenPoint
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ElementBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ElementBounds {
+    // Whether an element's (x, y, width, height) bounds overlap this rectangle
+    pub fn intersects(&self, (x, y, width, height): (f64, f64, f64, f64)) -> bool {
+        x < self.x + self.width && self.x < x + width && y < self.y + self.height && self.y < y + height
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetClickPointByIndexResponse {
+    pub element_index: usize,
+    pub role: String,
+    pub bounds: ElementBounds,
+    // Center of the bounds, rounded the way clickByIndex sends it
+    pub click_point: ScreenPoint,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ClickByIndexRequest {
     pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // 2 for a double-click (open an item), 3 for a triple-click (select a line); default 1
+    pub click_count: Option<u32>,
+    // Click once more if the listing after the click is identical to the one before
+    #[serde(default)]
+    pub retry_if_unchanged: bool,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+// Request for clickById: clickByIndex, addressed by the element_id of a listing entry,
+// which keeps pointing at the same element when a refresh renumbers the indices
+#[derive(Debug, Deserialize)]
+pub struct ClickByIdRequest {
+    pub element_id: String,
+    // Which app's listing the id is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    pub click_count: Option<u32>,
+    #[serde(default)]
+    pub retry_if_unchanged: bool,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+// Request for rightClickByIndex, which opens the element's context menu
+#[derive(Debug, Deserialize)]
+pub struct RightClickByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+// Frontmost app before and after an action
+#[derive(Debug, Default, Serialize)]
+pub struct FocusChange {
+    pub focus_stolen: bool,
+    pub previous_frontmost_app: Option<String>,
+    pub new_frontmost_app: Option<String>,
+}
+
+// How the listing after an action differs from the one before it, matched by element id
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct UiDiff {
+    pub added: usize,
+    pub removed: usize,
+    // Same element, different text, value or state
+    pub changed: usize,
+    pub summary: String,
+}
+
+// Find an element by its text and click it in one call
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClickByTextRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    pub query: String,
+    pub role: Option<String>,
+    // Only accept elements whose whole text equals the query (case-insensitive)
+    pub exact: Option<bool>,
+    // Click once more if the listing after the click is identical to the one before
+    #[serde(default)]
+    pub retry_if_unchanged: bool,
+    #[serde(default)]
+    pub restore_focus: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClickByTextResponse {
+    pub element_index: usize,
+    pub element: Value,
+    pub click: ClickByIndexResponse,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    pub ui_state_changed: Option<bool>,
+    // Whether the action changed the frontmost app, and from/to which
+    #[serde(flatten)]
+    pub focus: FocusChange,
 }
 
 #[derive(Debug, Serialize)]
@@ -277,24 +1028,123 @@ pub struct ClickByIndexResponse {
     pub success: bool,
     pub message: String,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the click was repeated because the first one left the UI unchanged
+    pub retried: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TypeByIndexRequest {
     pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
     pub text: String,
+    // Commit the value with AXConfirm (or Return) after typing; detected from the role when omitted
+    pub confirm_after: Option<bool>,
+    // Pause between the focus-click and the input, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+    // Refuse to type (FOCUS_MISMATCH error) when the focus-click focused some other element;
+    // otherwise a mismatch is only reported in focus_warning
+    #[serde(default)]
+    pub strict_focus: bool,
+    // How the text gets into the field, see TypingMethod
+    #[serde(default)]
+    pub method: TypingMethod,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+    // Focus the element by clicking it (default) or through the accessibility API, see FocusMethod
+    #[serde(default)]
+    pub focus_method: FocusMethod,
+}
+
+// Request for typeById: typeByIndex, addressed by the element_id of a listing entry
+#[derive(Debug, Deserialize)]
+pub struct TypeByIdRequest {
+    pub element_id: String,
+    // Which app's listing the id is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    pub text: String,
+    pub confirm_after: Option<bool>,
+    pub inter_action_delay_ms: Option<u64>,
+    #[serde(default)]
+    pub strict_focus: bool,
+    #[serde(default)]
+    pub method: TypingMethod,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+}
+
+// How typeByIndex and pressKeyByIndex give an element the keyboard focus first:
+// - click:         click it, like a user would; works on anything, but the click itself acts
+//                  (toggles a checkbox, follows a link, presses a button)
+// - accessibility: set AXFocused (or raise it and make it the app's focused element) without
+//                  touching the mouse; no side effects, but not every element accepts it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusMethod {
+    #[default]
+    Click,
+    Accessibility,
+}
+
+// How typeByIndex enters text:
+// - keystroke:     System Events keystrokes; behaves like a user typing (autocomplete, key
+//                  handlers fire) but mangles many non-ASCII characters and emoji
+// - paste:         put the text on the clipboard and press cmd+v, then restore the clipboard;
+//                  exact for any text, but some fields ignore or reformat pastes
+// - accessibility: set AXValue directly; exact and needs no focus, but apps that only listen
+//                  for key events may not notice the change
+// - auto:          paste when the text has non-ASCII characters, keystroke otherwise
+// Keystroke and paste fall back to accessibility when they fail.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypingMethod {
+    #[default]
+    Auto,
+    Keystroke,
+    Paste,
+    Accessibility,
+}
+
+impl TypingMethod {
+    // The concrete method auto stands for with this text
+    pub fn resolve(self, text: &str) -> TypingMethod {
+        match self {
+            TypingMethod::Auto if text.is_ascii() => TypingMethod::Keystroke,
+            TypingMethod::Auto => TypingMethod::Paste,
+            method => method,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct TypeByIndexResponse {
     pub success: bool,
     pub message: String,
+    pub confirmed: bool,
+    // Value read back from the field after typing (and confirming)
+    pub committed_value: Option<String>,
+    pub value_matches: Option<bool>,
+    // FOCUS_MISMATCH when the keyboard focus wasn't on the target as typing started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_warning: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PressKeyByIndexRequest {
     pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
     pub key_combo: String,
+    // Pause between the focus-click and the input, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+    // Focus restoring, screenshot and refresh around the action, see ActionOptions
+    #[serde(flatten)]
+    pub options: ActionOptions,
+    // Focus the element by clicking it (default) or through the accessibility API, see FocusMethod
+    #[serde(default)]
+    pub focus_method: FocusMethod,
 }
 
 #[derive(Debug, Serialize)]
@@ -307,6 +1157,8 @@ pub struct PressKeyByIndexResponse {
 #[derive(Debug, Deserialize)]
 pub struct InputControlRequest {
     pub action: InputAction,
+    // Wait before listing the app after the action, overriding REFRESH_DELAY_MS
+    pub delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -316,6 +1168,102 @@ pub enum InputAction {
     MouseMove { x: i32, y: i32 },
     MouseClick(String),
     WriteText(String),
+    // Raw hardware events by virtual key code, for holding keys or custom sequences
+    KeyDown(u16),
+    KeyUp(u16),
+}
+
+// Request for executeBatch: steps run in order and stop at the first one that fails
+#[derive(Debug, Deserialize)]
+pub struct ExecuteBatchRequest {
+    pub actions: Vec<BatchAction>,
+    // Which app's listing the by-index steps are from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // List the app after every step, not only after the last one
+    #[serde(default)]
+    pub refresh_after_each: bool,
+    // Pause between steps, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+    // Wait before listing the app after the action, overriding REFRESH_DELAY_MS
+    pub delay_ms: Option<u64>,
+    // Attach a small screenshot and an element summary to every step that ran, the failing
+    // one included, to see where a sequence went wrong (slow: lists the app after each step)
+    #[serde(default)]
+    pub debug_capture: bool,
+}
+
+// One batch step: the inputControl actions, tagged the same way, plus by-index actions
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum BatchAction {
+    KeyPress(String),
+    MouseMove { x: i32, y: i32 },
+    MouseClick(String),
+    WriteText(String),
+    KeyDown(u16),
+    KeyUp(u16),
+    ClickByIndex {
+        element_index: usize,
+        click_count: Option<u32>,
+    },
+    TypeByIndex {
+        element_index: usize,
+        text: String,
+        confirm_after: Option<bool>,
+        #[serde(default)]
+        method: TypingMethod,
+    },
+    PressKeyByIndex {
+        element_index: usize,
+        key_combo: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct ExecuteBatchResponse {
+    // Whether every step ran
+    pub success: bool,
+    pub completed_steps: usize,
+    // Position of the step that failed; the ones after it were not run
+    pub failed_step: Option<usize>,
+    pub steps: Vec<BatchStepResult>,
+    // Listing after the last step that ran
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether that listing differs from the one before the batch (None if unknown)
+    pub ui_state_changed: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct BatchStepResult {
+    pub step: usize,
+    // The step's type, e.g. "ClickByIndex"
+    pub action: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    // The error body the step's own endpoint would have answered with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+    // Listing after this step, with refresh_after_each
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elements: Option<ListElementsAndAttributesResponse>,
+    // The app's window after this step, with debug_capture
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<ActionScreenshot>,
+    // What the app listed after this step, with debug_capture
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_summary: Option<StepElementSummary>,
+}
+
+// A few lines' worth of a listing, for debug_capture
+#[derive(Debug, Serialize)]
+pub struct StepElementSummary {
+    pub element_count: usize,
+    // Most common roles first
+    pub roles: Vec<RoleCount>,
+    // Where the keyboard focus was, if a listed element had it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focused: Option<Value>,
 }
 
 #[derive(Serialize)]
@@ -328,6 +1276,8 @@ pub struct InputControlResponse {
 pub struct InputControlWithElementsResponse {
     pub input: InputControlResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
+    // Whether the refreshed listing differs from the one before the input (None if unknown)
+    pub ui_state_changed: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -359,3 +1309,593 @@ pub struct OpenUrlWithElementsResponse {
     pub url: OpenUrlResponse,
     pub elements: Option<ListElementsAndAttributesResponse>,
 }
+
+// Drag a file out of Finder and drop it on a listed element
+#[derive(Debug, Deserialize)]
+pub struct DragFileToIndexRequest {
+    pub path: String,
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub restore_focus: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DragFileToIndexResponse {
+    pub success: bool,
+    pub message: String,
+    // Where the file's Finder icon was picked up and where it was dropped
+    pub drag_start: ScreenPoint,
+    pub drop_point: ScreenPoint,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DescribeByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DescribeByIndexResponse {
+    pub element_index: usize,
+    // e.g. "enabled 'Send' button at top-right of the window, part of the 'Compose' toolbar"
+    pub description: String,
+}
+
+// List elements of several apps at once, into one shared index space
+#[derive(Debug, Default, Deserialize)]
+pub struct ListAllElementsRequest {
+    // Only walk these apps (display names or bundle ids); every running app when omitted
+    pub app_names: Option<Vec<String>>,
+    pub max_apps: Option<usize>,
+    pub max_elements: Option<usize>,
+    #[serde(default)]
+    pub format: ListFormat,
+    #[serde(default)]
+    pub classification: ClassificationProfile,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListAllElementsResponse {
+    // Apps that were walked, in listing order
+    pub apps: Vec<String>,
+    // Same shape as a single-app listing plus `app_name`; indices work with the by-index tools
+    pub elements: Vec<Value>,
+    pub cache_info: ElementCacheInfo,
+    // Set when the app or element cap stopped the walk early
+    pub truncated: bool,
+    pub processing_time_seconds: String,
+}
+
+// Zoom levels are factors: 1.0 is 100%
+#[derive(Debug, Deserialize)]
+pub struct GetZoomRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetZoomRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    pub zoom: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ZoomResponse {
+    // False when the app has neither a zoom control nor known zoom shortcuts
+    pub supported: bool,
+    pub zoom: Option<f64>,
+    // "control" (read/set on the app's zoom control) or "shortcuts" (browser Cmd+/Cmd-)
+    pub method: Option<String>,
+    pub message: String,
+}
+
+// Press a key repeatedly until an element with the given text is focused or selected
+#[derive(Debug, Deserialize)]
+pub struct PressUntilRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    pub key_combo: String,
+    pub condition: PressUntilCondition,
+    pub max_presses: Option<usize>,
+    // Pause after each press before checking, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PressUntilCondition {
+    // Case-insensitive substring of the element text (the whole text with `exact`)
+    pub text: String,
+    #[serde(default)]
+    pub state: PressUntilState,
+    #[serde(default)]
+    pub exact: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PressUntilState {
+    // The keyboard focus is on the element
+    Focused,
+    // The element is selected, e.g. a highlighted row or tab
+    #[default]
+    Selected,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PressUntilResponse {
+    // Whether the condition held before max_presses ran out
+    pub success: bool,
+    pub presses: usize,
+    pub message: String,
+    // The focused/selected element that satisfied the condition
+    pub matched: Option<Value>,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+// Open an app (or anything else Spotlight finds) by typing into Spotlight
+#[derive(Debug, Deserialize)]
+pub struct LaunchViaSpotlightRequest {
+    pub query: String,
+    // Press Return on the top result (default true); false leaves Spotlight open with the results
+    pub confirm: Option<bool>,
+    // How long to let Spotlight populate results before pressing Return (default 800ms)
+    pub results_delay_ms: Option<u64>,
+    // How long to wait for the frontmost app to change after confirming (default 5000ms)
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchViaSpotlightResponse {
+    pub success: bool,
+    pub message: String,
+    pub confirmed: bool,
+    // Frontmost app once the launch settled (Spotlight itself when not confirmed)
+    pub frontmost_app: Option<String>,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+// Read a table, outline or list from the last listing as rows of cell text
+#[derive(Debug, Deserialize)]
+pub struct GetTableByIndexRequest {
+    // The table itself, or the scroll area wrapping it
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // Rows to read at most (default 200, at most 1000)
+    pub max_rows: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetTableByIndexResponse {
+    pub element_index: usize,
+    // AXTable, AXOutline or AXList
+    pub role: String,
+    // Column titles, when the table has a header row
+    pub header: Option<Vec<String>>,
+    // Cell text by row and column; rows are padded to column_count
+    pub rows: Vec<Vec<String>>,
+    // Outlines only: nesting level of each row (0 = top level), parallel to rows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depths: Option<Vec<usize>>,
+    pub column_count: usize,
+    // Whether rows beyond max_rows were left out
+    pub truncated: bool,
+}
+
+// Step an incrementor (stepper) or slider up or down a number of times
+#[derive(Debug, Deserialize)]
+pub struct StepByIndexRequest {
+    // The stepper itself, or the field next to it
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    pub direction: StepDirection,
+    // How many steps to take (default 1, at most 100)
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepDirection {
+    Increment,
+    Decrement,
+}
+
+impl StepDirection {
+    // The accessibility action that takes one step in this direction
+    pub fn ax_action(self) -> &'static str {
+        match self {
+            StepDirection::Increment => "AXIncrement",
+            StepDirection::Decrement => "AXDecrement",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StepByIndexResponse {
+    pub success: bool,
+    pub steps: u32,
+    // Value of the field (or the stepper itself) after stepping
+    pub value: Option<String>,
+    pub message: String,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+// Run an arbitrary AppleScript (only when ENABLE_RAW_APPLESCRIPT is set)
+#[derive(Debug, Deserialize)]
+pub struct RunAppleScriptRequest {
+    pub script: String,
+    // Kill the script after this long (default 30000ms, at most 120000ms)
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunAppleScriptResponse {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    // None when osascript was killed by a signal
+    pub exit_code: Option<i32>,
+    // Always present: this tool runs with the server's full Automation permissions
+    pub warning: String,
+}
+
+// Read the banners and alerts currently shown by Notification Center
+#[derive(Debug, Default, Deserialize)]
+pub struct ListNotificationsRequest {
+    // Only notifications from this app (case-insensitive substring of the app name)
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListNotificationsResponse {
+    // Newest first, as Notification Center stacks them; empty when none are showing
+    pub notifications: Vec<NotificationInfo>,
+    // Why the list may be incomplete, e.g. Notification Center isn't running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotificationInfo {
+    pub app: Option<String>,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: Option<String>,
+}
+
+// Press Tab until the field with the given label has keyboard focus
+#[derive(Debug, Deserialize)]
+pub struct TabToFieldRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    // Case-insensitive substring of the field's label (the whole label with `exact`)
+    pub label: String,
+    #[serde(default)]
+    pub exact: bool,
+    // Give up after this many tabs (default 20, at most 200)
+    pub max_tabs: Option<usize>,
+    // Tab backwards with Shift+Tab
+    #[serde(default)]
+    pub reverse: bool,
+    pub inter_action_delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TabToFieldResponse {
+    // Whether the labelled field got focus before max_tabs ran out
+    pub success: bool,
+    pub tabs: usize,
+    pub message: String,
+    // The focused field that matched the label
+    pub focused: Option<Value>,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+// Read the text of an element together with its subtree
+#[derive(Debug, Deserialize)]
+pub struct GetTextByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    // Levels of children to include (0 = the element's own text; default 10, at most 25)
+    pub include_children: Option<usize>,
+    // Text the element should contain, e.g. what was just typed into it; sets `contains_expected`
+    pub expected_text: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetTextByIndexResponse {
+    pub element_index: usize,
+    pub role: String,
+    // Distinct texts of the element and its subtree in tree order, one per line
+    pub text: String,
+    pub depth: usize,
+    // The element's own current value (AXValue), what a text field holds right now
+    pub value: Option<String>,
+    // Whether value or text contains expected_text; null when none was given
+    pub contains_expected: Option<bool>,
+}
+
+// Screenshot an area and recognize the text in it (pure-vision fallback)
+#[derive(Debug, Default, Deserialize)]
+pub struct OcrScreenRequest {
+    // Capture this app's front window; the whole main display when neither this nor region is given
+    pub app_name: Option<String>,
+    pub bundle_id: Option<String>,
+    // Capture exactly this screen area (wins over app_name)
+    pub region: Option<ElementBounds>,
+    // Drop recognized text below this confidence (0-1, default 0.3)
+    pub min_confidence: Option<f32>,
+    // Attach the captured image (default true), downscaled to image_max_width (default 1280)
+    pub include_image: Option<bool>,
+    pub image_max_width: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OcrScreenResponse {
+    // The captured area in screen points
+    pub region: ElementBounds,
+    // Recognized lines of text, top to bottom
+    pub texts: Vec<OcrText>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ActionScreenshot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OcrText {
+    pub text: String,
+    pub confidence: f32,
+    // In screen points, ready for inputControl MouseMove/MouseClick
+    pub bounds: ElementBounds,
+    pub center: ScreenPoint,
+}
+
+// Answer whatever sheet or dialog is up by keyboard instead of indexing its buttons
+#[derive(Debug, Deserialize)]
+pub struct DismissDialogRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    #[serde(default)]
+    pub mode: DismissMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DismissMode {
+    // Return, which presses the default (highlighted) button
+    #[default]
+    Default,
+    // Escape, which presses Cancel or closes the sheet
+    Cancel,
+}
+
+impl DismissMode {
+    // Name of the key that answers the dialog this way
+    pub fn key(self) -> &'static str {
+        match self {
+            DismissMode::Default => "return",
+            DismissMode::Cancel => "escape",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DismissDialogResponse {
+    pub success: bool,
+    pub mode: DismissMode,
+    pub message: String,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+// Where the insertion point sits in a text element from the last listing
+#[derive(Debug, Deserialize)]
+pub struct GetCaretByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetCaretByIndexResponse {
+    pub element_index: usize,
+    pub role: String,
+    // None when the element has no caret, e.g. a button or static text
+    pub caret: Option<TextCaret>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TextCaret {
+    // Character offset of the insertion point (the selection's start when text is selected)
+    pub offset: usize,
+    // Length of the selected text, 0 for a plain caret
+    pub selection_length: usize,
+    // The caret (or the selection) on screen, when the app reports it
+    pub bounds: Option<ElementBounds>,
+}
+
+// Everything the accessibility API reports for one element of the last listing, unfiltered
+#[derive(Debug, Deserialize)]
+pub struct GetElementByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetElementByIndexResponse {
+    pub element_index: usize,
+    pub app_name: String,
+    pub role: String,
+    pub label: Option<String>,
+    pub value: Option<String>,
+    pub description: Option<String>,
+    // Every attribute, including the ones listings leave out (parents, children, windows)
+    pub properties: std::collections::BTreeMap<String, Option<Value>>,
+    pub bounds: Option<ElementBounds>,
+    pub enabled: Option<bool>,
+    pub focused: Option<bool>,
+    pub visible: Option<bool>,
+    pub actions: Vec<String>,
+}
+
+// Size of an app's accessibility tree, to pick a listing strategy before listing
+#[derive(Debug, Deserialize)]
+pub struct GetAppMetricsRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetAppMetricsResponse {
+    pub app_name: Option<String>,
+    pub bundle_id: Option<String>,
+    // Every element under the app, including ones a listing would exclude
+    pub element_count: usize,
+    // Levels below the application element (its windows are at depth 1)
+    pub max_depth: usize,
+    pub window_count: usize,
+    // Elements whose role counts as interactable, see is_non_interactable
+    pub interactable_count: usize,
+    // Set when the walk stopped at its element limit, so the counts are lower bounds
+    pub truncated: bool,
+    pub processing_time_ms: u64,
+}
+
+// Clear, type, verify and optionally confirm a field in one call
+#[derive(Debug, Deserialize)]
+pub struct SetFieldByIndexRequest {
+    pub element_index: usize,
+    // Which app's listing the index is from (default: the most recent listing)
+    pub app_name: Option<String>,
+    pub text: String,
+    // Paste through the clipboard instead of typing; by default only for long or non-ASCII text
+    pub use_clipboard: Option<bool>,
+    // Commit with AXConfirm (or Return) after verifying; detected from the role when omitted
+    pub confirm: Option<bool>,
+    // How often to try getting the value to read back right (default 2, at most 5)
+    pub max_attempts: Option<u32>,
+    // Pause between steps, overriding INTER_ACTION_DELAY_MS
+    pub inter_action_delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetFieldByIndexResponse {
+    // The field reads back exactly the requested text (or can't be read back, like a password field)
+    pub success: bool,
+    // Each focus/clear/type/verify/confirm sub-step in the order it ran
+    pub steps: Vec<SetFieldStep>,
+    // Value read back at the end
+    pub value: Option<String>,
+    pub attempts: u32,
+    pub elements: Option<ListElementsAndAttributesResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetFieldStep {
+    // "focus", "clear", "type", "verify" or "confirm"
+    pub step: &'static str,
+    pub success: bool,
+    // How the step was done, e.g. "AXValue", "select all + delete", "keystroke" or "clipboard"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// Request for resetInputState: release held keys and buttons, park the cursor, put a clipboard back
+#[derive(Debug, Deserialize)]
+pub struct ResetInputStateRequest {
+    // Where to move the cursor (defaults to INPUT_HOME_POSITION; left alone if neither is set)
+    pub home: Option<ScreenPoint>,
+    // Text to put back on the clipboard, e.g. what a test saved before it started
+    pub clipboard: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResetInputStateResponse {
+    pub success: bool,
+    // Where the cursor was moved, if anywhere
+    pub cursor: Option<ScreenPoint>,
+    pub clipboard_restored: bool,
+    pub message: String,
+}
+
+// Screenshot of a display or an app's front window, for models that reason over pixels
+#[derive(Debug, Deserialize)]
+pub struct CaptureScreenRequest {
+    // Capture this app's front window instead of a whole display
+    pub app_name: Option<String>,
+    pub bundle_id: Option<String>,
+    // Display to capture, numbered as screencapture does (1 = main, the default)
+    pub display: Option<u32>,
+    // Downscale to at most this many pixels wide / encode as JPEG with this quality (1-100)
+    pub max_width: Option<u32>,
+    pub quality: Option<u8>,
+    // Draw each cached element's box labelled with its index (set-of-marks), from the listing
+    // of app_name or else the latest listing; window or main display captures only
+    #[serde(default)]
+    pub annotate: bool,
+    // Mark at most this many elements, lowest indices first
+    pub annotate_max_elements: Option<usize>,
+    // Skip roles that aren't interactable (groups, static text, ...) to reduce clutter
+    #[serde(default)]
+    pub annotate_interactable_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureScreenResponse {
+    pub format: String, // "png" or "jpeg"
+    pub image_base64: String,
+    // The captured window in screen points, for mapping image pixels back to click coordinates
+    pub region: Option<ElementBounds>,
+    pub display: Option<u32>,
+    // Element indices drawn on the image, when annotating
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marked_indices: Option<Vec<usize>>,
+}
+
+// One numbered box drawn on an annotated screenshot, in screen points
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ScreenMark {
+    pub index: usize,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// Wait until an element matching a role and/or text shows up in an app
+#[derive(Debug, Deserialize)]
+pub struct WaitForElementRequest {
+    #[serde(default)]
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    // Predicate for the element; at least one must be given
+    pub role: Option<String>,          // e.g. "AXButton" or "button"
+    pub text_contains: Option<String>, // case-insensitive substring of the element text
+    // Give up after this long (default 5000, at most 60000)
+    pub timeout_ms: Option<u64>,
+    // Pause between listings (default 250, at least 100)
+    pub poll_interval_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct WaitForElementResponse {
+    pub element_index: usize,
+    pub element: Value,
+    // The listing the element was found in, now the cached one for by-index calls
+    pub elements: ListElementsAndAttributesResponse,
+    pub waited_ms: u64,
+    pub attempts: u32,
+}
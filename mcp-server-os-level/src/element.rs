@@ -2,6 +2,7 @@ use crate::errors::AutomationError;
 use crate::selector::Selector;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use super::{ClickResult, Locator};
 
@@ -30,6 +31,8 @@ pub(crate) trait UIElementImpl: Send + Sync + Debug {
     fn children(&self) -> Result<Vec<UIElement>, AutomationError>;
     fn parent(&self) -> Result<Option<UIElement>, AutomationError>;
     fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError>; // x, y, width, height
+    fn process_id(&self) -> Result<i32, AutomationError>;
+    fn screenshot(&self) -> Result<Vec<u8>, AutomationError>; // PNG bytes
     fn click(&self) -> Result<ClickResult, AutomationError>;
     fn double_click(&self) -> Result<ClickResult, AutomationError>;
     fn right_click(&self) -> Result<(), AutomationError>;
@@ -39,10 +42,14 @@ pub(crate) trait UIElementImpl: Send + Sync + Debug {
     fn press_key(&self, key: &str) -> Result<(), AutomationError>;
     fn get_text(&self, max_depth: usize) -> Result<String, AutomationError>;
     fn set_value(&self, value: &str) -> Result<(), AutomationError>;
+    fn set_bool_attribute(&self, attribute: &str, value: bool) -> Result<(), AutomationError>;
+    fn selected_text_range(&self) -> Result<Option<(usize, usize)>, AutomationError>;
+    fn bounds_for_range(&self, location: usize, length: usize) -> Result<Option<(f64, f64, f64, f64)>, AutomationError>;
     fn is_enabled(&self) -> Result<bool, AutomationError>;
     fn is_visible(&self) -> Result<bool, AutomationError>;
     fn is_focused(&self) -> Result<bool, AutomationError>;
     fn perform_action(&self, action: &str) -> Result<(), AutomationError>;
+    fn available_actions(&self) -> Result<Vec<String>, AutomationError>;
     fn as_any(&self) -> &dyn std::any::Any;
     fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError>;
     fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError>;
@@ -87,6 +94,16 @@ impl UIElement {
         self.inner.bounds()
     }
 
+    /// Get the process id of the application that owns this element
+    pub fn process_id(&self) -> Result<i32, AutomationError> {
+        self.inner.process_id()
+    }
+
+    /// Capture this element's on-screen area as PNG bytes, at the display's native resolution
+    pub fn screenshot(&self) -> Result<Vec<u8>, AutomationError> {
+        self.inner.screenshot()
+    }
+
     /// Click on this element
     pub fn click(&self) -> Result<ClickResult, AutomationError> {
         self.inner.click()
@@ -132,6 +149,23 @@ impl UIElement {
         self.inner.set_value(value)
     }
 
+    /// Set a boolean attribute on this element, e.g. AXEnhancedUserInterface on an application
+    pub fn set_bool_attribute(&self, attribute: &str, value: bool) -> Result<(), AutomationError> {
+        self.inner.set_bool_attribute(attribute, value)
+    }
+
+    /// Get the selected text range as (character offset, length); the length is 0 for a
+    /// plain caret. `None` when the element has no text selection, e.g. a button
+    pub fn selected_text_range(&self) -> Result<Option<(usize, usize)>, AutomationError> {
+        self.inner.selected_text_range()
+    }
+
+    /// Get the screen bounds (x, y, width, height) of a range of this element's text,
+    /// `None` when the element can't tell
+    pub fn bounds_for_range(&self, location: usize, length: usize) -> Result<Option<(f64, f64, f64, f64)>, AutomationError> {
+        self.inner.bounds_for_range(location, length)
+    }
+
     /// Check if element is enabled
     pub fn is_enabled(&self) -> Result<bool, AutomationError> {
         self.inner.is_enabled()
@@ -152,6 +186,30 @@ impl UIElement {
         self.inner.perform_action(action)
     }
 
+    /// Names of the actions this element supports, e.g. "AXPress" or "AXShowMenu"
+    pub fn available_actions(&self) -> Result<Vec<String>, AutomationError> {
+        self.inner.available_actions()
+    }
+
+    /// Poll this element's attributes every `interval` until `predicate` returns true or
+    /// `timeout` elapses. Returns whether the predicate was satisfied.
+    pub async fn wait_for<F>(&self, mut predicate: F, timeout: Duration, interval: Duration) -> bool
+    where
+        F: FnMut(&UIElementAttributes) -> bool,
+    {
+        let start = std::time::Instant::now();
+
+        loop {
+            if predicate(&self.attributes()) {
+                return true;
+            }
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            tokio::time::sleep(interval.min(timeout.saturating_sub(start.elapsed()))).await;
+        }
+    }
+
     /// Get the underlying implementation as a specific type
     pub(crate) fn as_any(&self) -> &dyn std::any::Any {
         self.inner.as_any()
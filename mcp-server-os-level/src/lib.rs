@@ -15,8 +15,8 @@ mod tests;
 
 pub use element::{UIElement, UIElementAttributes};
 pub use errors::AutomationError;
-pub use locator::Locator;
-pub use selector::Selector;
+pub use locator::{sort_elements, Locator};
+pub use selector::{QueryOp, QueryPredicate, Selector};
 
 // Define a new struct to hold click result information - move to module level
 pub struct ClickResult {
@@ -25,6 +25,14 @@ pub struct ClickResult {
     pub details: String,
 }
 
+/// Mouse button for `Desktop::click_mouse`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
 /// The main entry point for UI automation
 pub struct Desktop {
     engine: Arc<dyn platforms::AccessibilityEngine>,
@@ -59,16 +67,72 @@ impl Desktop {
         self.engine.get_applications()
     }
 
+    /// List the menu bar status items of all running apps, left to right as the system reports them
+    pub fn menu_bar_extras(&self) -> Result<Vec<UIElement>, AutomationError> {
+        self.engine.get_menu_bar_extras()
+    }
+
     /// Find an application by name
     pub fn application(&self, name: &str) -> Result<UIElement, AutomationError> {
         self.engine.get_application_by_name(name)
     }
 
+    /// Find a running application by bundle identifier (e.g. "com.apple.Safari")
+    pub fn application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        self.engine.get_application_by_bundle_id(bundle_id)
+    }
+
+    /// Get the bundle identifier of the application with this process id
+    pub fn bundle_id(&self, pid: i32) -> Result<Option<String>, AutomationError> {
+        self.engine.get_bundle_id(pid)
+    }
+
+    /// Post a raw key-down or key-up event, e.g. to hold a key in a game
+    pub fn post_key_event(&self, key_code: u16, key_down: bool) -> Result<(), AutomationError> {
+        self.engine.post_key_event(key_code, key_down)
+    }
+
+    /// Drag with the left mouse button from one screen point to another
+    pub fn drag_mouse(&self, from: (f64, f64), to: (f64, f64)) -> Result<(), AutomationError> {
+        self.engine.drag_mouse(from, to)
+    }
+
+    /// Move the mouse cursor to a screen point without clicking
+    pub fn move_mouse(&self, to: (f64, f64)) -> Result<(), AutomationError> {
+        self.engine.move_mouse(to)
+    }
+
+    /// Click a mouse button at a screen point, or where the cursor is; 2 or 3 clicks
+    /// arrive as a double or triple click
+    pub fn click_mouse(&self, at: Option<(f64, f64)>, button: MouseButton, click_count: u32) -> Result<(), AutomationError> {
+        self.engine.click_mouse(at, button, click_count)
+    }
+
+    /// Press a key or combo like "cmd+shift+4" in the focused app ("cmd" is Ctrl on Windows)
+    pub fn press_key_combo(&self, key_combo: &str) -> Result<(), AutomationError> {
+        self.engine.press_key_combo(key_combo)
+    }
+
+    /// Type text into the focused app, whatever the keyboard layout
+    pub fn type_text(&self, text: &str) -> Result<(), AutomationError> {
+        self.engine.type_text(text)
+    }
+
+    /// Release every modifier key and mouse button, e.g. after a crashed drag or held key
+    pub fn release_held_input(&self) -> Result<(), AutomationError> {
+        self.engine.release_held_input()
+    }
+
     /// Open an application by name
     pub fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
         self.engine.open_application(app_name)
     }
 
+    /// Open an application by bundle identifier (e.g. "com.apple.Safari")
+    pub fn open_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        self.engine.open_application_by_bundle_id(bundle_id)
+    }
+
     /// Open a URL in a specified browser (or default browser if None)
     pub fn open_url(&self, url: &str, browser: Option<&str>) -> Result<UIElement, AutomationError> {
         self.engine.open_url(url, browser)
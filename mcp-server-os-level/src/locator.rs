@@ -67,6 +67,17 @@ impl Locator {
         }
     }
 
+    /// Get all elements matching this locator in a stable order
+    ///
+    /// The accessibility tree doesn't guarantee sibling order between traversals, so
+    /// elements are sorted top-to-bottom, left-to-right by their bounds, then by role
+    /// and label. Listing an unchanged UI twice yields the same sequence.
+    pub fn all_sorted(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let mut elements = self.all()?;
+        sort_elements(&mut elements);
+        Ok(elements)
+    }
+
     /// Wait for an element to be available
     pub async fn wait(&self) -> Result<UIElement, AutomationError> {
         let start = std::time::Instant::now();
@@ -117,3 +128,30 @@ impl Locator {
         self.wait().await?.text(max_depth)
     }
 }
+
+/// Sort elements by position, then role, then label
+///
+/// Bounds are rounded to whole points so sub-pixel jitter between reads doesn't
+/// reorder elements; elements without bounds go last.
+pub fn sort_elements(elements: &mut [UIElement]) {
+    elements.sort_by_cached_key(|element| {
+        let bounds = element
+            .bounds()
+            .ok()
+            .map(|(x, y, width, height)| {
+                (
+                    y.round() as i64,
+                    x.round() as i64,
+                    width.round() as i64,
+                    height.round() as i64,
+                )
+            });
+        let attributes = element.attributes();
+        (
+            bounds.is_none(),
+            bounds,
+            attributes.role,
+            attributes.label.unwrap_or_default(),
+        )
+    });
+}
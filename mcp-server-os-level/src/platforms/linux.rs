@@ -1,7 +1,7 @@
 use crate::operator::element::UIElementImpl;
 use crate::operator::platforms::AccessibilityEngine;
 use crate::operator::ClickResult;
-use crate::operator::{AutomationError, Locator, Selector, UIElement, UIElementAttributes};
+use crate::operator::{AutomationError, Locator, MouseButton, Selector, UIElement, UIElementAttributes};
 use std::fmt::Debug;
 
 pub struct LinuxEngine;
@@ -63,6 +63,72 @@ impl AccessibilityEngine for LinuxEngine {
         ))
     }
 
+    fn get_application_by_bundle_id(&self, _bundle_id: &str) -> Result<UIElement, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn get_bundle_id(&self, _pid: i32) -> Result<Option<String>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn post_key_event(&self, _key_code: u16, _key_down: bool) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn drag_mouse(&self, _from: (f64, f64), _to: (f64, f64)) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn move_mouse(&self, _to: (f64, f64)) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn click_mouse(&self, _at: Option<(f64, f64)>, _button: MouseButton, _click_count: u32) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn press_key_combo(&self, _key_combo: &str) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn type_text(&self, _text: &str) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn release_held_input(&self) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn get_menu_bar_extras(&self) -> Result<Vec<UIElement>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn open_application_by_bundle_id(&self, _bundle_id: &str) -> Result<UIElement, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
     fn open_url(&self, _url: &str, _browser: Option<&str>) -> Result<UIElement, AutomationError> {
         Err(AutomationError::UnsupportedPlatform(
             "Linux implementation is not yet available".to_string(),
@@ -120,6 +186,18 @@ impl UIElementImpl for LinuxUIElement {
         ))
     }
 
+    fn process_id(&self) -> Result<i32, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn screenshot(&self) -> Result<Vec<u8>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
     fn click(&self) -> Result<ClickResult, AutomationError> {
         Err(AutomationError::UnsupportedPlatform(
             "Linux implementation is not yet available".to_string(),
@@ -174,6 +252,30 @@ impl UIElementImpl for LinuxUIElement {
         ))
     }
 
+    fn set_bool_attribute(&self, _attribute: &str, _value: bool) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn available_actions(&self) -> Result<Vec<String>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn selected_text_range(&self) -> Result<Option<(usize, usize)>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
+    fn bounds_for_range(&self, _location: usize, _length: usize) -> Result<Option<(f64, f64, f64, f64)>, AutomationError> {
+        Err(AutomationError::UnsupportedPlatform(
+            "Linux implementation is not yet available".to_string(),
+        ))
+    }
+
     fn is_enabled(&self) -> Result<bool, AutomationError> {
         Err(AutomationError::UnsupportedPlatform(
             "Linux implementation is not yet available".to_string(),
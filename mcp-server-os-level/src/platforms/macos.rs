@@ -1,7 +1,8 @@
 use crate::platforms::AccessibilityEngine;
 use crate::ClickResult;
 use crate::{
-    element::UIElementImpl, AutomationError, Locator, Selector, UIElement, UIElementAttributes,
+    element::UIElementImpl, AutomationError, Locator, MouseButton, QueryOp, QueryPredicate, Selector,
+    UIElement, UIElementAttributes,
 };
 
 use accessibility::AXUIElementAttributes;
@@ -10,15 +11,16 @@ use anyhow::Result;
 use core_foundation::array::{
     CFArrayGetCount, CFArrayGetTypeID, CFArrayGetValueAtIndex, __CFArray,
 };
-use core_foundation::base::{CFGetTypeID, TCFType};
+use core_foundation::base::{CFGetTypeID, CFRange, CFRelease, TCFType};
 use core_foundation::boolean::CFBoolean;
 use core_foundation::string::CFString;
 use core_graphics::event::CGEventType;
 use core_graphics::event::CGMouseButton;
 use core_graphics::event::CGEventTapLocation;
 use core_graphics::event_source::CGEventSourceStateID;
-use core_graphics::display::{CGPoint, CGSize};
-use core_graphics::event::{CGEvent, CGEventFlags, CGKeyCode};
+use core_graphics::display::{CGDisplay, CGPoint, CGSize};
+use core_graphics::geometry::CGRect;
+use core_graphics::event::{CGEvent, CGEventFlags, CGKeyCode, EventField};
 use core_graphics::event_source::CGEventSource;
 use serde_json::{self, Value};
 use std::collections::HashMap;
@@ -49,11 +51,23 @@ extern "C" {
         type_: u32,
         out: *mut ::std::os::raw::c_void,
     ) -> i32;
+    fn AXValueCreate(
+        type_: u32,
+        value: *const ::std::os::raw::c_void,
+    ) -> *const ::std::os::raw::c_void;
+    fn AXUIElementCopyParameterizedAttributeValue(
+        element: *mut ::std::os::raw::c_void,
+        attribute: *const ::std::os::raw::c_void,
+        parameter: *const ::std::os::raw::c_void,
+        value: *mut *const ::std::os::raw::c_void,
+    ) -> i32;
 }
 
 // Add these constant definitions instead - these are the official values from Apple's headers
 const K_AXVALUE_CGPOINT_TYPE: u32 = 1;
 const K_AXVALUE_CGSIZE_TYPE: u32 = 2;
+const K_AXVALUE_CGRECT_TYPE: u32 = 3;
+const K_AXVALUE_CFRANGE_TYPE: u32 = 4;
 
 // Add these constant definitions for key codes
 const KEY_RETURN: u16 = 36;
@@ -73,6 +87,83 @@ const MODIFIER_OPTION: CGEventFlags = CGEventFlags::CGEventFlagAlternate;
 const MODIFIER_CONTROL: CGEventFlags = CGEventFlags::CGEventFlagControl;
 const MODIFIER_FN: CGEventFlags = CGEventFlags::CGEventFlagSecondaryFn;
 
+// Virtual key code of a named key, or of a printable character on the ANSI layout
+fn key_code(key: &str) -> Result<u16, AutomationError> {
+    let key_map: HashMap<&str, u16> = [
+        ("return", KEY_RETURN),
+        ("enter", KEY_RETURN),
+        ("tab", KEY_TAB),
+        ("space", KEY_SPACE),
+        ("delete", KEY_DELETE),
+        ("backspace", KEY_DELETE),
+        ("esc", KEY_ESCAPE),
+        ("escape", KEY_ESCAPE),
+        ("left", KEY_ARROW_LEFT),
+        ("right", KEY_ARROW_RIGHT),
+        ("down", KEY_ARROW_DOWN),
+        ("up", KEY_ARROW_UP),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    let lower = key.to_lowercase();
+    if let Some(&code) = key_map.get(lower.as_str()) {
+        return Ok(code);
+    }
+
+    // Characters in key code order, so a character's position is its code on the ANSI
+    // layout; the gaps (\0) are keys that type nothing (10 is the ISO section key)
+    const ANSI_KEYS: &str = "asdfhgzxcv\0bqweryt123465=97-80]ou[ip\0lj'k;\\,/nm.\0\0`";
+    let mut chars = lower.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '\0' => ANSI_KEYS
+            .chars()
+            .position(|key| key == c)
+            .map(|position| position as u16)
+            .ok_or_else(|| AutomationError::InvalidArgument(format!("Unknown key: {}", key))),
+        _ => Err(AutomationError::InvalidArgument(format!("Unknown key: {}", key))),
+    }
+}
+
+// Split a combo like "cmd+shift+t" into the key's code and the modifier flags
+fn parse_key_combination(key_combo: &str) -> Result<(u16, CGEventFlags), AutomationError> {
+    let parts: Vec<String> = key_combo
+        .split('+')
+        .map(|s| s.trim().to_lowercase())
+        .collect();
+
+    if parts.iter().all(|part| part.is_empty()) {
+        return Err(AutomationError::InvalidArgument(
+            "Empty key combination".to_string(),
+        ));
+    }
+
+    // The last part is the actual key
+    let key = &parts[parts.len() - 1];
+    let key_code = key_code(key)?;
+
+    // All parts except the last one are modifiers
+    let mut flags = CGEventFlags::empty();
+    for modifier in &parts[0..parts.len() - 1] {
+        match modifier.as_str() {
+            "cmd" | "command" => flags.insert(MODIFIER_COMMAND),
+            "shift" => flags.insert(MODIFIER_SHIFT),
+            "alt" | "option" => flags.insert(MODIFIER_OPTION),
+            "ctrl" | "control" => flags.insert(MODIFIER_CONTROL),
+            "fn" => flags.insert(MODIFIER_FN),
+            _ => {
+                return Err(AutomationError::InvalidArgument(format!(
+                    "Unknown modifier: {}",
+                    modifier
+                )))
+            }
+        }
+    }
+
+    Ok((key_code, flags))
+}
+
 // Thread-safe wrapper for AXUIElement
 #[derive(Clone)]
 pub struct ThreadSafeAXUIElement(Arc<AXUIElement>);
@@ -405,6 +496,65 @@ fn get_pid_for_element(element: &ThreadSafeAXUIElement) -> i32 {
     }
 }
 
+// Helper function to find the PID of a running application by bundle identifier
+fn find_pid_for_bundle_id(bundle_id: &str) -> Option<i32> {
+    unsafe {
+        use objc::{class, msg_send, sel, sel_impl};
+
+        let workspace_class = class!(NSWorkspace);
+        let shared_workspace: *mut objc::runtime::Object =
+            msg_send![workspace_class, sharedWorkspace];
+        let apps: *mut objc::runtime::Object = msg_send![shared_workspace, runningApplications];
+        let count: usize = msg_send![apps, count];
+
+        for i in 0..count {
+            let app: *mut objc::runtime::Object = msg_send![apps, objectAtIndex:i];
+            let bundle_obj: *mut objc::runtime::Object = msg_send![app, bundleIdentifier];
+
+            if !bundle_obj.is_null() {
+                let found_bundle: &str = {
+                    let nsstring = bundle_obj as *const objc::runtime::Object;
+                    let bytes: *const std::os::raw::c_char = msg_send![nsstring, UTF8String];
+                    let len: usize = msg_send![nsstring, lengthOfBytesUsingEncoding:4]; // NSUTF8StringEncoding = 4
+                    let bytes_slice = std::slice::from_raw_parts(bytes as *const u8, len);
+                    std::str::from_utf8_unchecked(bytes_slice)
+                };
+
+                // Bundle identifiers are case-insensitive on macOS
+                if found_bundle.eq_ignore_ascii_case(bundle_id) {
+                    let pid: i32 = msg_send![app, processIdentifier];
+                    return Some(pid);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn find_bundle_id_for_pid(pid: i32) -> Option<String> {
+    unsafe {
+        use objc::{class, msg_send, sel, sel_impl};
+
+        let app_class = class!(NSRunningApplication);
+        let app: *mut objc::runtime::Object =
+            msg_send![app_class, runningApplicationWithProcessIdentifier: pid];
+        if app.is_null() {
+            return None;
+        }
+
+        let bundle_obj: *mut objc::runtime::Object = msg_send![app, bundleIdentifier];
+        if bundle_obj.is_null() {
+            return None;
+        }
+
+        let bytes: *const std::os::raw::c_char = msg_send![bundle_obj, UTF8String];
+        let len: usize = msg_send![bundle_obj, lengthOfBytesUsingEncoding:4]; // NSUTF8StringEncoding = 4
+        let bytes_slice = std::slice::from_raw_parts(bytes as *const u8, len);
+        Some(String::from_utf8_lossy(bytes_slice).into_owned())
+    }
+}
+
 // Modified to return Vec<String> for multiple possible role matches
 fn map_generic_role_to_macos_roles(role: &str) -> Vec<String> {
     match role.to_lowercase().as_str() {
@@ -549,6 +699,36 @@ impl AccessibilityEngine for MacOSEngine {
 
         Ok(app_elements)
     }
+
+    fn get_menu_bar_extras(&self) -> Result<Vec<UIElement>, AutomationError> {
+        // Status items hang off each owning app's extras menu bar, and most of those
+        // owners are background agents, so look at every running process
+        let pids = get_running_application_pids(true)?;
+        let extras_attr = AXAttribute::new(&CFString::new("AXExtrasMenuBar"));
+
+        let mut items = Vec::new();
+        for pid in pids {
+            let app_element = ThreadSafeAXUIElement::application(pid);
+            let menu_bar = match app_element.0.attribute(&extras_attr) {
+                Ok(value) => match value.downcast_into::<AXUIElement>() {
+                    Some(menu_bar) => menu_bar,
+                    None => continue,
+                },
+                Err(_) => continue,
+            };
+
+            if let Ok(children) = menu_bar.children() {
+                trace!("pid {} owns {} menu bar extras", pid, children.len());
+                for child in children.iter() {
+                    items.push(self.wrap_element(ThreadSafeAXUIElement::new(child.clone())));
+                }
+            }
+        }
+
+        debug!("Found {} menu bar extras", items.len());
+        Ok(items)
+    }
+
     fn get_root_element(&self) -> UIElement {
         self.wrap_element(self.system_wide.clone())
     }
@@ -596,6 +776,178 @@ impl AccessibilityEngine for MacOSEngine {
         )))
     }
 
+    fn get_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        match find_pid_for_bundle_id(bundle_id) {
+            Some(pid) => {
+                debug!("found application with bundle id '{}': pid {}", bundle_id, pid);
+                Ok(self.wrap_element(ThreadSafeAXUIElement::application(pid)))
+            }
+            None => Err(AutomationError::ElementNotFound(format!(
+                "Application with bundle id '{}' not found",
+                bundle_id
+            ))),
+        }
+    }
+
+    fn get_bundle_id(&self, pid: i32) -> Result<Option<String>, AutomationError> {
+        Ok(find_bundle_id_for_pid(pid))
+    }
+
+    fn post_key_event(&self, key_code: u16, key_down: bool) -> Result<(), AutomationError> {
+        debug!("posting key {} event for key code {}", if key_down { "down" } else { "up" }, key_code);
+
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        let event = CGEvent::new_keyboard_event(source, key_code as CGKeyCode, key_down)
+            .map_err(|_| AutomationError::PlatformError("Failed to create keyboard event".to_string()))?;
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    fn drag_mouse(&self, from: (f64, f64), to: (f64, f64)) -> Result<(), AutomationError> {
+        debug!("dragging from ({}, {}) to ({}, {})", from.0, from.1, to.0, to.1);
+
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        let post = |event_type: CGEventType, (x, y): (f64, f64)| -> Result<(), AutomationError> {
+            let event = CGEvent::new_mouse_event(source.clone(), event_type, CGPoint::new(x, y), CGMouseButton::Left)
+                .map_err(|_| AutomationError::PlatformError("Failed to create mouse event".to_string()))?;
+            event.post(CGEventTapLocation::HID);
+            Ok(())
+        };
+
+        post(CGEventType::MouseMoved, from)?;
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        post(CGEventType::LeftMouseDown, from)?;
+        // Hold briefly so the source recognizes a drag rather than a click
+        std::thread::sleep(std::time::Duration::from_millis(150));
+
+        // Move in small steps; drop targets only highlight (and accept) after seeing drag movement over them
+        const STEPS: u32 = 20;
+        for step in 1..=STEPS {
+            let t = step as f64 / STEPS as f64;
+            let point = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+            post(CGEventType::LeftMouseDragged, point)?;
+            std::thread::sleep(std::time::Duration::from_millis(15));
+        }
+
+        // Let the target register the hover before dropping
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        post(CGEventType::LeftMouseUp, to)?;
+
+        debug!("dropped at ({}, {})", to.0, to.1);
+        Ok(())
+    }
+
+    fn move_mouse(&self, to: (f64, f64)) -> Result<(), AutomationError> {
+        debug!("moving mouse to ({}, {})", to.0, to.1);
+
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        let event = CGEvent::new_mouse_event(source, CGEventType::MouseMoved, CGPoint::new(to.0, to.1), CGMouseButton::Left)
+            .map_err(|_| AutomationError::PlatformError("Failed to create mouse event".to_string()))?;
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    fn click_mouse(&self, at: Option<(f64, f64)>, button: MouseButton, click_count: u32) -> Result<(), AutomationError> {
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        let location = match at {
+            Some((x, y)) => CGPoint::new(x, y),
+            None => CGEvent::new(source.clone())
+                .map_err(|_| AutomationError::PlatformError("Failed to read mouse position".to_string()))?
+                .location(),
+        };
+        let (down, up, cg_button) = match button {
+            MouseButton::Left => (CGEventType::LeftMouseDown, CGEventType::LeftMouseUp, CGMouseButton::Left),
+            MouseButton::Right => (CGEventType::RightMouseDown, CGEventType::RightMouseUp, CGMouseButton::Right),
+            MouseButton::Middle => (CGEventType::OtherMouseDown, CGEventType::OtherMouseUp, CGMouseButton::Center),
+        };
+        debug!("clicking {:?} button {} time(s) at ({}, {})", button, click_count, location.x, location.y);
+
+        if at.is_some() {
+            let event = CGEvent::new_mouse_event(source.clone(), CGEventType::MouseMoved, location, cg_button)
+                .map_err(|_| AutomationError::PlatformError("Failed to create mouse event".to_string()))?;
+            event.post(CGEventTapLocation::HID);
+        }
+
+        // Apps tell a double or triple click by the click state on each down/up pair,
+        // not by timing, so number the clicks 1..n
+        for click in 1..=click_count.max(1) {
+            for event_type in [down, up] {
+                let event = CGEvent::new_mouse_event(source.clone(), event_type, location, cg_button)
+                    .map_err(|_| AutomationError::PlatformError("Failed to create mouse event".to_string()))?;
+                event.set_integer_value_field(EventField::MOUSE_EVENT_CLICK_STATE, click as i64);
+                event.post(CGEventTapLocation::HID);
+            }
+        }
+        Ok(())
+    }
+
+    fn press_key_combo(&self, key_combo: &str) -> Result<(), AutomationError> {
+        let (key_code, flags) = parse_key_combination(key_combo)?;
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        for key_down in [true, false] {
+            let event = CGEvent::new_keyboard_event(source.clone(), key_code as CGKeyCode, key_down)
+                .map_err(|_| AutomationError::PlatformError("Failed to create keyboard event".to_string()))?;
+            event.set_flags(flags);
+            event.post(CGEventTapLocation::HID);
+        }
+        debug!("pressed key combination: {}", key_combo);
+        Ok(())
+    }
+
+    fn type_text(&self, text: &str) -> Result<(), AutomationError> {
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+        // A keyboard event carries at most 20 UTF-16 units of text; the key code is ignored
+        let units: Vec<u16> = text.encode_utf16().collect();
+        for chunk in units.chunks(20) {
+            for key_down in [true, false] {
+                let event = CGEvent::new_keyboard_event(source.clone(), 0, key_down)
+                    .map_err(|_| AutomationError::PlatformError("Failed to create keyboard event".to_string()))?;
+                event.set_string_from_utf16_unchecked(chunk);
+                event.post(CGEventTapLocation::HID);
+            }
+        }
+        debug!("typed {} characters", text.chars().count());
+        Ok(())
+    }
+
+    fn release_held_input(&self) -> Result<(), AutomationError> {
+        // Left and right command, shift, option and control, then fn
+        const MODIFIER_KEY_CODES: [u16; 9] = [55, 54, 56, 60, 58, 61, 59, 62, 63];
+
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::PlatformError("Failed to create event source".to_string()))?;
+
+        for key_code in MODIFIER_KEY_CODES {
+            let event = CGEvent::new_keyboard_event(source.clone(), key_code as CGKeyCode, false)
+                .map_err(|_| AutomationError::PlatformError("Failed to create keyboard event".to_string()))?;
+            event.set_flags(CGEventFlags::CGEventFlagNull);
+            event.post(CGEventTapLocation::HID);
+        }
+
+        // Release the buttons where the cursor is, so nothing gets dropped somewhere else
+        let location = CGEvent::new(source.clone())
+            .map_err(|_| AutomationError::PlatformError("Failed to read mouse position".to_string()))?
+            .location();
+        for (event_type, button) in [
+            (CGEventType::LeftMouseUp, CGMouseButton::Left),
+            (CGEventType::RightMouseUp, CGMouseButton::Right),
+            (CGEventType::OtherMouseUp, CGMouseButton::Center),
+        ] {
+            let event = CGEvent::new_mouse_event(source.clone(), event_type, location, button)
+                .map_err(|_| AutomationError::PlatformError("Failed to create mouse event".to_string()))?;
+            event.post(CGEventTapLocation::HID);
+        }
+
+        debug!("released modifier keys and mouse buttons at ({}, {})", location.x, location.y);
+        Ok(())
+    }
+
     fn find_element(
         &self,
         selector: &Selector,
@@ -790,6 +1142,28 @@ impl AccessibilityEngine for MacOSEngine {
             Selector::Filter(_) => Err(AutomationError::UnsupportedOperation(
                 "Filter selector not implemented".to_string(),
             )),
+            Selector::Query(predicates) => {
+                let predicates_owned = predicates.clone();
+                let collector = ElementFinderWithWindows::new(
+                    &self.system_wide.0,
+                    move |e| element_matches_query(e, &predicates_owned),
+                    None,
+                );
+                let walker: TreeWalkerWithWindows = TreeWalkerWithWindows::new();
+
+                walker.walk(start_element, &collector);
+
+                let ax_ui_element = match collector.find() {
+                    Ok(ax_ui_element) => ax_ui_element,
+                    Err(_) => {
+                        return Err(AutomationError::ElementNotFound(format!(
+                            "Element matching query {:?} not found",
+                            predicates
+                        )))
+                    }
+                };
+                Ok(self.wrap_element(ThreadSafeAXUIElement::new(ax_ui_element)))
+            }
         }
     }
 
@@ -888,6 +1262,27 @@ impl AccessibilityEngine for MacOSEngine {
             Selector::Chain(_) => Err(AutomationError::UnsupportedOperation(
                 "Chain selector not implemented for find_elements".to_string(),
             )),
+            Selector::Query(predicates) => {
+                let predicates_owned = predicates.clone();
+                let collector = ElementsCollectorWithWindows::new(start_element, move |e| {
+                    element_matches_query(e, &predicates_owned)
+                });
+
+                let ax_ui_elements = collector.find_all();
+                debug!(
+                    "found {} elements matching query {:?}",
+                    ax_ui_elements.len(),
+                    predicates
+                );
+
+                // Convert AXUIElements to UIElements
+                let ui_elements = ax_ui_elements
+                    .into_iter()
+                    .map(|e| self.wrap_element(ThreadSafeAXUIElement::new(e)))
+                    .collect();
+
+                Ok(ui_elements)
+            }
         }
     }
 
@@ -969,6 +1364,55 @@ impl AccessibilityEngine for MacOSEngine {
         self.get_application_by_name(app_name)
     }
 
+    fn open_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        debug!("opening application by bundle id: {}", bundle_id);
+
+        // Launch (or activate) the application by bundle identifier
+        let status = std::process::Command::new("open")
+            .args(["-b", bundle_id])
+            .status()
+            .map_err(|e| {
+                AutomationError::PlatformError(format!("failed to execute 'open' command: {}", e))
+            })?;
+
+        if !status.success() {
+            return Err(AutomationError::PlatformError(format!(
+                "failed to open application with bundle id '{}': exit code {:?}",
+                bundle_id,
+                status.code()
+            )));
+        }
+
+        // Wait for the process to appear and for its accessibility tree to answer
+        let max_retries = 50;
+        let retry_delay = std::time::Duration::from_millis(100);
+
+        for attempt in 0..max_retries {
+            if let Some(pid) = find_pid_for_bundle_id(bundle_id) {
+                let app_element = ThreadSafeAXUIElement::application(pid);
+
+                // The process can exist before it responds to accessibility queries
+                if app_element.0.role().is_ok() {
+                    debug!(
+                        "bundle '{}' is accessible with pid {} after {} attempts",
+                        bundle_id,
+                        pid,
+                        attempt + 1
+                    );
+                    return Ok(self.wrap_element(app_element));
+                }
+                trace!("bundle '{}' has pid {} but is not accessible yet", bundle_id, pid);
+            }
+
+            std::thread::sleep(retry_delay);
+        }
+
+        Err(AutomationError::Timeout(format!(
+            "application with bundle id '{}' did not become accessible",
+            bundle_id
+        )))
+    }
+
     fn open_url(&self, url: &str, browser: Option<&str>) -> Result<UIElement, AutomationError> {
         debug!("opening url: {} in browser: {:?}", url, browser);
 
@@ -1289,73 +1733,6 @@ impl MacOSUIElement {
         }
     }
 
-    fn get_key_code(&self, key: &str) -> Result<u16, AutomationError> {
-        let key_map: HashMap<&str, u16> = [
-            ("return", KEY_RETURN),
-            ("enter", KEY_RETURN),
-            ("tab", KEY_TAB),
-            ("space", KEY_SPACE),
-            ("delete", KEY_DELETE),
-            ("backspace", KEY_DELETE),
-            ("esc", KEY_ESCAPE),
-            ("escape", KEY_ESCAPE),
-            ("left", KEY_ARROW_LEFT),
-            ("right", KEY_ARROW_RIGHT),
-            ("down", KEY_ARROW_DOWN),
-            ("up", KEY_ARROW_UP),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        key_map
-            .get(key.to_lowercase().as_str())
-            .copied()
-            .ok_or_else(|| AutomationError::InvalidArgument(format!("Unknown key: {}", key)))
-    }
-
-    // Add a method to parse key combinations with modifiers
-    fn parse_key_combination(
-        &self,
-        key_combo: &str,
-    ) -> Result<(u16, CGEventFlags), AutomationError> {
-        // Change Vec<&str> to Vec<String> to match the to_lowercase() output type
-        let parts: Vec<String> = key_combo
-            .split('+')
-            .map(|s| s.trim().to_lowercase())
-            .collect();
-
-        if parts.is_empty() {
-            return Err(AutomationError::InvalidArgument(
-                "Empty key combination".to_string(),
-            ));
-        }
-
-        // The last part is the actual key
-        let key = &parts[parts.len() - 1];
-        let key_code = self.get_key_code(key)?;
-
-        // All parts except the last one are modifiers
-        let mut flags = CGEventFlags::empty();
-        for modifier in &parts[0..parts.len() - 1] {
-            match modifier.as_str() {
-                "cmd" | "command" => flags.insert(MODIFIER_COMMAND),
-                "shift" => flags.insert(MODIFIER_SHIFT),
-                "alt" | "option" => flags.insert(MODIFIER_OPTION),
-                "ctrl" | "control" => flags.insert(MODIFIER_CONTROL),
-                "fn" => flags.insert(MODIFIER_FN),
-                _ => {
-                    return Err(AutomationError::InvalidArgument(format!(
-                        "Unknown modifier: {}",
-                        modifier
-                    )))
-                }
-            }
-        }
-
-        Ok((key_code, flags))
-    }
-
     fn generate_stable_id(&self) -> String {
         let mut hasher = DefaultHasher::new();
 
@@ -1741,6 +2118,79 @@ impl UIElementImpl for MacOSUIElement {
         Ok((x, y, width, height))
     }
 
+    fn process_id(&self) -> Result<i32, AutomationError> {
+        match get_pid_for_element(&self.element) {
+            -1 => Err(AutomationError::PlatformError(
+                "failed to get pid for element".to_string(),
+            )),
+            pid => Ok(pid),
+        }
+    }
+
+    fn screenshot(&self) -> Result<Vec<u8>, AutomationError> {
+        let (x, y, width, height) = self.bounds()?;
+        if width <= 0.0 || height <= 0.0 {
+            return Err(AutomationError::InvalidArgument(
+                "element has no on-screen bounds".to_string(),
+            ));
+        }
+
+        // Refuse elements that don't overlap any display (scrolled away, hidden windows, ...)
+        let on_screen = CGDisplay::active_displays()
+            .map(|displays| {
+                displays.into_iter().any(|id| {
+                    let display = CGDisplay::new(id).bounds();
+                    x < display.origin.x + display.size.width
+                        && x + width > display.origin.x
+                        && y < display.origin.y + display.size.height
+                        && y + height > display.origin.y
+                })
+            })
+            .unwrap_or(true);
+        if !on_screen {
+            return Err(AutomationError::InvalidArgument(format!(
+                "element at ({}, {}) is offscreen",
+                x, y
+            )));
+        }
+
+        // screencapture takes the rect in points and captures at the display's backing
+        // scale, so on retina screens the image is 2x the element's point size
+        let path = std::env::temp_dir().join(format!("element-{}.png", uuid::Uuid::new_v4()));
+        let rect = format!(
+            "{},{},{},{}",
+            x.round(),
+            y.round(),
+            width.round(),
+            height.round()
+        );
+        debug!("capturing element rect {} to {:?}", rect, path);
+
+        let output = std::process::Command::new("screencapture")
+            .args(["-x", "-t", "png", "-R", &rect])
+            .arg(&path)
+            .output()
+            .map_err(|e| {
+                AutomationError::PlatformError(format!("failed to run screencapture: {}", e))
+            })?;
+
+        if !output.status.success() {
+            return Err(AutomationError::PlatformError(format!(
+                "screencapture failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let png = std::fs::read(&path).map_err(|e| {
+            AutomationError::PlatformError(format!(
+                "failed to read screenshot (is Screen Recording permission granted?): {}",
+                e
+            ))
+        });
+        let _ = std::fs::remove_file(&path);
+        png
+    }
+
     fn click(&self) -> Result<ClickResult, AutomationError> {
         // Use the default Auto selection
         self.click_with_method(ClickMethodSelection::Auto)
@@ -1921,7 +2371,7 @@ impl UIElementImpl for MacOSUIElement {
         }
 
         // Parse the key combination
-        let (key_code, flags) = self.parse_key_combination(key_combo)?;
+        let (key_code, flags) = parse_key_combination(key_combo)?;
 
         // Create event source
         let source =
@@ -2031,6 +2481,93 @@ impl UIElementImpl for MacOSUIElement {
         Ok(())
     }
 
+    fn set_bool_attribute(&self, attribute: &str, value: bool) -> Result<(), AutomationError> {
+        let cf_bool = if value { CFBoolean::true_value() } else { CFBoolean::false_value() };
+
+        unsafe {
+            let element_ref = self.element.0.as_concrete_TypeRef() as *mut ::std::os::raw::c_void;
+            let attr_str = CFString::new(attribute);
+            let attr_str_ref = attr_str.as_concrete_TypeRef() as *const ::std::os::raw::c_void;
+            let value_ref = cf_bool.as_concrete_TypeRef() as *const ::std::os::raw::c_void;
+
+            let result = AXUIElementSetAttributeValue(element_ref, attr_str_ref, value_ref);
+
+            if result != 0 {
+                debug!("Failed to set {}: error code {}", attribute, result);
+
+                return Err(AutomationError::PlatformError(format!(
+                    "Failed to set {}: error code {}",
+                    attribute, result
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn selected_text_range(&self) -> Result<Option<(usize, usize)>, AutomationError> {
+        // Elements without the attribute have no caret
+        let Ok(range) = self
+            .element
+            .0
+            .attribute(&AXAttribute::new(&CFString::new("AXSelectedTextRange")))
+        else {
+            return Ok(None);
+        };
+
+        unsafe {
+            let mut cf_range = CFRange { location: 0, length: 0 };
+            let range_ptr = &mut cf_range as *mut CFRange as *mut ::std::os::raw::c_void;
+
+            if AXValueGetValue(range.as_CFTypeRef() as *const _, K_AXVALUE_CFRANGE_TYPE, range_ptr) == 0 {
+                return Ok(None);
+            }
+            Ok(Some((cf_range.location.max(0) as usize, cf_range.length.max(0) as usize)))
+        }
+    }
+
+    fn bounds_for_range(&self, location: usize, length: usize) -> Result<Option<(f64, f64, f64, f64)>, AutomationError> {
+        let cf_range = CFRange { location: location as isize, length: length as isize };
+
+        unsafe {
+            let range_value = AXValueCreate(
+                K_AXVALUE_CFRANGE_TYPE,
+                &cf_range as *const CFRange as *const ::std::os::raw::c_void,
+            );
+            if range_value.is_null() {
+                return Err(AutomationError::PlatformError(
+                    "Failed to create range value".to_string(),
+                ));
+            }
+
+            let element_ref = self.element.0.as_concrete_TypeRef() as *mut ::std::os::raw::c_void;
+            let attr_str = CFString::new("AXBoundsForRange");
+            let attr_str_ref = attr_str.as_concrete_TypeRef() as *const ::std::os::raw::c_void;
+            let mut bounds_value: *const ::std::os::raw::c_void = std::ptr::null();
+
+            let result = AXUIElementCopyParameterizedAttributeValue(
+                element_ref,
+                attr_str_ref,
+                range_value,
+                &mut bounds_value,
+            );
+            CFRelease(range_value as _);
+
+            // Not every text element answers this, which is no error
+            if result != 0 || bounds_value.is_null() {
+                debug!("AXBoundsForRange unavailable: error code {}", result);
+                return Ok(None);
+            }
+
+            let mut rect = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(0.0, 0.0));
+            let rect_ptr = &mut rect as *mut CGRect as *mut ::std::os::raw::c_void;
+            let extracted = AXValueGetValue(bounds_value, K_AXVALUE_CGRECT_TYPE, rect_ptr) != 0;
+            CFRelease(bounds_value as _);
+
+            Ok(extracted.then(|| (rect.origin.x, rect.origin.y, rect.size.width, rect.size.height)))
+        }
+    }
+
     fn is_enabled(&self) -> Result<bool, AutomationError> {
         // not implemented
         Err(AutomationError::UnsupportedOperation(
@@ -2074,6 +2611,14 @@ impl UIElementImpl for MacOSUIElement {
             })
     }
 
+    fn available_actions(&self) -> Result<Vec<String>, AutomationError> {
+        let names = self.element.0.action_names().map_err(|e| {
+            AutomationError::PlatformError(format!("Failed to get action names: {}", e))
+        })?;
+
+        Ok(names.iter().map(|name| name.to_string()).collect())
+    }
+
     fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError> {
         // Get the platform-specific instance of the engine
         let engine = MacOSEngine::new(self.use_background_apps, self.activate_app)?;
@@ -2151,9 +2696,22 @@ impl UIElementImpl for MacOSUIElement {
             }
         };
 
-        // Create scroll wheel event
+        // Scroll events go to whatever is under the cursor, so move it over the element first
+        let mouse_move = CGEvent::new_mouse_event(
+            source.clone(),
+            CGEventType::MouseMoved,
+            CGPoint::new(center_x, center_y),
+            CGMouseButton::Left,
+        )
+        .map_err(|_| {
+            AutomationError::PlatformError("Failed to create mouse move event".to_string())
+        })?;
+        mouse_move.post(CGEventTapLocation::HID);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // Create scroll wheel event, in lines
         let scroll_event = CGEvent::new_scroll_event(
-            source, 0, 1, // number of wheels (1 for standard mouse wheel)
+            source, 1, 1, // number of wheels (1 for standard mouse wheel)
             scroll_y, scroll_x, 0, // z scroll amount (unused)
         )
         .map_err(|_| AutomationError::PlatformError("Failed to create scroll event".to_string()))?;
@@ -2185,12 +2743,26 @@ fn parse_ax_attribute_value(
     // Handle different types based on known attribute names and value types
     match name {
         // String values (text, identifiers, descriptions)
-        "AXRole" | "AXRoleDescription" | "AXIdentifier" | "AXValue" => {
+        "AXRole" | "AXRoleDescription" | "AXIdentifier" | "AXMenuItemMarkChar" => {
             if let Some(cf_string) = value.downcast_into::<CFString>() {
                 return Some(Value::String(cf_string.to_string()));
             }
         }
 
+        // Text for most elements, but numeric for progress indicators, sliders, etc.
+        "AXValue" => {
+            if let Some(cf_string) = value.downcast::<CFString>() {
+                return Some(Value::String(cf_string.to_string()));
+            }
+            if let Some(cf_num) = value.downcast_into::<CFNumber>() {
+                return cf_num
+                    .to_f64()
+                    .filter(|num| num.is_finite())
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number);
+            }
+        }
+
         // Boolean values
         "AXEnabled" | "AXFocused" => {
             if let Some(cf_bool) = value.downcast_into::<CFBoolean>() {
@@ -2199,7 +2771,7 @@ fn parse_ax_attribute_value(
         }
 
         // Numeric values
-        "AXNumberOfCharacters" | "AXInsertionPointLineNumber" => {
+        "AXNumberOfCharacters" | "AXInsertionPointLineNumber" | "AXMinValue" | "AXMaxValue" => {
             if let Some(cf_num) = value.downcast_into::<CFNumber>() {
                 if let Some(num) = cf_num.to_i64() {
                     return Some(Value::Number(serde_json::Number::from(num)));
@@ -2298,6 +2870,50 @@ fn parse_ax_attribute_value(
     None
 }
 
+// Check an element against every predicate of a query selector
+fn element_matches_query(e: &AXUIElement, predicates: &[QueryPredicate]) -> bool {
+    let string_value = |value: Option<String>| value.unwrap_or_default();
+    let title = || string_value(e.title().ok().map(|t| t.to_string()));
+    let value = || {
+        string_value(
+            e.value()
+                .ok()
+                .and_then(|v| v.downcast_into::<CFString>())
+                .map(|v| v.to_string()),
+        )
+    };
+    let description = || string_value(e.description().ok().map(|d| d.to_string()));
+
+    predicates.iter().all(|predicate| match predicate.key.as_str() {
+        "role" => {
+            let role = string_value(e.role().ok().map(|r| r.to_string()));
+            // Accept generic role names like "button" as well as raw AX roles
+            let mapped = predicate.values.iter().any(|v| {
+                map_generic_role_to_macos_roles(v).contains(&role)
+            });
+            match predicate.op {
+                QueryOp::Equals => mapped || predicate.matches(&role),
+                QueryOp::NotEquals => !mapped && predicate.matches(&role),
+                _ => predicate.matches(&role),
+            }
+        }
+        "subrole" => predicate.matches(&string_value(e.subrole().ok().map(|r| r.to_string()))),
+        "title" | "name" | "label" => predicate.matches(&title()),
+        "value" => predicate.matches(&value()),
+        "description" => predicate.matches(&description()),
+        "id" => predicate.matches(&string_value(e.identifier().ok().map(|i| i.to_string()))),
+        "text" => {
+            let texts = [title(), value(), description()];
+            match predicate.op {
+                // Negated text predicates must hold for every text attribute
+                QueryOp::NotEquals => texts.iter().all(|t| predicate.matches(t)),
+                _ => texts.iter().any(|t| predicate.matches(t)),
+            }
+        }
+        _ => false,
+    })
+}
+
 // Add this helper function after the selector handler
 fn element_contains_text(e: &AXUIElement, text: &str) -> bool {
     // Check immediate element attributes for text
@@ -1,4 +1,4 @@
-use crate::{AutomationError, Selector, UIElement};
+use crate::{AutomationError, MouseButton, Selector, UIElement};
 
 /// The common trait that all platform-specific engines must implement
 pub trait AccessibilityEngine: Send + Sync {
@@ -13,9 +13,41 @@ pub trait AccessibilityEngine: Send + Sync {
     /// Get all running applications
     fn get_applications(&self) -> Result<Vec<UIElement>, AutomationError>;
 
+    /// Get the status items in the menu bar (Wi-Fi, battery, third-party menu extras)
+    fn get_menu_bar_extras(&self) -> Result<Vec<UIElement>, AutomationError>;
+
     /// Get application by name
     fn get_application_by_name(&self, name: &str) -> Result<UIElement, AutomationError>;
 
+    /// Get a running application by bundle identifier
+    fn get_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError>;
+
+    /// Get the bundle identifier of the application with this process id, if it has one
+    fn get_bundle_id(&self, pid: i32) -> Result<Option<String>, AutomationError>;
+
+    /// Post a single hardware key-down or key-up event for a virtual key code
+    fn post_key_event(&self, key_code: u16, key_down: bool) -> Result<(), AutomationError>;
+
+    /// Press the left mouse button at `from`, drag to `to` and release it there
+    fn drag_mouse(&self, from: (f64, f64), to: (f64, f64)) -> Result<(), AutomationError>;
+
+    /// Move the mouse cursor to a screen point without clicking
+    fn move_mouse(&self, to: (f64, f64)) -> Result<(), AutomationError>;
+
+    /// Click `button` `click_count` times at `at` (or where the cursor is), as one double or
+    /// triple click rather than separate single clicks
+    fn click_mouse(&self, at: Option<(f64, f64)>, button: MouseButton, click_count: u32) -> Result<(), AutomationError>;
+
+    /// Press a key or combo such as "ctrl+shift+t" in whatever has keyboard focus
+    fn press_key_combo(&self, key_combo: &str) -> Result<(), AutomationError>;
+
+    /// Type text as Unicode key events into whatever has keyboard focus
+    fn type_text(&self, text: &str) -> Result<(), AutomationError>;
+
+    /// Post key-up for every modifier key and button-up for every mouse button, releasing
+    /// anything left held by an interrupted key press or drag
+    fn release_held_input(&self) -> Result<(), AutomationError>;
+
     /// Find elements using a selector
     fn find_element(
         &self,
@@ -35,6 +67,9 @@ pub trait AccessibilityEngine: Send + Sync {
     /// Open an application by name
     fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError>;
 
+    /// Open an application by bundle identifier and wait until it is accessible
+    fn open_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError>;
+
     /// Open a URL in a specified browser (or default if None)
     fn open_url(&self, url: &str, browser: Option<&str>) -> Result<UIElement, AutomationError>;
 
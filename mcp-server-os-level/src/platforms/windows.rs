@@ -1,56 +1,238 @@
-use crate::operator::element::UIElementImpl;
-use crate::operator::platforms::AccessibilityEngine;
-use crate::operator::{AutomationError, Locator, Selector, UIElement, UIElementAttributes};
+use crate::element::UIElementImpl;
+use crate::platforms::AccessibilityEngine;
+use crate::{AutomationError, ClickResult, Locator, MouseButton, QueryPredicate, Selector, UIElement, UIElementAttributes};
+use serde_json::Value;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use crate::operator::ClickResult;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
 
-pub struct WindowsEngine;
+use ::windows::core::{BSTR, PWSTR};
+use ::windows::Win32::Foundation::{CloseHandle, BOOL, RECT};
+use ::windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+use ::windows::Win32::System::Threading::{OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION};
+use ::windows::Win32::UI::Accessibility::*;
+use ::windows::Win32::UI::Input::KeyboardAndMouse::*;
+use ::windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetSystemMetrics, GetWindowThreadProcessId, SetForegroundWindow, SM_CXVIRTUALSCREEN,
+    SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+};
+
+// Deepest level walked below an application's windows; UIA trees of web content nest deeply
+const MAX_WALK_DEPTH: usize = 64;
+// One notch of the scroll wheel
+const WHEEL_DELTA: f64 = 120.0;
+
+fn platform_error(context: &str, e: ::windows::core::Error) -> AutomationError {
+    AutomationError::PlatformError(format!("{}: {}", context, e))
+}
+
+// UI Automation objects are free-threaded once COM is up in the multithreaded apartment.
+// Threads that never initialized COM still join that apartment implicitly while it exists,
+// but initializing explicitly keeps it alive for as long as any engine thread runs.
+fn ensure_com() {
+    thread_local! {
+        static COM_READY: Cell<bool> = const { Cell::new(false) };
+    }
+    COM_READY.with(|ready| {
+        if !ready.get() {
+            // S_FALSE (already initialized) and RPC_E_CHANGED_MODE (an STA thread) both leave
+            // COM usable on this thread
+            let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+            ready.set(true);
+        }
+    });
+}
+
+// COM interface pointers aren't Send/Sync in the windows crate, but UI Automation client
+// objects created in the MTA may be used from any thread
+#[derive(Clone)]
+struct ThreadSafeAutomation(IUIAutomation);
+unsafe impl Send for ThreadSafeAutomation {}
+unsafe impl Sync for ThreadSafeAutomation {}
+
+#[derive(Clone)]
+struct ThreadSafeElement(IUIAutomationElement);
+unsafe impl Send for ThreadSafeElement {}
+unsafe impl Sync for ThreadSafeElement {}
+
+pub struct WindowsEngine {
+    automation: Arc<ThreadSafeAutomation>,
+    // The desktop element, fetched once so get_root_element can't fail later
+    root: ThreadSafeElement,
+    use_background_apps: bool,
+    activate_app: bool,
+}
 
 impl WindowsEngine {
     pub fn new(use_background_apps: bool, activate_app: bool) -> Result<Self, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        ensure_com();
+        let automation: IUIAutomation = unsafe { CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER) }
+            .map_err(|e| platform_error("failed to create UI Automation client", e))?;
+        let root = unsafe { automation.GetRootElement() }.map_err(|e| platform_error("failed to get the desktop element", e))?;
+        Ok(Self {
+            automation: Arc::new(ThreadSafeAutomation(automation)),
+            root: ThreadSafeElement(root),
+            use_background_apps,
+            activate_app,
+        })
+    }
+
+    fn wrap(&self, node: Node) -> UIElement {
+        UIElement::new(Box::new(WindowsUIElement {
+            node,
+            automation: self.automation.clone(),
+            use_background_apps: self.use_background_apps,
+            activate_app: self.activate_app,
+        }))
+    }
+
+    // Top-level windows grouped into one application per process, in z-order of their
+    // first window
+    fn running_applications(&self) -> Result<Vec<(i32, String)>, AutomationError> {
+        let mut apps: Vec<(i32, String)> = Vec::new();
+        for window in top_level_windows(&self.automation.0, &self.root.0) {
+            let Ok(pid) = (unsafe { window.CurrentProcessId() }) else { continue };
+            if pid <= 0 || apps.iter().any(|(known, _)| *known == pid) {
+                continue;
+            }
+            let name = process_name(pid).unwrap_or_else(|| element_name(&window));
+            apps.push((pid, name));
+        }
+        Ok(apps)
+    }
+
+    fn application_by_pid(&self, pid: i32) -> Result<UIElement, AutomationError> {
+        let name = process_name(pid).unwrap_or_default();
+        if self.activate_app {
+            activate_process(&self.automation.0, pid);
+        }
+        Ok(self.wrap(Node::Application { pid, name }))
     }
 }
 
 impl AccessibilityEngine for WindowsEngine {
     fn get_root_element(&self) -> UIElement {
-        panic!("Windows implementation is not yet available")
+        self.wrap(Node::Element(self.root.clone()))
     }
 
-    fn get_element_by_id(&self, _id: &str) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn get_element_by_id(&self, id: &str) -> Result<UIElement, AutomationError> {
+        self.find_element(&Selector::Id(id.to_string()), None)
     }
 
     fn get_focused_element(&self) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        let focused = unsafe { self.automation.0.GetFocusedElement() }
+            .map_err(|e| platform_error("failed to get the focused element", e))?;
+        Ok(self.wrap(Node::Element(ThreadSafeElement(focused))))
     }
 
     fn get_applications(&self) -> Result<Vec<UIElement>, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        let apps = self.running_applications()?;
+        debug!("found {} applications with windows", apps.len());
+        Ok(apps
+            .into_iter()
+            .map(|(pid, name)| self.wrap(Node::Application { pid, name }))
+            .collect())
     }
 
-    fn get_application_by_name(&self, _name: &str) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
+    fn get_menu_bar_extras(&self) -> Result<Vec<UIElement>, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "menu bar extras are a macOS concept; the Windows notification area isn't exposed yet".to_string(),
         ))
     }
 
-    fn find_elements(
-        &self,
-        _selector: &Selector,
-        _root: Option<&UIElement>,
-    ) -> Result<Vec<UIElement>, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn get_application_by_name(&self, name: &str) -> Result<UIElement, AutomationError> {
+        // Match the executable name ("notepad", "chrome.exe") first, then a window title
+        let wanted = name.trim_end_matches(".exe").to_lowercase();
+        let apps = self.running_applications()?;
+        if let Some((pid, _)) = apps.iter().find(|(_, app)| app.to_lowercase() == wanted) {
+            return self.application_by_pid(*pid);
+        }
+        for window in top_level_windows(&self.automation.0, &self.root.0) {
+            if element_name(&window).to_lowercase() == wanted {
+                if let Ok(pid) = unsafe { window.CurrentProcessId() } {
+                    return self.application_by_pid(pid);
+                }
+            }
+        }
+        Err(AutomationError::ElementNotFound(format!("Application '{}' not found", name)))
+    }
+
+    fn get_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(format!(
+            "bundle identifiers don't exist on Windows, look '{}' up by name instead",
+            bundle_id
+        )))
+    }
+
+    fn get_bundle_id(&self, _pid: i32) -> Result<Option<String>, AutomationError> {
+        Ok(None)
+    }
+
+    // `key_code` is a Windows virtual-key code here
+    fn post_key_event(&self, key_code: u16, key_down: bool) -> Result<(), AutomationError> {
+        send_inputs(&[key_input(VIRTUAL_KEY(key_code), key_down)])
+    }
+
+    fn drag_mouse(&self, from: (f64, f64), to: (f64, f64)) -> Result<(), AutomationError> {
+        debug!("dragging from ({}, {}) to ({}, {})", from.0, from.1, to.0, to.1);
+        send_inputs(&[mouse_move_input(from)])?;
+        std::thread::sleep(Duration::from_millis(50));
+        send_inputs(&[mouse_button_input(MOUSEEVENTF_LEFTDOWN)])?;
+        // Hold briefly so the source recognizes a drag rather than a click
+        std::thread::sleep(Duration::from_millis(150));
+
+        // Move in small steps; drop targets only highlight (and accept) after seeing movement over them
+        const STEPS: u32 = 20;
+        for step in 1..=STEPS {
+            let t = step as f64 / STEPS as f64;
+            send_inputs(&[mouse_move_input((from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t))])?;
+            std::thread::sleep(Duration::from_millis(15));
+        }
+
+        // Let the target register the hover before dropping
+        std::thread::sleep(Duration::from_millis(200));
+        send_inputs(&[mouse_button_input(MOUSEEVENTF_LEFTUP)])
+    }
+
+    fn move_mouse(&self, to: (f64, f64)) -> Result<(), AutomationError> {
+        send_inputs(&[mouse_move_input(to)])
+    }
+
+    fn click_mouse(&self, at: Option<(f64, f64)>, button: MouseButton, click_count: u32) -> Result<(), AutomationError> {
+        let (down, up) = match button {
+            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+        };
+        // Windows counts clicks within the double-click time at one spot as a double click,
+        // so the pairs go out in a single batch
+        let mut inputs: Vec<INPUT> = at.map(mouse_move_input).into_iter().collect();
+        for _ in 0..click_count.max(1) {
+            inputs.extend([mouse_button_input(down), mouse_button_input(up)]);
+        }
+        send_inputs(&inputs)
+    }
+
+    fn press_key_combo(&self, key_combo: &str) -> Result<(), AutomationError> {
+        send_inputs(&key_combo_inputs(key_combo)?)
+    }
+
+    fn type_text(&self, text: &str) -> Result<(), AutomationError> {
+        send_inputs(&unicode_text_inputs(text))
+    }
+
+    fn release_held_input(&self) -> Result<(), AutomationError> {
+        let mut inputs: Vec<INPUT> = [VK_LSHIFT, VK_RSHIFT, VK_LCONTROL, VK_RCONTROL, VK_LMENU, VK_RMENU, VK_LWIN, VK_RWIN]
+            .into_iter()
+            .map(|key| key_input(key, false))
+            .collect();
+        inputs.extend([MOUSEEVENTF_LEFTUP, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_MIDDLEUP].map(mouse_button_input));
+        send_inputs(&inputs)
     }
 
     fn find_element(
@@ -58,163 +240,932 @@ impl AccessibilityEngine for WindowsEngine {
         selector: &Selector,
         root: Option<&UIElement>,
     ) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        self.find_elements(selector, root)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AutomationError::ElementNotFound(format!("Element matching {:?} not found", selector)))
     }
 
-    fn open_application(&self, _app_name: &str) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn find_elements(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+    ) -> Result<Vec<UIElement>, AutomationError> {
+        let start = match root {
+            Some(root) => match root.as_any().downcast_ref::<WindowsUIElement>() {
+                Some(element) => element.node.clone(),
+                None => return Err(AutomationError::InvalidArgument("Root element is not a Windows element".to_string())),
+            },
+            None => Node::Element(self.root.clone()),
+        };
+
+        let mut found = Vec::new();
+        for top in start.walk_roots(&self.automation.0)? {
+            walk(&self.automation.0, &top, 0, MAX_WALK_DEPTH, &mut |element| {
+                if matches_selector(element, selector) {
+                    found.push(element.clone());
+                }
+            })?;
+        }
+        debug!("found {} elements matching {:?}", found.len(), selector);
+        Ok(found
+            .into_iter()
+            .map(|element| self.wrap(Node::Element(ThreadSafeElement(element))))
+            .collect())
     }
 
-    fn open_url(&self, _url: &str, _browser: Option<&str>) -> Result<UIElement, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
+        debug!("opening application: {}", app_name);
+        start_shell(&[app_name])?;
+
+        // Windows take a moment to appear after the process starts
+        for _ in 0..10 {
+            std::thread::sleep(Duration::from_millis(500));
+            if let Ok(app) = self.get_application_by_name(app_name) {
+                return Ok(app);
+            }
+        }
+        Err(AutomationError::Timeout(format!("no window of '{}' appeared after opening it", app_name)))
+    }
+
+    fn open_application_by_bundle_id(&self, bundle_id: &str) -> Result<UIElement, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(format!(
+            "bundle identifiers don't exist on Windows, open '{}' by name instead",
+            bundle_id
+        )))
+    }
+
+    fn open_url(&self, url: &str, browser: Option<&str>) -> Result<UIElement, AutomationError> {
+        match browser {
+            Some(browser) => start_shell(&[browser, url])?,
+            None => start_shell(&[url])?,
+        }
+        std::thread::sleep(Duration::from_millis(1000));
+
+        // The browser comes to the front with the page
+        let window = unsafe { GetForegroundWindow() };
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(window, Some(&mut pid)) };
+        if pid == 0 {
+            return Err(AutomationError::ElementNotFound(format!("no browser window came up for {}", url)));
+        }
+        self.application_by_pid(pid as i32)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// UI Automation has no application element; an application is the set of top-level
+// windows its process owns
+#[derive(Clone)]
+enum Node {
+    Application { pid: i32, name: String },
+    Element(ThreadSafeElement),
+}
+
+impl Node {
+    // Elements a walk over this node starts from
+    fn walk_roots(&self, automation: &IUIAutomation) -> Result<Vec<IUIAutomationElement>, AutomationError> {
+        match self {
+            Node::Element(element) => Ok(vec![element.0.clone()]),
+            Node::Application { pid, .. } => {
+                let root = unsafe { automation.GetRootElement() }
+                    .map_err(|e| platform_error("failed to get the desktop element", e))?;
+                Ok(windows_of(automation, &root, *pid))
+            }
+        }
     }
 }
 
-// Placeholder WindowsUIElement that implements UIElementImpl
-pub struct WindowsUIElement;
+pub struct WindowsUIElement {
+    node: Node,
+    automation: Arc<ThreadSafeAutomation>,
+    use_background_apps: bool,
+    activate_app: bool,
+}
 
 impl Debug for WindowsUIElement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("WindowsUIElement").finish()
+        match &self.node {
+            Node::Application { pid, name } => f
+                .debug_struct("WindowsUIElement")
+                .field("application", name)
+                .field("pid", pid)
+                .finish(),
+            Node::Element(element) => f
+                .debug_struct("WindowsUIElement")
+                .field("role", &ax_role(&element.0))
+                .field("name", &element_name(&element.0))
+                .finish(),
+        }
+    }
+}
+
+impl WindowsUIElement {
+    fn wrap(&self, node: Node) -> UIElement {
+        UIElement::new(Box::new(WindowsUIElement {
+            node,
+            automation: self.automation.clone(),
+            use_background_apps: self.use_background_apps,
+            activate_app: self.activate_app,
+        }))
+    }
+
+    // The UIA element behind this node; an application stands in with its first window
+    fn element(&self) -> Result<IUIAutomationElement, AutomationError> {
+        match &self.node {
+            Node::Element(element) => Ok(element.0.clone()),
+            Node::Application { pid, name } => self
+                .node
+                .walk_roots(&self.automation.0)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| AutomationError::ElementNotFound(format!("{} (pid {}) has no windows", name, pid))),
+        }
+    }
+
+    fn pattern<T: ::windows::core::Interface>(&self, id: UIA_PATTERN_ID) -> Option<T> {
+        let element = self.element().ok()?;
+        unsafe { element.GetCurrentPatternAs::<T>(id) }.ok()
+    }
+
+    fn center(&self) -> Result<(f64, f64), AutomationError> {
+        let (x, y, width, height) = self.bounds()?;
+        Ok((x + width / 2.0, y + height / 2.0))
+    }
+
+    fn click_at_center(&self, down: MOUSE_EVENT_FLAGS, up: MOUSE_EVENT_FLAGS, clicks: usize) -> Result<ClickResult, AutomationError> {
+        let point = self.center()?;
+        send_inputs(&[mouse_move_input(point)])?;
+        for _ in 0..clicks {
+            send_inputs(&[mouse_button_input(down), mouse_button_input(up)])?;
+        }
+        Ok(ClickResult {
+            method: "SendInput".to_string(),
+            coordinates: Some(point),
+            details: format!("Clicked {} time(s) at the element's center with SendInput", clicks),
+        })
     }
 }
 
 impl UIElementImpl for WindowsUIElement {
     fn object_id(&self) -> usize {
-        0
+        let mut hasher = DefaultHasher::new();
+        match &self.node {
+            Node::Application { pid, .. } => ("application", pid).hash(&mut hasher),
+            Node::Element(element) => {
+                let element = &element.0;
+                unsafe { element.CurrentProcessId() }.unwrap_or_default().hash(&mut hasher);
+                ax_role(element).hash(&mut hasher);
+                element_name(element).hash(&mut hasher);
+                automation_id(element).hash(&mut hasher);
+                if let Ok(rect) = unsafe { element.CurrentBoundingRectangle() } {
+                    (rect.left, rect.top, rect.right, rect.bottom).hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish() as usize
     }
 
     fn id(&self) -> Option<String> {
-        None
+        match &self.node {
+            Node::Application { .. } => None,
+            Node::Element(element) => automation_id(&element.0),
+        }
     }
 
     fn role(&self) -> String {
-        "".to_string()
+        match &self.node {
+            Node::Application { .. } => "AXApplication".to_string(),
+            Node::Element(element) => ax_role(&element.0).to_string(),
+        }
     }
 
     fn attributes(&self) -> UIElementAttributes {
+        let element = match &self.node {
+            Node::Application { name, pid } => {
+                let mut properties = HashMap::new();
+                properties.insert("AXRole".to_string(), Some(Value::String("AXApplication".to_string())));
+                properties.insert("ProcessId".to_string(), Some(Value::from(*pid)));
+                return UIElementAttributes {
+                    role: "AXApplication".to_string(),
+                    label: Some(name.clone()),
+                    value: None,
+                    description: None,
+                    properties,
+                };
+            }
+            Node::Element(element) => &element.0,
+        };
+
+        let role = ax_role(element);
+        let value = element_value(element);
+        let mut properties = HashMap::new();
+        properties.insert("AXRole".to_string(), Some(Value::String(role.to_string())));
+        properties.insert("UIAControlType".to_string(), Some(Value::String(control_type_name(element).to_string())));
+        if let Some(id) = automation_id(element) {
+            properties.insert("UIAAutomationId".to_string(), Some(Value::String(id)));
+        }
+        if let Ok(class_name) = unsafe { element.CurrentClassName() } {
+            properties.insert("UIAClassName".to_string(), Some(Value::String(class_name.to_string())));
+        }
+        if let Ok(enabled) = unsafe { element.CurrentIsEnabled() } {
+            properties.insert("AXEnabled".to_string(), Some(Value::Bool(enabled.as_bool())));
+        }
+        if let Ok(focused) = unsafe { element.CurrentHasKeyboardFocus() } {
+            properties.insert("AXFocused".to_string(), Some(Value::Bool(focused.as_bool())));
+        }
+        if let Some(value) = &value {
+            properties.insert("AXValue".to_string(), Some(Value::String(value.clone())));
+        }
+
         UIElementAttributes {
-            role: "".to_string(),
-            label: None,
-            value: None,
-            description: None,
-            properties: std::collections::HashMap::new(),
+            role: role.to_string(),
+            label: Some(element_name(element)).filter(|name| !name.is_empty()),
+            value,
+            description: unsafe { element.CurrentHelpText() }
+                .ok()
+                .map(|help| help.to_string())
+                .filter(|help| !help.is_empty()),
+            properties,
         }
     }
 
     fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        let children = match &self.node {
+            Node::Application { .. } => self.node.walk_roots(&self.automation.0)?,
+            Node::Element(element) => child_elements(&self.automation.0, &element.0)?,
+        };
+        Ok(children
+            .into_iter()
+            .map(|child| self.wrap(Node::Element(ThreadSafeElement(child))))
+            .collect())
     }
 
     fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        let Node::Element(element) = &self.node else {
+            return Ok(None);
+        };
+        let walker = unsafe { self.automation.0.ControlViewWalker() }
+            .map_err(|e| platform_error("failed to create a tree walker", e))?;
+        Ok(unsafe { walker.GetParentElement(&element.0) }
+            .ok()
+            .map(|parent| self.wrap(Node::Element(ThreadSafeElement(parent)))))
     }
 
     fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
+        let rect: RECT = unsafe { self.element()?.CurrentBoundingRectangle() }
+            .map_err(|e| platform_error("failed to get element bounds", e))?;
+        Ok((
+            rect.left as f64,
+            rect.top as f64,
+            (rect.right - rect.left) as f64,
+            (rect.bottom - rect.top) as f64,
         ))
     }
 
-    fn click(&self) -> Result<ClickResult, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
+    fn process_id(&self) -> Result<i32, AutomationError> {
+        match &self.node {
+            Node::Application { pid, .. } => Ok(*pid),
+            Node::Element(element) => unsafe { element.0.CurrentProcessId() }
+                .map_err(|e| platform_error("failed to get the process id", e)),
+        }
+    }
+
+    fn screenshot(&self) -> Result<Vec<u8>, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "element screenshots are not yet available on Windows".to_string(),
         ))
     }
 
+    fn click(&self) -> Result<ClickResult, AutomationError> {
+        if let Node::Application { pid, .. } = &self.node {
+            activate_process(&self.automation.0, *pid);
+            return Ok(ClickResult {
+                method: "SetForegroundWindow".to_string(),
+                coordinates: None,
+                details: "Brought the application's window to the front".to_string(),
+            });
+        }
+
+        // Prefer the control's own pattern, which works even when it's covered or offscreen
+        if let Some(invoke) = self.pattern::<IUIAutomationInvokePattern>(UIA_InvokePatternId) {
+            if unsafe { invoke.Invoke() }.is_ok() {
+                return Ok(ClickResult {
+                    method: "Invoke".to_string(),
+                    coordinates: None,
+                    details: "Used the UI Automation Invoke pattern".to_string(),
+                });
+            }
+        }
+        if let Some(toggle) = self.pattern::<IUIAutomationTogglePattern>(UIA_TogglePatternId) {
+            if unsafe { toggle.Toggle() }.is_ok() {
+                return Ok(ClickResult {
+                    method: "Toggle".to_string(),
+                    coordinates: None,
+                    details: "Used the UI Automation Toggle pattern".to_string(),
+                });
+            }
+        }
+        if let Some(item) = self.pattern::<IUIAutomationSelectionItemPattern>(UIA_SelectionItemPatternId) {
+            if unsafe { item.Select() }.is_ok() {
+                return Ok(ClickResult {
+                    method: "SelectionItem".to_string(),
+                    coordinates: None,
+                    details: "Used the UI Automation SelectionItem pattern".to_string(),
+                });
+            }
+        }
+        self.click_at_center(MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 1)
+    }
+
     fn double_click(&self) -> Result<ClickResult, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        self.click_at_center(MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 2)
     }
 
     fn right_click(&self) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        self.click_at_center(MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 1).map(|_| ())
     }
 
     fn hover(&self) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        send_inputs(&[mouse_move_input(self.center()?)])
     }
 
     fn focus(&self) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        if let Node::Application { pid, .. } = &self.node {
+            activate_process(&self.automation.0, *pid);
+            return Ok(());
+        }
+        unsafe { self.element()?.SetFocus() }.map_err(|e| platform_error("failed to focus element", e))
     }
 
-    fn type_text(&self, _text: &str) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn type_text(&self, text: &str) -> Result<(), AutomationError> {
+        // Setting the value is exact for any text; controls without a Value pattern get
+        // the text typed as Unicode key events instead
+        if self.set_value(text).is_ok() {
+            return Ok(());
+        }
+        self.focus()?;
+        send_inputs(&unicode_text_inputs(text))
     }
 
-    fn press_key(&self, _key: &str) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        let inputs = key_combo_inputs(key)?;
+        let _ = self.focus();
+        send_inputs(&inputs)
     }
 
     fn get_text(&self, max_depth: usize) -> Result<String, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        let mut texts: Vec<String> = Vec::new();
+        for top in self.node.walk_roots(&self.automation.0)? {
+            collect_text(&self.automation.0, &top, max_depth, &mut texts);
+        }
+        Ok(texts.join("\n"))
     }
 
-    fn set_value(&self, _value: &str) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        let pattern = self
+            .pattern::<IUIAutomationValuePattern>(UIA_ValuePatternId)
+            .ok_or_else(|| AutomationError::UnsupportedOperation("element has no Value pattern".to_string()))?;
+        unsafe { pattern.SetValue(&BSTR::from(value)) }.map_err(|e| platform_error("failed to set value", e))
+    }
+
+    fn set_bool_attribute(&self, attribute: &str, _value: bool) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedOperation(format!(
+            "{} is a macOS accessibility attribute with no UI Automation equivalent",
+            attribute
+        )))
+    }
+
+    fn available_actions(&self) -> Result<Vec<String>, AutomationError> {
+        // Named after the macOS actions the handlers ask for
+        let mut actions = Vec::new();
+        let element = self.element()?;
+        let supports = |id: UIA_PATTERN_ID| unsafe { element.GetCurrentPattern(id) }.is_ok();
+        if supports(UIA_InvokePatternId) || supports(UIA_TogglePatternId) || supports(UIA_SelectionItemPatternId) {
+            actions.push("AXPress".to_string());
+        }
+        if supports(UIA_RangeValuePatternId) {
+            actions.push("AXIncrement".to_string());
+            actions.push("AXDecrement".to_string());
+        }
+        actions.push("AXShowMenu".to_string());
+        actions.push("AXRaise".to_string());
+        Ok(actions)
+    }
+
+    fn selected_text_range(&self) -> Result<Option<(usize, usize)>, AutomationError> {
+        Ok(None)
+    }
+
+    fn bounds_for_range(&self, _location: usize, _length: usize) -> Result<Option<(f64, f64, f64, f64)>, AutomationError> {
+        Ok(None)
     }
 
     fn is_enabled(&self) -> Result<bool, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        unsafe { self.element()?.CurrentIsEnabled() }
+            .map(|enabled| enabled.as_bool())
+            .map_err(|e| platform_error("failed to read IsEnabled", e))
     }
 
     fn is_visible(&self) -> Result<bool, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        unsafe { self.element()?.CurrentIsOffscreen() }
+            .map(|offscreen| !offscreen.as_bool())
+            .map_err(|e| platform_error("failed to read IsOffscreen", e))
     }
 
     fn is_focused(&self) -> Result<bool, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+        unsafe { self.element()?.CurrentHasKeyboardFocus() }
+            .map(|focused| focused.as_bool())
+            .map_err(|e| platform_error("failed to read HasKeyboardFocus", e))
     }
 
-    fn perform_action(&self, _action: &str) -> Result<(), AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
+        match action {
+            "AXPress" => self.click().map(|_| ()),
+            "AXShowMenu" => self.right_click(),
+            "AXRaise" => self.focus(),
+            "AXConfirm" => self.press_key("return"),
+            "AXCancel" => self.press_key("escape"),
+            "AXIncrement" | "AXDecrement" => {
+                let pattern = self
+                    .pattern::<IUIAutomationRangeValuePattern>(UIA_RangeValuePatternId)
+                    .ok_or_else(|| AutomationError::UnsupportedOperation("element has no RangeValue pattern".to_string()))?;
+                unsafe {
+                    let step = pattern.CurrentSmallChange().unwrap_or(1.0);
+                    let current = pattern.CurrentValue().map_err(|e| platform_error("failed to read value", e))?;
+                    let next = if action == "AXIncrement" { current + step } else { current - step };
+                    pattern.SetValue(next).map_err(|e| platform_error("failed to set value", e))
+                }
+            }
+            _ => Err(AutomationError::UnsupportedOperation(format!(
+                "{} has no UI Automation equivalent",
+                action
+            ))),
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
-    fn create_locator(&self, _selector: Selector) -> Result<Locator, AutomationError> {
-        Err(AutomationError::UnsupportedPlatform(
-            "Windows implementation is not yet available".to_string(),
-        ))
+    fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError> {
+        let engine = WindowsEngine::new(self.use_background_apps, self.activate_app)?;
+        let root = self.wrap(self.node.clone());
+        Ok(Locator::new(Arc::new(engine), selector).within(root))
+    }
+
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        // Wheel events go to the window under the cursor
+        send_inputs(&[mouse_move_input(self.center()?)])?;
+        let notches = (amount * WHEEL_DELTA).round() as i32;
+        let (flags, delta) = match direction {
+            "up" => (MOUSEEVENTF_WHEEL, notches),
+            "down" => (MOUSEEVENTF_WHEEL, -notches),
+            "right" => (MOUSEEVENTF_HWHEEL, notches),
+            "left" => (MOUSEEVENTF_HWHEEL, -notches),
+            _ => {
+                return Err(AutomationError::InvalidArgument(format!(
+                    "invalid scroll direction: {}",
+                    direction
+                )))
+            }
+        };
+        let mut input = mouse_button_input(flags);
+        input.Anonymous.mi.mouseData = delta;
+        send_inputs(&[input])
     }
 
     fn clone_box(&self) -> Box<dyn UIElementImpl> {
-        Box::new(WindowsUIElement)
+        Box::new(WindowsUIElement {
+            node: self.node.clone(),
+            automation: self.automation.clone(),
+            use_background_apps: self.use_background_apps,
+            activate_app: self.activate_app,
+        })
+    }
+}
+
+// Tree walking
+
+fn child_elements(automation: &IUIAutomation, element: &IUIAutomationElement) -> Result<Vec<IUIAutomationElement>, AutomationError> {
+    let walker = unsafe { automation.ControlViewWalker() }.map_err(|e| platform_error("failed to create a tree walker", e))?;
+    let mut children = Vec::new();
+    let mut child = unsafe { walker.GetFirstChildElement(element) }.ok();
+    while let Some(current) = child {
+        child = unsafe { walker.GetNextSiblingElement(&current) }.ok();
+        children.push(current);
+    }
+    Ok(children)
+}
+
+fn top_level_windows(automation: &IUIAutomation, root: &IUIAutomationElement) -> Vec<IUIAutomationElement> {
+    child_elements(automation, root).unwrap_or_default()
+}
+
+fn windows_of(automation: &IUIAutomation, root: &IUIAutomationElement, pid: i32) -> Vec<IUIAutomationElement> {
+    top_level_windows(automation, root)
+        .into_iter()
+        .filter(|window| unsafe { window.CurrentProcessId() }.map_or(false, |owner| owner == pid))
+        .collect()
+}
+
+// Visit `element` and everything below it down to `max_depth`, depth first in document order
+fn walk(
+    automation: &IUIAutomation,
+    element: &IUIAutomationElement,
+    depth: usize,
+    max_depth: usize,
+    visit: &mut dyn FnMut(&IUIAutomationElement),
+) -> Result<(), AutomationError> {
+    visit(element);
+    if depth >= max_depth {
+        return Ok(());
+    }
+    for child in child_elements(automation, element)? {
+        walk(automation, &child, depth + 1, max_depth, visit)?;
+    }
+    Ok(())
+}
+
+fn collect_text(automation: &IUIAutomation, element: &IUIAutomationElement, max_depth: usize, texts: &mut Vec<String>) {
+    let _ = walk(automation, element, 0, max_depth, &mut |element| {
+        for text in [Some(element_name(element)), element_value(element)].into_iter().flatten() {
+            if !text.is_empty() && !texts.contains(&text) {
+                texts.push(text);
+            }
+        }
+    });
+}
+
+// Selector matching
+
+fn matches_selector(element: &IUIAutomationElement, selector: &Selector) -> bool {
+    match selector {
+        Selector::Role { role, name } => {
+            role_matches(element, role) && name.as_ref().map_or(true, |name| element_name(element) == *name)
+        }
+        Selector::Id(id) => automation_id(element).as_deref() == Some(id.as_str()),
+        // An empty name or text lists everything, as `locator("")` does for a listing
+        Selector::Name(name) => name.is_empty() || element_name(element) == *name,
+        Selector::Text(text) => {
+            let text = text.to_lowercase();
+            text.is_empty()
+                || [Some(element_name(element)), element_value(element)]
+                    .into_iter()
+                    .flatten()
+                    .any(|candidate| candidate.to_lowercase().contains(&text))
+        }
+        Selector::Attributes(attributes) => attributes
+            .iter()
+            .all(|(key, value)| query_attribute(element, key).map_or(false, |actual| actual == *value)),
+        Selector::Query(predicates) => predicates.iter().all(|predicate| predicate_matches(element, predicate)),
+        Selector::Chain(selectors) => selectors.iter().all(|selector| matches_selector(element, selector)),
+        Selector::Path(_) | Selector::Filter(_) => false,
+    }
+}
+
+fn predicate_matches(element: &IUIAutomationElement, predicate: &QueryPredicate) -> bool {
+    if predicate.key.eq_ignore_ascii_case("text") {
+        let candidates = [Some(element_name(element)), element_value(element), query_attribute(element, "description")];
+        return candidates.into_iter().flatten().any(|text| predicate.matches(&text));
+    }
+    predicate.matches(&query_attribute(element, &predicate.key).unwrap_or_default())
+}
+
+fn query_attribute(element: &IUIAutomationElement, key: &str) -> Option<String> {
+    match key.to_lowercase().as_str() {
+        "role" => Some(ax_role(element).to_string()),
+        "subrole" => Some(control_type_name(element).to_string()),
+        "title" | "name" | "label" => Some(element_name(element)),
+        "value" => element_value(element),
+        "description" => unsafe { element.CurrentHelpText() }.ok().map(|help| help.to_string()),
+        "id" => automation_id(element),
+        _ => None,
+    }
+}
+
+fn role_matches(element: &IUIAutomationElement, wanted: &str) -> bool {
+    let role = ax_role(element);
+    let wanted = wanted.trim_start_matches("AX").to_lowercase();
+    role.trim_start_matches("AX").to_lowercase() == wanted
+        || control_type_name(element).to_lowercase() == wanted
+        || (matches!(wanted.as_str(), "textfield" | "input" | "textbox") && matches!(role, "AXTextField" | "AXTextArea"))
+}
+
+// Element properties
+
+fn element_name(element: &IUIAutomationElement) -> String {
+    unsafe { element.CurrentName() }.map(|name| name.to_string()).unwrap_or_default()
+}
+
+fn element_value(element: &IUIAutomationElement) -> Option<String> {
+    let pattern = unsafe { element.GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId) }.ok()?;
+    unsafe { pattern.CurrentValue() }.ok().map(|value| value.to_string())
+}
+
+fn automation_id(element: &IUIAutomationElement) -> Option<String> {
+    unsafe { element.CurrentAutomationId() }
+        .ok()
+        .map(|id| id.to_string())
+        .filter(|id| !id.is_empty())
+}
+
+// Control types under the macOS role names the handlers already classify by
+fn ax_role(element: &IUIAutomationElement) -> &'static str {
+    let Ok(control_type) = (unsafe { element.CurrentControlType() }) else {
+        return "AXUnknown";
+    };
+    match control_type {
+        UIA_ButtonControlTypeId => "AXButton",
+        UIA_SplitButtonControlTypeId => "AXMenuButton",
+        UIA_CheckBoxControlTypeId => "AXCheckBox",
+        UIA_RadioButtonControlTypeId | UIA_TabItemControlTypeId => "AXRadioButton",
+        UIA_ComboBoxControlTypeId => "AXComboBox",
+        UIA_EditControlTypeId => "AXTextField",
+        UIA_DocumentControlTypeId => "AXTextArea",
+        UIA_TextControlTypeId => "AXStaticText",
+        UIA_HyperlinkControlTypeId => "AXLink",
+        UIA_ImageControlTypeId => "AXImage",
+        UIA_ListControlTypeId => "AXList",
+        UIA_ListItemControlTypeId | UIA_DataItemControlTypeId => "AXCell",
+        UIA_TreeControlTypeId => "AXOutline",
+        UIA_TreeItemControlTypeId => "AXRow",
+        UIA_TableControlTypeId | UIA_DataGridControlTypeId => "AXTable",
+        UIA_MenuBarControlTypeId => "AXMenuBar",
+        UIA_MenuControlTypeId => "AXMenu",
+        UIA_MenuItemControlTypeId => "AXMenuItem",
+        UIA_TabControlTypeId => "AXTabGroup",
+        UIA_ToolBarControlTypeId | UIA_AppBarControlTypeId => "AXToolbar",
+        UIA_SliderControlTypeId => "AXSlider",
+        UIA_SpinnerControlTypeId => "AXIncrementor",
+        UIA_ProgressBarControlTypeId => "AXProgressIndicator",
+        UIA_ScrollBarControlTypeId => "AXScrollBar",
+        UIA_ToolTipControlTypeId => "AXHelpTag",
+        UIA_WindowControlTypeId => "AXWindow",
+        UIA_HeaderItemControlTypeId => "AXSortButton",
+        UIA_SeparatorControlTypeId => "AXSplitter",
+        _ => "AXGroup",
+    }
+}
+
+fn control_type_name(element: &IUIAutomationElement) -> &'static str {
+    let Ok(control_type) = (unsafe { element.CurrentControlType() }) else {
+        return "Unknown";
+    };
+    match control_type {
+        UIA_AppBarControlTypeId => "AppBar",
+        UIA_ButtonControlTypeId => "Button",
+        UIA_CalendarControlTypeId => "Calendar",
+        UIA_CheckBoxControlTypeId => "CheckBox",
+        UIA_ComboBoxControlTypeId => "ComboBox",
+        UIA_CustomControlTypeId => "Custom",
+        UIA_DataGridControlTypeId => "DataGrid",
+        UIA_DataItemControlTypeId => "DataItem",
+        UIA_DocumentControlTypeId => "Document",
+        UIA_EditControlTypeId => "Edit",
+        UIA_GroupControlTypeId => "Group",
+        UIA_HeaderControlTypeId => "Header",
+        UIA_HeaderItemControlTypeId => "HeaderItem",
+        UIA_HyperlinkControlTypeId => "Hyperlink",
+        UIA_ImageControlTypeId => "Image",
+        UIA_ListControlTypeId => "List",
+        UIA_ListItemControlTypeId => "ListItem",
+        UIA_MenuBarControlTypeId => "MenuBar",
+        UIA_MenuControlTypeId => "Menu",
+        UIA_MenuItemControlTypeId => "MenuItem",
+        UIA_PaneControlTypeId => "Pane",
+        UIA_ProgressBarControlTypeId => "ProgressBar",
+        UIA_RadioButtonControlTypeId => "RadioButton",
+        UIA_ScrollBarControlTypeId => "ScrollBar",
+        UIA_SemanticZoomControlTypeId => "SemanticZoom",
+        UIA_SeparatorControlTypeId => "Separator",
+        UIA_SliderControlTypeId => "Slider",
+        UIA_SpinnerControlTypeId => "Spinner",
+        UIA_SplitButtonControlTypeId => "SplitButton",
+        UIA_StatusBarControlTypeId => "StatusBar",
+        UIA_TabControlTypeId => "Tab",
+        UIA_TabItemControlTypeId => "TabItem",
+        UIA_TableControlTypeId => "Table",
+        UIA_TextControlTypeId => "Text",
+        UIA_ThumbControlTypeId => "Thumb",
+        UIA_TitleBarControlTypeId => "TitleBar",
+        UIA_ToolBarControlTypeId => "ToolBar",
+        UIA_ToolTipControlTypeId => "ToolTip",
+        UIA_TreeControlTypeId => "Tree",
+        UIA_TreeItemControlTypeId => "TreeItem",
+        UIA_WindowControlTypeId => "Window",
+        _ => "Unknown",
+    }
+}
+
+// Processes and windows
+
+// Executable name without ".exe", e.g. "notepad" - what people call the app by on Windows
+fn process_name(pid: i32) -> Option<String> {
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL::from(false), pid as u32).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut length);
+        let _ = CloseHandle(process);
+        queried.ok()?;
+        let path = String::from_utf16_lossy(&buffer[..length as usize]);
+        std::path::Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+    }
+}
+
+// Bring the process's first window to the front
+fn activate_process(automation: &IUIAutomation, pid: i32) {
+    let Ok(root) = (unsafe { automation.GetRootElement() }) else { return };
+    if let Some(window) = windows_of(automation, &root, pid).into_iter().next() {
+        if let Ok(handle) = unsafe { window.CurrentNativeWindowHandle() } {
+            let _ = unsafe { SetForegroundWindow(handle) };
+        }
+        let _ = unsafe { window.SetFocus() };
+    }
+}
+
+// Hand something to the shell's `start`, which resolves app names on the path, registered
+// apps and URLs the same way the Run dialog does
+fn start_shell(args: &[&str]) -> Result<(), AutomationError> {
+    let status = std::process::Command::new("cmd")
+        .args(["/C", "start", ""])
+        .args(args)
+        .status()
+        .map_err(|e| AutomationError::PlatformError(format!("failed to execute 'start': {}", e)))?;
+    if !status.success() {
+        return Err(AutomationError::PlatformError(format!(
+            "failed to start '{}': exit code {:?}",
+            args.join(" "),
+            status.code()
+        )));
+    }
+    Ok(())
+}
+
+// Input
+
+fn send_inputs(inputs: &[INPUT]) -> Result<(), AutomationError> {
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        // SendInput drops everything when UIPI blocks input to a more privileged window
+        return Err(AutomationError::PermissionDenied(format!(
+            "SendInput delivered {} of {} events; the target may run elevated",
+            sent,
+            inputs.len()
+        )));
+    }
+    Ok(())
+}
+
+fn key_input(key: VIRTUAL_KEY, key_down: bool) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: key,
+                wScan: 0,
+                dwFlags: if key_down { KEYBD_EVENT_FLAGS(0) } else { KEYEVENTF_KEYUP },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn unicode_input(unit: u16, key_down: bool) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: if key_down { KEYEVENTF_UNICODE } else { KEYEVENTF_UNICODE | KEYEVENTF_KEYUP },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn unicode_text_inputs(text: &str) -> Vec<INPUT> {
+    text.encode_utf16()
+        .flat_map(|unit| [unicode_input(unit, true), unicode_input(unit, false)])
+        .collect()
+}
+
+// Modifiers down, the key down and up, modifiers up in reverse
+fn key_combo_inputs(combo: &str) -> Result<Vec<INPUT>, AutomationError> {
+    let (modifiers, key) = parse_key_combo(combo)?;
+    let mut inputs: Vec<INPUT> = modifiers.iter().map(|&modifier| key_input(modifier, true)).collect();
+    inputs.push(key_input(key, true));
+    inputs.push(key_input(key, false));
+    inputs.extend(modifiers.iter().rev().map(|&modifier| key_input(modifier, false)));
+    Ok(inputs)
+}
+
+fn mouse_button_input(flags: MOUSE_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx: 0,
+                dy: 0,
+                mouseData: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+// A move to a screen point, which (unlike SetCursorPos) apps see as real mouse movement.
+// Absolute coordinates are normalized to 0..65535 across the whole virtual desktop.
+fn mouse_move_input((x, y): (f64, f64)) -> INPUT {
+    let (left, top, width, height) = unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN) as f64,
+            GetSystemMetrics(SM_YVIRTUALSCREEN) as f64,
+            GetSystemMetrics(SM_CXVIRTUALSCREEN).max(2) as f64,
+            GetSystemMetrics(SM_CYVIRTUALSCREEN).max(2) as f64,
+        )
+    };
+    let mut input = mouse_button_input(MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK);
+    input.Anonymous.mi.dx = ((x - left) * 65535.0 / (width - 1.0)).round() as i32;
+    input.Anonymous.mi.dy = ((y - top) * 65535.0 / (height - 1.0)).round() as i32;
+    input
+}
+
+// Split a combo like "ctrl+shift+s" into modifier keys and the main key. "cmd"/"command"
+// map to Ctrl, so combos written for macOS do the same thing here.
+fn parse_key_combo(combo: &str) -> Result<(Vec<VIRTUAL_KEY>, VIRTUAL_KEY), AutomationError> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).filter(|part| !part.is_empty()).collect();
+    let (key, modifiers) = parts
+        .split_last()
+        .ok_or_else(|| AutomationError::InvalidArgument("empty key combination".to_string()))?;
+
+    let modifiers = modifiers
+        .iter()
+        .map(|modifier| match modifier.to_lowercase().as_str() {
+            "cmd" | "command" | "ctrl" | "control" => Ok(VK_CONTROL),
+            "shift" => Ok(VK_SHIFT),
+            "alt" | "option" => Ok(VK_MENU),
+            "win" | "windows" | "super" | "meta" => Ok(VK_LWIN),
+            other => Err(AutomationError::InvalidArgument(format!("unknown modifier: {}", other))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((modifiers, virtual_key(key)?))
+}
+
+fn virtual_key(key: &str) -> Result<VIRTUAL_KEY, AutomationError> {
+    let lower = key.to_lowercase();
+    let named = match lower.as_str() {
+        "return" | "enter" => Some(VK_RETURN),
+        "tab" => Some(VK_TAB),
+        "escape" | "esc" => Some(VK_ESCAPE),
+        // "delete" is backspace, as on a Mac keyboard
+        "backspace" | "delete" => Some(VK_BACK),
+        "forwarddelete" | "del" => Some(VK_DELETE),
+        "space" => Some(VK_SPACE),
+        "up" | "uparrow" => Some(VK_UP),
+        "down" | "downarrow" => Some(VK_DOWN),
+        "left" | "leftarrow" => Some(VK_LEFT),
+        "right" | "rightarrow" => Some(VK_RIGHT),
+        "home" => Some(VK_HOME),
+        "end" => Some(VK_END),
+        "pageup" => Some(VK_PRIOR),
+        "pagedown" => Some(VK_NEXT),
+        // On its own, e.g. to open the Start menu's search
+        "win" | "windows" => Some(VK_LWIN),
+        _ => None,
+    };
+    if let Some(key) = named {
+        return Ok(key);
+    }
+
+    // F1-F24 are consecutive
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u16>().ok()) {
+        if (1..=24).contains(&number) {
+            return Ok(VIRTUAL_KEY(VK_F1.0 + number - 1));
+        }
+    }
+
+    // Letters and digits use their uppercase ASCII code as the virtual key
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Ok(VIRTUAL_KEY(c.to_ascii_uppercase() as u16)),
+        // The US-layout punctuation that shortcuts use, e.g. ctrl+= to zoom in
+        (Some('='), None) => Ok(VK_OEM_PLUS),
+        (Some('-'), None) => Ok(VK_OEM_MINUS),
+        (Some(','), None) => Ok(VK_OEM_COMMA),
+        (Some('.'), None) => Ok(VK_OEM_PERIOD),
+        _ => Err(AutomationError::InvalidArgument(format!("unknown key: {}", key))),
     }
 }
@@ -1,3 +1,4 @@
+use crate::AutomationError;
 use std::collections::BTreeMap;
 
 /// Represents ways to locate a UI element
@@ -19,12 +20,147 @@ pub enum Selector {
     Filter(usize), // Uses an ID to reference a filter predicate stored separately
     /// Chain multiple selectors
     Chain(Vec<Selector>),
+    /// Match all of several attribute predicates, e.g. `role=AXButton title~=save`
+    Query(Vec<QueryPredicate>),
+}
+
+/// How a query predicate compares an attribute against its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryOp {
+    /// `key=value` - equal, ignoring case
+    Equals,
+    /// `key!=value` - not equal, ignoring case
+    NotEquals,
+    /// `key~=value` - contains, ignoring case
+    Contains,
+    /// `key^=value` - starts with, ignoring case
+    StartsWith,
+}
+
+/// One `key<op>value` term of a query. Values may be quoted (`title="Save As"`)
+/// and may list alternatives separated by `|` (`role=AXButton|AXLink`).
+///
+/// Keys: `role`, `subrole`, `title` (or `name`/`label`), `value`, `description`,
+/// `id`, and `text` (title, value or description).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryPredicate {
+    pub key: String,
+    pub op: QueryOp,
+    pub values: Vec<String>,
+}
+
+const QUERY_KEYS: [&str; 9] = [
+    "role",
+    "subrole",
+    "title",
+    "name",
+    "label",
+    "value",
+    "description",
+    "id",
+    "text",
+];
+
+impl QueryPredicate {
+    /// Whether an attribute value satisfies this predicate
+    pub fn matches(&self, actual: &str) -> bool {
+        let actual = actual.to_lowercase();
+        let any = |f: &dyn Fn(&str) -> bool| self.values.iter().any(|v| f(&v.to_lowercase()));
+        match self.op {
+            QueryOp::Equals => any(&|v| actual == v),
+            QueryOp::NotEquals => !any(&|v| actual == v),
+            QueryOp::Contains => any(&|v| actual.contains(v)),
+            QueryOp::StartsWith => any(&|v| actual.starts_with(v)),
+        }
+    }
+}
+
+impl Selector {
+    /// Parse a query such as `role=AXButton title~="Save As"` into a [`Selector::Query`]
+    pub fn query(query: &str) -> Result<Selector, AutomationError> {
+        let mut predicates = Vec::new();
+        for term in split_query_terms(query)? {
+            predicates.push(parse_query_term(&term)?);
+        }
+        if predicates.is_empty() {
+            return Err(AutomationError::InvalidArgument(
+                "query must contain at least one key=value term".to_string(),
+            ));
+        }
+        Ok(Selector::Query(predicates))
+    }
+}
+
+// Split on whitespace outside of double quotes, dropping the quotes
+fn split_query_terms(query: &str) -> Result<Vec<String>, AutomationError> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(AutomationError::InvalidArgument(format!(
+            "unterminated quote in query: {}",
+            query
+        )));
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    Ok(terms)
+}
+
+fn parse_query_term(term: &str) -> Result<QueryPredicate, AutomationError> {
+    let eq = term.find('=').ok_or_else(|| {
+        AutomationError::InvalidArgument(format!("expected key=value in query term: {}", term))
+    })?;
+
+    let (key, op) = match &term[..eq] {
+        k if k.ends_with('~') => (&k[..k.len() - 1], QueryOp::Contains),
+        k if k.ends_with('^') => (&k[..k.len() - 1], QueryOp::StartsWith),
+        k if k.ends_with('!') => (&k[..k.len() - 1], QueryOp::NotEquals),
+        k => (k, QueryOp::Equals),
+    };
+
+    let key = key.to_lowercase();
+    if !QUERY_KEYS.contains(&key.as_str()) {
+        return Err(AutomationError::InvalidArgument(format!(
+            "unknown query key '{}', expected one of: {}",
+            key,
+            QUERY_KEYS.join(", ")
+        )));
+    }
+
+    let values: Vec<String> = term[eq + 1..].split('|').map(|v| v.to_string()).collect();
+
+    Ok(QueryPredicate { key, op, values })
 }
 
 impl From<&str> for Selector {
     fn from(s: &str) -> Self {
         // Make common UI roles like "window", "button", etc. default to Role selectors
         // instead of Name selectors
+        // `key=value` terms form a query
+        if let Some(eq) = s.find('=') {
+            let key = s[..eq].trim_end_matches(['~', '^', '!']);
+            if QUERY_KEYS.contains(&key.to_lowercase().as_str()) {
+                if let Ok(query) = Selector::query(s) {
+                    return query;
+                }
+            }
+        }
+
         match s {
             "window" | "button" | "checkbox" | "menu" | "menuitem" | "menubar" | "textfield"
             | "input" => Selector::Role {
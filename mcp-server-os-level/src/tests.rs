@@ -177,6 +177,81 @@ mod tests {
             );
         }
 
+        #[test]
+        #[ignore]
+        fn test_sorted_listing_is_deterministic() {
+            setup_tracing();
+
+            let desktop = match Desktop::new(false, false) {
+                Ok(d) => d,
+                Err(e) => {
+                    println!("Failed to create Desktop automation: {:?}", e);
+                    return;
+                }
+            };
+
+            let app = desktop.application("Finder").unwrap();
+
+            // List the same, unchanged app twice and compare index -> element
+            let list_ids = || -> Vec<String> {
+                app.locator("")
+                    .unwrap()
+                    .all_sorted()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|e| e.id().unwrap_or_default())
+                    .collect()
+            };
+
+            let first = list_ids();
+            let second = list_ids();
+
+            println!("listed {} elements", first.len());
+            assert!(!first.is_empty(), "No elements found in Finder");
+            assert_eq!(first, second, "Index to element mapping changed between lists");
+        }
+
+        #[test]
+        #[ignore]
+        fn test_menu_item_mark_chars() {
+            setup_tracing();
+
+            let desktop = match Desktop::new(false, false) {
+                Ok(d) => d,
+                Err(e) => {
+                    println!("Failed to create Desktop automation: {:?}", e);
+                    return;
+                }
+            };
+
+            // Finder's View menu always has one "as Icons/List/Columns/Gallery" item checked
+            let app = desktop.application("Finder").unwrap();
+            let items = app
+                .locator(crate::Selector::query("role=AXMenuItem").unwrap())
+                .unwrap()
+                .all()
+                .unwrap_or_default();
+            println!("found {} menu items", items.len());
+
+            let checked: Vec<_> = items
+                .iter()
+                .map(|item| item.attributes())
+                .filter(|attrs| {
+                    attrs
+                        .properties
+                        .get("AXMenuItemMarkChar")
+                        .and_then(|v| v.as_ref())
+                        .and_then(|v| v.as_str())
+                        .map_or(false, |mark| !mark.is_empty())
+                })
+                .collect();
+            for attrs in &checked {
+                println!("checked: {:?}", attrs.label);
+            }
+
+            assert!(!checked.is_empty(), "No checked menu items found in Finder");
+        }
+
         #[test]
         #[ignore]
         fn test_find_and_fill_text_inputs() {